@@ -16,7 +16,7 @@ use embedded_graphics::{image::Image, pixelcolor::Rgb565, prelude::*};
 use embedded_hal_bus::spi::ExclusiveDevice;
 use tinybmp::Bmp;
 
-use st7735_embassy::{self, buffer_size, ST7735};
+use st7735_embassy::{self, pixel_count, ST7735};
 
 bind_interrupts!(struct Irqs {
     SPIM3 => spim::InterruptHandler<peripherals::SPI3>;
@@ -39,11 +39,13 @@ async fn main(_spawner: Spawner) {
 
     let dc = Output::new(p.P1_02.degrade(), Level::High, OutputDrive::Standard);
 
-    let mut display = ST7735::<_, _, _, 160, 128, { buffer_size(160, 128) }>::new(
+    let mut display = ST7735::<_, _, [u16; pixel_count(160, 128)]>::new(
         spi_dev,
         dc,
         rst,
         Default::default(),
+        160,
+        128,
     );
     display.init(&mut Delay).await.unwrap();
     display.clear(Rgb565::BLACK).unwrap();
@@ -16,12 +16,9 @@ use embassy_sync::signal::Signal;
 use embassy_time::{Delay, Duration, Ticker};
 use embedded_graphics::{pixelcolor::Rgb565, prelude::*};
 use embedded_hal_bus::spi::ExclusiveDevice;
-use st7735_embassy::{self, Frame, ST7735IF};
-use static_cell::StaticCell;
+use st7735_embassy::{self, static_frame, Frame, Orientation, ST7735IF};
 
 const BUF_SIZE: usize = 160 * 128 * 2;
-static FRAME_A: StaticCell<Frame<BUF_SIZE>> = StaticCell::new();
-static FRAME_B: StaticCell<Frame<BUF_SIZE>> = StaticCell::new();
 static NEXT_FRAME: Signal<ThreadModeRawMutex, &'static mut Frame<BUF_SIZE>> = Signal::new();
 static READY_FRAME: Signal<ThreadModeRawMutex, &'static mut Frame<BUF_SIZE>> = Signal::new();
 
@@ -59,10 +56,10 @@ async fn main(spawner: Spawner) {
     let dc = Output::new(p.P1_02.degrade(), Level::High, OutputDrive::Standard);
     let rst = Output::new(p.P1_01.degrade(), Level::High, OutputDrive::Standard);
 
-    let frame_a = FRAME_A.init(Default::default());
+    let frame_a = static_frame!(160, 128, Orientation::Landscape);
     NEXT_FRAME.signal(frame_a);
 
-    let frame_b = FRAME_B.init(Default::default());
+    let frame_b = static_frame!(160, 128, Orientation::Landscape);
     READY_FRAME.signal(frame_b);
 
     defmt::unwrap!(spawner.spawn(render(spi_dev, dc, rst)));
@@ -19,12 +19,15 @@ use embedded_graphics::{image::Image, pixelcolor::Rgb565, prelude::*};
 use static_cell::StaticCell;
 use tinybmp::Bmp;
 
-use st7735_embassy::{self, buffer_size, ST7735};
+use st7735_embassy::{self, pixel_count, SpiInterface, ST7735};
 
 type SpiDev = SpiDevice<'static, ThreadModeRawMutex, Spim<'static, SPI3>, Output<'static>>;
 // Creatr a `Display` type, so we don´t need to specify the generic multipule times.
-type Display =
-    ST7735<SpiDev, Output<'static>, Output<'static>, 160, 128, { buffer_size(160, 128) }>;
+type Display = ST7735<
+    SpiInterface<SpiDev, Output<'static>>,
+    Output<'static>,
+    [u16; pixel_count(160, 128)],
+>;
 
 bind_interrupts!(struct Irqs {
     SPIM3 => spim::InterruptHandler<peripherals::SPI3>;
@@ -55,7 +58,7 @@ async fn main(spawner: Spawner) {
     let spi_dev = SpiDevice::new(spi_bus, cs_pin);
     let dc = Output::new(p.P1_02.degrade(), Level::High, OutputDrive::Standard);
     let rst = Output::new(p.P1_01.degrade(), Level::High, OutputDrive::Standard);
-    let display = Display::new(spi_dev, dc, rst, Default::default());
+    let display = Display::new(spi_dev, dc, rst, Default::default(), 160, 128);
     unwrap!(spawner.spawn(display_task(display)));
 
     let mut backlight = Output::new(p.P0_13, Level::High, OutputDrive::Standard);
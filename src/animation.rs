@@ -0,0 +1,118 @@
+//! Frame sequence animations streamed from flash.
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{Backlight, Error, Model, WriteOnlyInterface, ST7735IF};
+
+/// Single frame of an `Animation`.
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationFrame<'a> {
+    /// Big endian `Rgb565` pixel data, e.g. from `include_bytes!`.
+    pub data: &'a [u8],
+    /// How long the frame is shown.
+    pub duration_ms: u32,
+}
+
+impl<'a> AnimationFrame<'a> {
+    /// Creates a frame shown for `duration_ms`.
+    #[must_use]
+    pub const fn new(data: &'a [u8], duration_ms: u32) -> Self {
+        Self { data, duration_ms }
+    }
+}
+
+/// Sequence of equally sized frames with per frame durations.
+///
+/// Frames are written straight from their slices to display RAM, so playing
+/// an animation needs no framebuffer no matter how many frames it has.
+#[derive(Clone, Copy, Debug)]
+pub struct Animation<'a> {
+    frames: &'a [AnimationFrame<'a>],
+    width: u16,
+    height: u16,
+}
+
+impl<'a> Animation<'a> {
+    /// Creates an animation of `width` x `height` pixel frames.
+    #[must_use]
+    pub const fn new(frames: &'a [AnimationFrame<'a>], width: u16, height: u16) -> Self {
+        Self {
+            frames,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the frames.
+    #[must_use]
+    pub fn frames(&self) -> &'a [AnimationFrame<'a>] {
+        self.frames
+    }
+
+    /// Returns the frame size as `(width, height)`.
+    #[must_use]
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Total duration of one pass through all frames.
+    #[must_use]
+    pub fn duration_ms(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.duration_ms).sum()
+    }
+
+    /// Index of the frame shown `elapsed_ms` after the start, looping around.
+    ///
+    /// Useful to pick the frame from a monotonic clock, e.g. to draw it with
+    /// `draw_raw_image` of a buffered driver. `None` if the animation is empty.
+    #[must_use]
+    pub fn frame_index_at(&self, elapsed_ms: u64) -> Option<usize> {
+        let duration = self.duration_ms();
+        if duration == 0 {
+            return (!self.frames.is_empty()).then_some(0);
+        }
+        let mut time = (elapsed_ms % u64::from(duration)) as u32;
+        self.frames.iter().position(|frame| {
+            if time < frame.duration_ms {
+                true
+            } else {
+                time -= frame.duration_ms;
+                false
+            }
+        })
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Plays all frames of an animation once with their top left corner at `(x, y)`.
+    ///
+    /// Each frame is written to the area it covers, the rest of the display is
+    /// left alone. The duration of a frame is waited after its transfer, so
+    /// slow transfers stretch the animation. Pass e.g. `embassy_time::Delay`
+    /// as `delay`, and call again to loop.
+    pub async fn play_animation<D>(
+        &mut self,
+        animation: &Animation<'_>,
+        x: u16,
+        y: u16,
+        delay: &mut D,
+    ) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        for frame in animation.frames {
+            self.draw_raw_image(x, y, animation.width, animation.height, frame.data)
+                .await?;
+            delay.delay_ms(frame.duration_ms).await;
+        }
+        Ok(())
+    }
+}
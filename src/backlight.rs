@@ -0,0 +1,50 @@
+//! Backlight control.
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal::pwm::SetDutyCycle;
+
+/// Display backlight with brightness control.
+pub trait Backlight {
+    /// Sets the brightness in percent, values above 100 are clamped.
+    fn set_brightness(&mut self, percent: u8);
+}
+
+/// No managed backlight, e.g. tied to the supply or driven by the application.
+pub struct NoBacklight;
+
+impl Backlight for NoBacklight {
+    fn set_brightness(&mut self, _percent: u8) {}
+}
+
+/// Backlight switched by an output pin, on for any non-zero brightness.
+pub struct BacklightPin<P>(pub P)
+where
+    P: OutputPin<Error = Infallible>;
+
+impl<P> Backlight for BacklightPin<P>
+where
+    P: OutputPin<Error = Infallible>,
+{
+    fn set_brightness(&mut self, percent: u8) {
+        if percent > 0 {
+            self.0.set_high().ok();
+        } else {
+            self.0.set_low().ok();
+        }
+    }
+}
+
+/// Backlight dimmed by a PWM channel, the duty cycle follows the brightness.
+pub struct PwmBacklight<P>(pub P)
+where
+    P: SetDutyCycle<Error = Infallible>;
+
+impl<P> Backlight for PwmBacklight<P>
+where
+    P: SetDutyCycle<Error = Infallible>,
+{
+    fn set_brightness(&mut self, percent: u8) {
+        self.0.set_duty_cycle_percent(percent.min(100)).ok();
+    }
+}
@@ -0,0 +1,18 @@
+//! Alpha blending of `Rgb565` colors.
+use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+
+/// Blends `fg` over `bg` with `alpha` from 0 (only `bg`) to 255 (only `fg`).
+#[must_use]
+pub fn blend(bg: Rgb565, fg: Rgb565, alpha: u8) -> Rgb565 {
+    let alpha = u16::from(alpha);
+    let mix = |bg: u8, fg: u8| -> u8 {
+        let value = u16::from(fg) * alpha + u16::from(bg) * (255 - alpha);
+        // Rounded division by 255
+        ((value + 128 + (value >> 8)) >> 8) as u8
+    };
+    Rgb565::new(
+        mix(bg.r(), fg.r()),
+        mix(bg.g(), fg.g()),
+        mix(bg.b(), fg.b()),
+    )
+}
@@ -0,0 +1,573 @@
+//! Blocking driver for applications without an async executor.
+//!
+//! `BlockingST7735` wraps `ST7735IF` over a `BlockingSpiInterface` and runs
+//! each operation to completion with `embassy_futures::block_on`. Since the
+//! blocking transport and `BlockingDelay` never pend, nothing spins, and the
+//! command, orientation and `Frame` logic is shared with the async driver.
+//! Use `run` for driver methods without a blocking counterpart here.
+//!
+//! `BlockingBufferedST7735` does the same for the `ST7735` framebuffer
+//! driver, requires the `buffered` feature.
+#[cfg(feature = "buffered")]
+use core::borrow::BorrowMut;
+use core::convert::Infallible;
+use core::future::Future;
+
+use embassy_futures::block_on;
+use embedded_graphics_core::pixelcolor::Rgb565;
+#[cfg(feature = "buffered")]
+use embedded_graphics_core::prelude::*;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+
+use crate::instruction::Instruction;
+use crate::model::{self, Model};
+use crate::readback::{DisplayStatus, SelfDiagnostic};
+use crate::{
+    Backlight, BlockingSpiInterface, Config, Error, Frame, FrameRateConfig, Madctl, Mirror,
+    NoBacklight, Orientation, ReadInterface, TearingEffect, WriteOnlyInterface, ST7735IF,
+};
+
+/// Adapts a blocking `embedded_hal::delay::DelayNs` to the async trait the driver takes.
+pub struct BlockingDelay<D>(pub D);
+
+impl<D: embedded_hal::delay::DelayNs> embedded_hal_async::delay::DelayNs for BlockingDelay<D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns);
+    }
+}
+
+/// Blocking ST7735 driver without framebuffer.
+pub struct BlockingST7735<DI, RST, BL = NoBacklight, M = model::ST7735>
+where
+    DI: WriteOnlyInterface,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    inner: ST7735IF<DI, RST, BL, M>,
+}
+
+impl<SPI, DC, RST> BlockingST7735<BlockingSpiInterface<SPI, DC>, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses blocking hardware SPI.
+    pub fn new(spi: SPI, dc: DC, rst: RST, config: Config) -> Self {
+        let di = BlockingSpiInterface::new(spi, dc)
+            .with_dc_timing(config.dc_timing)
+            .with_max_chunk_size(config.max_chunk_size);
+        Self {
+            inner: ST7735IF::with_interface(di, rst, config),
+        }
+    }
+}
+
+impl<DI, RST, BL, M, E> BlockingST7735<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Wraps an async driver, its interface should not pend or `block_on` busy-waits.
+    pub fn from_async(inner: ST7735IF<DI, RST, BL, M>) -> Self {
+        Self { inner }
+    }
+
+    /// Hands the backlight over to the driver, see `set_backlight_brightness`.
+    pub fn with_backlight<B: Backlight>(self, backlight: B) -> BlockingST7735<DI, RST, B, M> {
+        BlockingST7735 {
+            inner: self.inner.with_backlight(backlight),
+        }
+    }
+
+    /// Drives a sibling controller of the ST7735, see `model`.
+    pub fn with_model<O: Model>(self, model: O) -> BlockingST7735<DI, RST, BL, O> {
+        BlockingST7735 {
+            inner: self.inner.with_model(model),
+        }
+    }
+
+    /// Returns the wrapped async driver.
+    pub fn inner_mut(&mut self) -> &mut ST7735IF<DI, RST, BL, M> {
+        &mut self.inner
+    }
+
+    /// Unwraps the async driver.
+    pub fn into_inner(self) -> ST7735IF<DI, RST, BL, M> {
+        self.inner
+    }
+
+    /// Runs any async driver operation to completion, e.g.
+    /// `display.run(|d| d.fade_backlight(0, 500, &mut delay))`.
+    pub fn run<'a, F>(
+        &'a mut self,
+        f: impl FnOnce(&'a mut ST7735IF<DI, RST, BL, M>) -> F,
+    ) -> F::Output
+    where
+        F: Future,
+    {
+        block_on(f(&mut self.inner))
+    }
+
+    /// Runs the init sequence and turns the display on, see `ST7735IF::init`.
+    pub fn init<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        block_on(self.inner.init(&mut BlockingDelay(delay)))
+    }
+
+    /// Whether a write failed since the last initialization, see `ST7735IF::needs_recovery`.
+    pub fn needs_recovery(&self) -> bool {
+        self.inner.needs_recovery()
+    }
+
+    /// Resets and reinitializes the display after a communication failure.
+    pub fn recover<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        block_on(self.inner.recover(&mut BlockingDelay(delay)))
+    }
+
+    /// Resets the controller through the reset pin.
+    pub fn hard_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        block_on(self.inner.hard_reset(&mut BlockingDelay(delay)))
+    }
+
+    /// Sets the backlight brightness in percent, values above 100 are clamped.
+    pub fn set_backlight_brightness(&mut self, percent: u8) {
+        self.inner.set_backlight_brightness(percent);
+    }
+
+    /// Sets the display orientation.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
+        block_on(self.inner.set_orientation(orientation))
+    }
+
+    /// Sets the output mirroring.
+    pub fn set_output_mirror(&mut self, mirror: Mirror) -> Result<(), Error<E>> {
+        block_on(self.inner.set_output_mirror(mirror))
+    }
+
+    /// Writes MADCTL with individually chosen bits.
+    pub fn set_madctl(&mut self, madctl: Madctl) -> Result<(), Error<E>> {
+        block_on(self.inner.set_madctl(madctl))
+    }
+
+    /// Writes a command with its parameters, see `ST7735IF::send_command`.
+    pub fn send_command(
+        &mut self,
+        instruction: Instruction,
+        params: &[u8],
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.send_command(instruction, params))
+    }
+
+    /// Writes a raw command byte with its parameters.
+    pub fn send_raw(&mut self, command: u8, params: &[u8]) -> Result<(), Error<E>> {
+        block_on(self.inner.send_raw(command, params))
+    }
+
+    /// Sets the frame rate control.
+    pub fn set_frame_rate(&mut self, frame_rate: FrameRateConfig) -> Result<(), Error<E>> {
+        block_on(self.inner.set_frame_rate(frame_rate))
+    }
+
+    /// Turns on the tearing effect output.
+    pub fn enable_tearing_effect(&mut self, mode: TearingEffect) -> Result<(), Error<E>> {
+        block_on(self.inner.enable_tearing_effect(mode))
+    }
+
+    /// Turns off the tearing effect output.
+    pub fn disable_tearing_effect(&mut self) -> Result<(), Error<E>> {
+        block_on(self.inner.disable_tearing_effect())
+    }
+
+    /// Sets the global offset of the displayed image.
+    pub fn set_offset(&mut self, dx: u16, dy: u16) {
+        self.inner.set_offset(dx, dy);
+    }
+
+    /// Sets the address window for the display.
+    pub fn set_address_window(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.set_address_window(sx, sy, ex, ey))
+    }
+
+    /// Sets the address window and starts a memory write into it in one batch.
+    pub fn start_window_write(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.start_window_write(sx, sy, ex, ey))
+    }
+
+    /// Starts a memory write into the current address window.
+    pub fn start_memory_write(&mut self) -> Result<(), Error<E>> {
+        block_on(self.inner.start_memory_write())
+    }
+
+    /// Streams big endian `Rgb565` data into a memory write started by `start_memory_write`.
+    pub fn write_memory(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        block_on(self.inner.write_memory(data))
+    }
+
+    /// Fills the whole display RAM with `color`, see `ST7735IF::clear_screen`.
+    pub fn clear_screen(&mut self, color: Rgb565) -> Result<(), Error<E>> {
+        block_on(self.inner.clear_screen(color))
+    }
+
+    /// Fills a rectangle with `color` straight in display RAM.
+    pub fn fill_rect(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        color: Rgb565,
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.fill_rect(x, y, w, h, color))
+    }
+
+    /// Draws a horizontal line straight in display RAM.
+    pub fn hline(&mut self, x: u16, y: u16, len: u16, color: Rgb565) -> Result<(), Error<E>> {
+        block_on(self.inner.hline(x, y, len, color))
+    }
+
+    /// Draws a vertical line straight in display RAM.
+    pub fn vline(&mut self, x: u16, y: u16, len: u16, color: Rgb565) -> Result<(), Error<E>> {
+        block_on(self.inner.vline(x, y, len, color))
+    }
+
+    /// Starts a memory write and streams `colors` into the current address window.
+    pub fn write_pixels<I>(&mut self, colors: I) -> Result<(), Error<E>>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        block_on(self.inner.write_pixels(colors))
+    }
+
+    /// Writes a `w` x `h` big endian `Rgb565` image straight to display RAM at `(x, y)`.
+    pub fn draw_raw_image(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        data: &[u8],
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.draw_raw_image(x, y, w, h, data))
+    }
+
+    /// Transfer an external buffer of `width` x `height` pixels to the LCD display.
+    pub fn flush_buffer(&mut self, width: u16, height: u16, buf: &[u8]) -> Result<(), Error<E>> {
+        block_on(self.inner.flush_buffer(width, height, buf))
+    }
+
+    /// Transfer a frame to the LCD display.
+    pub fn flush_frame<const N: usize>(&mut self, frame: &Frame<N>) -> Result<(), Error<E>> {
+        block_on(self.inner.flush_frame(frame))
+    }
+}
+
+impl<DI, RST, BL, M, E> BlockingST7735<DI, RST, BL, M>
+where
+    DI: ReadInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Reads the display ID, see `ST7735IF::read_id`.
+    pub fn read_id(&mut self) -> Result<[u8; 3], Error<E>> {
+        block_on(self.inner.read_id())
+    }
+
+    /// Reads the display status, see `ST7735IF::read_status`.
+    pub fn read_status(&mut self) -> Result<DisplayStatus, Error<E>> {
+        block_on(self.inner.read_status())
+    }
+
+    /// Reads the self-diagnostic result, see `ST7735IF::read_self_diagnostic`.
+    pub fn read_self_diagnostic(&mut self) -> Result<SelfDiagnostic, Error<E>> {
+        block_on(self.inner.read_self_diagnostic())
+    }
+}
+
+/// Blocking ST7735 driver with an internal framebuffer, requires the `buffered` feature.
+///
+/// Drawing goes to the framebuffer like with `ST7735`, `flush` blocks until
+/// it is transferred.
+#[cfg(feature = "buffered")]
+pub struct BlockingBufferedST7735<DI, RST, BUF, BL = NoBacklight, M = model::ST7735>
+where
+    DI: WriteOnlyInterface,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+    BL: Backlight,
+    M: Model,
+{
+    inner: crate::ST7735<DI, RST, BUF, BL, M>,
+}
+
+#[cfg(feature = "buffered")]
+impl<SPI, DC, RST, const N: usize>
+    BlockingBufferedST7735<BlockingSpiInterface<SPI, DC>, RST, [u16; N]>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses blocking hardware SPI.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is not `width` x `height`, see `pixel_count`, or the size is zero.
+    pub fn new(spi: SPI, dc: DC, rst: RST, config: Config, width: u16, height: u16) -> Self {
+        Self::new_with_buffer(spi, dc, rst, config, width, height, [0; N])
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<SPI, DC, RST, BUF> BlockingBufferedST7735<BlockingSpiInterface<SPI, DC>, RST, BUF>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+{
+    /// Like `new`, but drawing into `buffer`, e.g. from `static_buffer!`.
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` does not hold `width` x `height` pixels or the size is zero.
+    pub fn new_with_buffer(
+        spi: SPI,
+        dc: DC,
+        rst: RST,
+        config: Config,
+        width: u16,
+        height: u16,
+        buffer: BUF,
+    ) -> Self {
+        let di = BlockingSpiInterface::new(spi, dc)
+            .with_dc_timing(config.dc_timing)
+            .with_max_chunk_size(config.max_chunk_size);
+        Self {
+            inner: crate::ST7735::with_interface_and_buffer(di, rst, config, width, height, buffer),
+        }
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST, BUF, BL, M, E> BlockingBufferedST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Wraps an async driver, its interface should not pend or `block_on` busy-waits.
+    pub fn from_async(inner: crate::ST7735<DI, RST, BUF, BL, M>) -> Self {
+        Self { inner }
+    }
+
+    /// Hands the backlight over to the driver, see `set_backlight_brightness`.
+    pub fn with_backlight<B: Backlight>(
+        self,
+        backlight: B,
+    ) -> BlockingBufferedST7735<DI, RST, BUF, B, M> {
+        BlockingBufferedST7735 {
+            inner: self.inner.with_backlight(backlight),
+        }
+    }
+
+    /// Drives a sibling controller of the ST7735, see `model`.
+    pub fn with_model<O: Model>(self, model: O) -> BlockingBufferedST7735<DI, RST, BUF, BL, O> {
+        BlockingBufferedST7735 {
+            inner: self.inner.with_model(model),
+        }
+    }
+
+    /// Returns the wrapped async driver.
+    pub fn inner_mut(&mut self) -> &mut crate::ST7735<DI, RST, BUF, BL, M> {
+        &mut self.inner
+    }
+
+    /// Unwraps the async driver.
+    pub fn into_inner(self) -> crate::ST7735<DI, RST, BUF, BL, M> {
+        self.inner
+    }
+
+    /// Runs any async driver operation to completion, see `BlockingST7735::run`.
+    pub fn run<'a, F>(
+        &'a mut self,
+        f: impl FnOnce(&'a mut crate::ST7735<DI, RST, BUF, BL, M>) -> F,
+    ) -> F::Output
+    where
+        F: Future,
+    {
+        block_on(f(&mut self.inner))
+    }
+
+    /// Runs the init sequence and turns the display on, see `ST7735IF::init`.
+    pub fn init<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        block_on(self.inner.init(&mut BlockingDelay(delay)))
+    }
+
+    /// Whether a write failed since the last initialization, see `ST7735IF::needs_recovery`.
+    pub fn needs_recovery(&self) -> bool {
+        self.inner.needs_recovery()
+    }
+
+    /// Resets and reinitializes the display, then restores it from the internal buffer.
+    pub fn recover<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: embedded_hal::delay::DelayNs,
+    {
+        block_on(self.inner.recover(&mut BlockingDelay(delay)))
+    }
+
+    /// Sets the backlight brightness in percent, values above 100 are clamped.
+    pub fn set_backlight_brightness(&mut self, percent: u8) {
+        self.inner.set_backlight_brightness(percent);
+    }
+
+    /// Sets the display orientation.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
+        block_on(self.inner.set_orientation(orientation))
+    }
+
+    /// Sets the output mirroring.
+    pub fn set_output_mirror(&mut self, mirror: Mirror) -> Result<(), Error<E>> {
+        block_on(self.inner.set_output_mirror(mirror))
+    }
+
+    /// Writes MADCTL with individually chosen bits.
+    pub fn set_madctl(&mut self, madctl: Madctl) -> Result<(), Error<E>> {
+        block_on(self.inner.set_madctl(madctl))
+    }
+
+    /// Writes a command with its parameters, see `ST7735IF::send_command`.
+    pub fn send_command(
+        &mut self,
+        instruction: Instruction,
+        params: &[u8],
+    ) -> Result<(), Error<E>> {
+        block_on(self.inner.send_command(instruction, params))
+    }
+
+    /// Transfers the internal buffer to the LCD display.
+    pub fn flush(&mut self) -> Result<(), Error<E>> {
+        block_on(self.inner.flush())
+    }
+
+    /// Transfers an external buffer of the display size to the LCD display.
+    pub fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
+        block_on(self.inner.flush_buffer(buf))
+    }
+
+    /// Copies a `w` x `h` big endian `Rgb565` image into the buffer at `(x, y)`.
+    pub fn draw_raw_image(&mut self, x: u16, y: u16, w: u16, h: u16, data: &[u8]) {
+        self.inner.draw_raw_image(x, y, w, h, data);
+    }
+
+    /// Sets a pixel color at the given coords, out of bounds coords are ignored.
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: u16) {
+        self.inner.set_pixel(x, y, color);
+    }
+
+    /// Returns the pixel color at the given coords, `None` if they are out of bounds.
+    #[must_use]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        self.inner.get_pixel(x, y)
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST, BUF, BL, M, E> DrawTarget for BlockingBufferedST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+    BL: Backlight,
+    M: Model,
+{
+    type Error = ();
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.inner.draw_iter(pixels)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.inner.clear(color)
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST, BUF, BL, M, E> OriginDimensions for BlockingBufferedST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+    BL: Backlight,
+    M: Model,
+{
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+}
+
+#[cfg(all(test, feature = "buffered"))]
+mod tests {
+    use embedded_graphics_core::pixelcolor::RgbColor;
+
+    use super::*;
+    use crate::mock::{NoDelay, Recorder};
+
+    #[test]
+    fn buffered_driver_flushes_blocking() {
+        let recorder = Recorder::new();
+        let mut display = BlockingBufferedST7735::from_async(crate::ST7735::new_with_buffer(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            2,
+            1,
+            [0; 2],
+        ));
+        let mut delay = NoDelay;
+        display.run(|d| d.power_up(&mut delay)).unwrap();
+        Pixel(Point::new(1, 0), Rgb565::RED)
+            .draw(&mut display)
+            .unwrap();
+        display.flush().unwrap();
+        let (command, data) = recorder.commands("DC").pop().unwrap();
+        assert_eq!(command, Instruction::RAMWR as u8);
+        assert_eq!(data, [0x00, 0x00, 0xF8, 0x00]);
+    }
+}
@@ -0,0 +1,352 @@
+//! Uncompressed BMP images streamed scanline by scanline.
+//!
+//! Supports 16 bit (RGB565 or RGB555), 24 bit and 32 bit images, bottom up
+//! and top down. Rows are read straight from the image data in display
+//! order, so no framebuffer is needed to show a full screen bitmap.
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawU16, Rgb555, Rgb565, Rgb888},
+    prelude::*,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+const FILE_HEADER_LEN: usize = 14;
+/// Offset of the color masks of `BI_BITFIELDS` images.
+const MASKS_OFFSET: usize = FILE_HEADER_LEN + 40;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+/// Pixel layout of a BMP image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Rgb565,
+    Rgb555,
+    Bgr888,
+    Bgra8888,
+}
+
+/// Uncompressed BMP image, e.g. from `include_bytes!`.
+#[derive(Clone, Copy, Debug)]
+pub struct BmpImage<'a> {
+    /// Pixel array
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    format: Format,
+    bottom_up: bool,
+}
+
+impl<'a> BmpImage<'a> {
+    /// Reads the image headers, `None` if the format is not supported or the data is truncated.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let u16_at = |offset: usize| Some(u16::from_le_bytes(*data.get(offset..)?.first_chunk()?));
+        let u32_at = |offset: usize| Some(u32::from_le_bytes(*data.get(offset..)?.first_chunk()?));
+        if data.get(..2)? != b"BM" {
+            return None;
+        }
+        let pixel_offset = u32_at(10)? as usize;
+        let width = u32_at(18)? as i32;
+        let height = u32_at(22)? as i32;
+        let bpp = u16_at(28)?;
+        let compression = u32_at(30)?;
+        let format = match (bpp, compression) {
+            (16, BI_RGB) => Format::Rgb555,
+            (16, BI_BITFIELDS) if u32_at(MASKS_OFFSET)? == 0xF800 => Format::Rgb565,
+            (16, BI_BITFIELDS) if u32_at(MASKS_OFFSET)? == 0x7C00 => Format::Rgb555,
+            (24, BI_RGB) => Format::Bgr888,
+            (32, BI_RGB | BI_BITFIELDS) => Format::Bgra8888,
+            _ => return None,
+        };
+        let image = Self {
+            data: data.get(pixel_offset..)?,
+            width: u16::try_from(width).ok()?,
+            height: u16::try_from(height.unsigned_abs()).ok()?,
+            format,
+            bottom_up: height > 0,
+        };
+        (image.data.len() >= image.stride() * usize::from(image.height)).then_some(image)
+    }
+
+    /// Returns the image size.
+    #[must_use]
+    pub fn size(&self) -> Size {
+        Size::new(u32::from(self.width), u32::from(self.height))
+    }
+
+    /// Bytes per pixel.
+    fn pixel_len(&self) -> usize {
+        match self.format {
+            Format::Rgb565 | Format::Rgb555 => 2,
+            Format::Bgr888 => 3,
+            Format::Bgra8888 => 4,
+        }
+    }
+
+    /// Bytes per row, rows are padded to a multiple of four bytes.
+    fn stride(&self) -> usize {
+        (usize::from(self.width) * self.pixel_len()).next_multiple_of(4)
+    }
+
+    /// Returns the pixels of row `y`, counted from the top.
+    fn row(&self, y: u16) -> impl Iterator<Item = Rgb565> + 'a {
+        let pixel_len = self.pixel_len();
+        let row = if self.bottom_up {
+            self.height - 1 - y
+        } else {
+            y
+        };
+        let start = usize::from(row) * self.stride();
+        let end = start + usize::from(self.width) * pixel_len;
+        let format = self.format;
+        self.data[start..end]
+            .chunks_exact(pixel_len)
+            .map(move |bytes| match format {
+                Format::Rgb565 => RawU16::new(u16::from_le_bytes([bytes[0], bytes[1]])).into(),
+                Format::Rgb555 => {
+                    let raw = u16::from_le_bytes([bytes[0], bytes[1]]);
+                    Rgb555::from(RawU16::new(raw)).into()
+                }
+                Format::Bgr888 | Format::Bgra8888 => {
+                    Rgb888::new(bytes[2], bytes[1], bytes[0]).into()
+                }
+            })
+    }
+
+    /// Decodes the pixels row by row from the top.
+    pub fn pixels(&self) -> impl Iterator<Item = Rgb565> + 'a {
+        let image = *self;
+        (0..self.height).flat_map(move |y| image.row(y))
+    }
+
+    /// Draws the image into a frame with its top left corner at `pos`, clipped to the frame.
+    pub fn draw<const N: usize>(&self, frame: &mut Frame<N>, pos: Point) {
+        for y in 0..self.height {
+            for (x, color) in self.row(y).enumerate() {
+                let dest_x = u16::try_from(pos.x + x as i32);
+                let dest_y = u16::try_from(pos.y + i32::from(y));
+                if let (Ok(x), Ok(y)) = (dest_x, dest_y) {
+                    frame.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Streams a BMP image straight to display RAM at `(x, y)`.
+    ///
+    /// Scanlines are converted to `Rgb565` through a small chunk buffer. The
+    /// image must lie within the display, it is not clipped. Unsupported or
+    /// truncated images are ignored.
+    pub async fn draw_bmp(&mut self, x: u16, y: u16, data: &[u8]) -> Result<(), Error<E>> {
+        let Some(image) = BmpImage::new(data) else {
+            return Ok(());
+        };
+        if image.width == 0 || image.height == 0 {
+            return Ok(());
+        }
+        self.start_window_write(x, y, x + image.width - 1, y + image.height - 1)
+            .await?;
+        self.write_colors(image.pixels()).await
+    }
+}
+
+/// Copies a BMP image into a framebuffer of `size` at `(x, y)`, clipped to it.
+#[cfg(feature = "buffered")]
+pub(crate) fn blit_bmp(buffer: &mut [u16], size: (u16, u16), pos: (u16, u16), data: &[u8]) {
+    use embedded_graphics_core::pixelcolor::raw::RawData;
+
+    if let Some(image) = BmpImage::new(data) {
+        let pixels = image.pixels().map(|color| RawU16::from(color).into_inner());
+        crate::blit_pixels(buffer, size, pos, image.width, pixels);
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<const N: usize> Frame<N> {
+    /// Encodes the frame as a 24 bit BMP image, e.g. to check rendering code on the host.
+    #[must_use]
+    pub fn to_bmp(&self) -> std::vec::Vec<u8> {
+        let stride = (self.width as usize * 3).next_multiple_of(4);
+        let pixel_offset = MASKS_OFFSET as u32;
+        let image_len = (stride * self.height as usize) as u32;
+
+        let mut bmp = std::vec::Vec::with_capacity(MASKS_OFFSET + image_len as usize);
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(pixel_offset + image_len).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&pixel_offset.to_le_bytes());
+        // BITMAPINFOHEADER
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&self.width.to_le_bytes());
+        bmp.extend_from_slice(&self.height.to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&24u16.to_le_bytes());
+        bmp.extend_from_slice(&BI_RGB.to_le_bytes());
+        bmp.extend_from_slice(&image_len.to_le_bytes());
+        // Resolution and palette fields are unused.
+        bmp.extend_from_slice(&[0; 16]);
+
+        // Rows are stored bottom up.
+        for y in (0..self.height).rev() {
+            let row_start = bmp.len();
+            for x in 0..self.width {
+                let color = self
+                    .get_pixel(x as u16, y as u16)
+                    .map_or(Rgb888::BLACK, Rgb888::from);
+                bmp.extend_from_slice(&[color.b(), color.g(), color.r()]);
+            }
+            bmp.resize(row_start + stride, 0);
+        }
+        bmp
+    }
+
+    /// Writes the frame to a 24 bit BMP file, see `to_bmp`.
+    pub fn write_bmp<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bmp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{buffer_size, Orientation};
+
+    /// Builds a BMP with a BITMAPINFOHEADER, color masks if `masks` is given and `pixels` rows.
+    fn bmp(width: i32, height: i32, bpp: u16, masks: Option<[u32; 3]>, pixels: &[u8]) -> Vec<u8> {
+        let compression = if masks.is_some() {
+            BI_BITFIELDS
+        } else {
+            BI_RGB
+        };
+        let pixel_offset = MASKS_OFFSET as u32 + if masks.is_some() { 12 } else { 0 };
+        let mut data = b"BM".to_vec();
+        data.extend((pixel_offset + pixels.len() as u32).to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(pixel_offset.to_le_bytes());
+        data.extend(40u32.to_le_bytes());
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(bpp.to_le_bytes());
+        data.extend(compression.to_le_bytes());
+        data.extend((pixels.len() as u32).to_le_bytes());
+        data.extend([0; 16]);
+        for mask in masks.iter().flatten() {
+            data.extend(mask.to_le_bytes());
+        }
+        data.extend_from_slice(pixels);
+        data
+    }
+
+    const RGB565_MASKS: [u32; 3] = [0xF800, 0x07E0, 0x001F];
+
+    /// 2 x 2 RGB565 rows from the top: red, green / blue, white, padded to 4 bytes.
+    const TOP_ROW: [u8; 4] = [0x00, 0xF8, 0xE0, 0x07];
+    const BOTTOM_ROW: [u8; 4] = [0x1F, 0x00, 0xFF, 0xFF];
+    const COLORS: [Rgb565; 4] = [Rgb565::RED, Rgb565::GREEN, Rgb565::BLUE, Rgb565::WHITE];
+
+    #[test]
+    fn frame_round_trips_through_bmp() {
+        let mut frame = Frame::<{ buffer_size(3, 2) }>::new_black(3, 2, Orientation::Landscape);
+        frame.set_pixel(0, 0, Rgb565::RED);
+        frame.set_pixel(2, 0, Rgb565::WHITE);
+        frame.set_pixel(1, 1, Rgb565::BLUE);
+        let data = frame.to_bmp();
+        let image = BmpImage::new(&data).unwrap();
+        assert_eq!(image.size(), Size::new(3, 2));
+        let expected: Vec<_> = (0..2)
+            .flat_map(|y| (0..3).map(move |x| (x, y)))
+            .map(|(x, y)| frame.get_pixel(x, y).unwrap())
+            .collect();
+        assert_eq!(image.pixels().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn top_down_and_bottom_up_rows() {
+        let top_down = [TOP_ROW, BOTTOM_ROW].concat();
+        let data = bmp(2, -2, 16, Some(RGB565_MASKS), &top_down);
+        let image = BmpImage::new(&data).unwrap();
+        assert_eq!(image.pixels().collect::<Vec<_>>(), COLORS);
+
+        let bottom_up = [BOTTOM_ROW, TOP_ROW].concat();
+        let data = bmp(2, 2, 16, Some(RGB565_MASKS), &bottom_up);
+        let image = BmpImage::new(&data).unwrap();
+        assert_eq!(image.pixels().collect::<Vec<_>>(), COLORS);
+    }
+
+    #[test]
+    fn bitfields_and_rgb_formats() {
+        // RGB555 without masks, then with 5-5-5 masks: red, then white
+        let rgb555 = [0x00, 0x7C, 0xFF, 0x7F];
+        for masks in [None, Some([0x7C00, 0x03E0, 0x001F])] {
+            let data = bmp(2, 1, 16, masks, &rgb555);
+            let image = BmpImage::new(&data).unwrap();
+            assert_eq!(
+                image.pixels().collect::<Vec<_>>(),
+                [Rgb565::RED, Rgb565::WHITE]
+            );
+        }
+        // 32 bit BGRA with masks, alpha ignored
+        let bgra = [0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x80];
+        let masks = Some([0x00FF_0000, 0x0000_FF00, 0x0000_00FF]);
+        let data = bmp(2, 1, 32, masks, &bgra);
+        let image = BmpImage::new(&data).unwrap();
+        assert_eq!(
+            image.pixels().collect::<Vec<_>>(),
+            [Rgb565::BLUE, Rgb565::GREEN]
+        );
+        // 24 bit rows are padded to 4 bytes
+        let bgr = [0x00, 0x00, 0xFF, 0, 0x00, 0xFF, 0x00, 0];
+        let data = bmp(1, -2, 24, None, &bgr);
+        let image = BmpImage::new(&data).unwrap();
+        assert_eq!(
+            image.pixels().collect::<Vec<_>>(),
+            [Rgb565::RED, Rgb565::GREEN]
+        );
+    }
+
+    #[test]
+    fn truncated_and_hostile_headers_are_rejected() {
+        let pixels = [TOP_ROW, BOTTOM_ROW].concat();
+        let data = bmp(2, 2, 16, Some(RGB565_MASKS), &pixels);
+        for len in 0..data.len() {
+            assert!(BmpImage::new(&data[..len]).is_none(), "{len} bytes");
+        }
+        let cases = [
+            // Not a BMP
+            [b"MB".as_slice(), &data[2..]].concat(),
+            // 8 bit palette images are not supported
+            bmp(2, 2, 8, None, &pixels),
+            // Unknown 16 bit masks
+            bmp(2, 2, 16, Some([0xF000, 0x0F00, 0x00F0]), &pixels),
+            // Negative width
+            bmp(-2, 2, 16, Some(RGB565_MASKS), &pixels),
+            // Height whose magnitude does not fit
+            bmp(2, i32::MIN, 16, Some(RGB565_MASKS), &pixels),
+            // Width beyond u16
+            bmp(0x1_0000, 1, 16, Some(RGB565_MASKS), &pixels),
+            // Size larger than the pixel data
+            bmp(2, 3, 16, Some(RGB565_MASKS), &pixels),
+        ];
+        for (i, case) in cases.iter().enumerate() {
+            assert!(BmpImage::new(case).is_none(), "case {i}");
+        }
+        // Pixel offset past the end of the data
+        let mut offset = data.clone();
+        offset[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(BmpImage::new(&offset).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! Cooperative rendering in bounded slices of work.
+//!
+//! Drawing into a framebuffer is synchronous and can't be preempted by other
+//! tasks on the same executor. `render_budgeted` runs queued drawing jobs
+//! only until a pixel budget is used up, so a render task can yield between
+//! slices and keep the latency of other tasks bounded.
+//!
+//! ```ignore
+//! let mut background = |t: &mut PixelCounter<'_, _>| t.clear(Rgb565::BLACK);
+//! let mut label = |t: &mut PixelCounter<'_, _>| text.draw(t).map(drop);
+//! let mut queue = [&mut background as Job<_>, &mut label].into_iter();
+//! while !render_budgeted(&mut frame, 4096, &mut queue)?.complete {
+//!     yield_now().await;
+//! }
+//! ```
+use embedded_graphics_core::{draw_target::DrawTarget, prelude::*, primitives::Rectangle};
+
+/// Outcome of a `render_budgeted` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BudgetReport {
+    /// Pixels drawn by the jobs run in this call.
+    pub pixels: u32,
+    /// Number of jobs run in this call.
+    pub jobs: usize,
+    /// Whether the work queue ran empty.
+    pub complete: bool,
+}
+
+/// Drawing job queued for `render_budgeted`, jobs of different closure types
+/// can share one queue.
+pub type Job<'j, D> =
+    &'j mut dyn FnMut(&mut PixelCounter<'_, D>) -> Result<(), <D as DrawTarget>::Error>;
+
+/// Runs jobs from `work_queue` until `budget_pixels` pixels have been drawn.
+///
+/// Jobs are not interrupted, the last job run may exceed the budget by its
+/// own size, so large drawings should be split into several jobs. Jobs left in
+/// the queue are run by the next call.
+pub fn render_budgeted<'j, D, I>(
+    target: &mut D,
+    budget_pixels: u32,
+    work_queue: &mut I,
+) -> Result<BudgetReport, D::Error>
+where
+    D: DrawTarget + 'j,
+    I: Iterator<Item = Job<'j, D>>,
+{
+    let mut counter = PixelCounter::new(target);
+    let mut jobs = 0;
+    let complete = loop {
+        if counter.pixels() >= budget_pixels {
+            break false;
+        }
+        let Some(job) = work_queue.next() else {
+            break true;
+        };
+        job(&mut counter)?;
+        jobs += 1;
+    };
+    Ok(BudgetReport {
+        pixels: counter.pixels(),
+        jobs,
+        complete,
+    })
+}
+
+/// Draw target adapter counting the pixels drawn through it.
+///
+/// Fills count the pixels of the requested area.
+pub struct PixelCounter<'a, D> {
+    target: &'a mut D,
+    pixels: u32,
+}
+
+impl<'a, D: DrawTarget> PixelCounter<'a, D> {
+    pub fn new(target: &'a mut D) -> Self {
+        Self { target, pixels: 0 }
+    }
+
+    /// Pixels drawn so far.
+    pub fn pixels(&self) -> u32 {
+        self.pixels
+    }
+
+    fn count_area(&mut self, area: &Rectangle) {
+        let count = area.size.width.saturating_mul(area.size.height);
+        self.pixels = self.pixels.saturating_add(count);
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for PixelCounter<'_, D> {
+    type Error = D::Error;
+    type Color = D::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut count = 0u32;
+        let result = self
+            .target
+            .draw_iter(pixels.into_iter().inspect(|_| count += 1));
+        self.pixels = self.pixels.saturating_add(count);
+        result
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.count_area(area);
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.count_area(area);
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.count_area(&self.target.bounding_box());
+        self.target.clear(color)
+    }
+}
+
+impl<D: DrawTarget> Dimensions for PixelCounter<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor};
+
+    use super::*;
+    use crate::{buffer_size, Frame, Orientation};
+
+    #[test]
+    fn jobs_of_different_types_share_a_queue() {
+        let mut frame = Frame::<{ buffer_size(4, 4) }>::new_black(4, 4, Orientation::Landscape);
+        let mut clear = |t: &mut PixelCounter<'_, _>| t.clear(Rgb565::BLUE);
+        let color = Rgb565::RED;
+        let mut dot = move |t: &mut PixelCounter<'_, _>| Pixel(Point::new(1, 1), color).draw(t);
+        let mut queue = [&mut clear as Job<'_, _>, &mut dot].into_iter();
+
+        let report = render_budgeted(&mut frame, 16, &mut queue).unwrap();
+        assert_eq!(
+            report,
+            BudgetReport {
+                pixels: 16,
+                jobs: 1,
+                complete: false,
+            }
+        );
+        let report = render_budgeted(&mut frame, 16, &mut queue).unwrap();
+        assert_eq!(
+            report,
+            BudgetReport {
+                pixels: 1,
+                jobs: 1,
+                complete: true,
+            }
+        );
+        assert_eq!(frame.get_pixel(1, 1), Some(Rgb565::RED));
+        assert_eq!(frame.get_pixel(0, 0), Some(Rgb565::BLUE));
+    }
+}
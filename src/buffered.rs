@@ -0,0 +1,655 @@
+//! Driver with an internal framebuffer, requires the `buffered` feature.
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec};
+use core::borrow::BorrowMut;
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    pixelcolor::{
+        raw::{RawData, RawU16},
+        Rgb565,
+    },
+    prelude::*,
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+use crate::blend::blend;
+use crate::bmp::blit_bmp;
+use crate::instruction::Instruction;
+use crate::interface::{SpiInterface, WriteOnlyInterface};
+use crate::model::{self, Model};
+use crate::rle::blit_rle;
+
+use crate::{
+    blit_raw_image, pixel_count, pixel_index, transform, Backlight, Config, Error, FrameRateConfig,
+    Madctl, Mirror, NoBacklight, Orientation, OutOfBounds, SyncSignal, TearingEffect, ST7735IF,
+};
+
+/// Async ST7735 LCD display driver with an internal framebuffer.
+///
+/// `BUF` holds the native `Rgb565` pixels of the `width` x `height` logical
+/// size in `Config::orientation`, width and height swap when
+/// `set_orientation` switches between portrait and landscape. It can be
+/// owned, e.g. `[u16; N]`, borrowed from a static, e.g. from `static_buffer!`
+/// to keep 40 KB at 160 x 128 off the stack, or boxed, see `HeapST7735`.
+pub struct ST7735<DI, RST, BUF, BL = NoBacklight, M = model::ST7735>
+where
+    DI: WriteOnlyInterface,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+    BUF: BorrowMut<[u16]>,
+{
+    iface: ST7735IF<DI, RST, BL, M>,
+    /// Logical size in the current orientation
+    size: (u16, u16),
+    /// Native `Rgb565` pixel values, swapped to big endian chunk by chunk while flushing.
+    buffer: BUF,
+}
+
+/// `ST7735` with a framebuffer allocated at runtime, so the same binary can
+/// drive panels of different sizes. Requires the `alloc` feature.
+#[cfg(feature = "alloc")]
+pub type HeapST7735<DI, RST, BL = NoBacklight, M = model::ST7735> =
+    ST7735<DI, RST, Box<[u16]>, BL, M>;
+
+impl<SPI, DC, RST, const N: usize> ST7735<SpiInterface<SPI, DC>, RST, [u16; N]>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses hardware SPI.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is not `width` x `height`, see `pixel_count`, or the size is zero.
+    pub fn new(spi: SPI, dc: DC, rst: RST, config: Config, width: u16, height: u16) -> Self {
+        Self::new_with_buffer(spi, dc, rst, config, width, height, [0; N])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<SPI, DC, RST> HeapST7735<SpiInterface<SPI, DC>, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses hardware SPI, with a framebuffer
+    /// allocated for `width` x `height`.
+    ///
+    /// # Panics
+    ///
+    /// If the size is zero.
+    pub fn new_boxed(spi: SPI, dc: DC, rst: RST, config: Config, width: u16, height: u16) -> Self {
+        let buffer = vec![0; pixel_count(width, height)].into_boxed_slice();
+        Self::new_with_buffer(spi, dc, rst, config, width, height, buffer)
+    }
+}
+
+impl<SPI, DC, RST, BUF> ST7735<SpiInterface<SPI, DC>, RST, BUF>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+{
+    /// Like `new`, but drawing into `buffer`, e.g. from `static_buffer!`.
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` does not hold `width` x `height` pixels or the size is zero.
+    pub fn new_with_buffer(
+        spi: SPI,
+        dc: DC,
+        rst: RST,
+        config: Config,
+        width: u16,
+        height: u16,
+        buffer: BUF,
+    ) -> Self {
+        let di = SpiInterface::new(spi, dc)
+            .with_dc_timing(config.dc_timing)
+            .with_max_chunk_size(config.max_chunk_size);
+        Self::with_interface_and_buffer(di, rst, config, width, height, buffer)
+    }
+}
+
+impl<DI, RST, E, const N: usize> ST7735<DI, RST, [u16; N]>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses the given display interface.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is not `width` x `height`, see `pixel_count`, or the size is zero.
+    pub fn with_interface(di: DI, rst: RST, config: Config, width: u16, height: u16) -> Self {
+        Self::with_interface_and_buffer(di, rst, config, width, height, [0; N])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<DI, RST, E> HeapST7735<DI, RST>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Like `with_interface`, with a framebuffer allocated for `width` x `height`.
+    ///
+    /// # Panics
+    ///
+    /// If the size is zero.
+    pub fn with_interface_boxed(di: DI, rst: RST, config: Config, width: u16, height: u16) -> Self {
+        let buffer = vec![0; pixel_count(width, height)].into_boxed_slice();
+        Self::with_interface_and_buffer(di, rst, config, width, height, buffer)
+    }
+}
+
+impl<DI, RST, BUF, E> ST7735<DI, RST, BUF>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BUF: BorrowMut<[u16]>,
+{
+    /// Like `with_interface`, but drawing into `buffer`, which is cleared to black.
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` does not hold `width` x `height` pixels or the size is zero.
+    pub fn with_interface_and_buffer(
+        di: DI,
+        rst: RST,
+        config: Config,
+        width: u16,
+        height: u16,
+        mut buffer: BUF,
+    ) -> Self {
+        assert!(
+            width > 0 && height > 0,
+            "Invalid size: width and height must not be zero!"
+        );
+        let pixels = buffer.borrow_mut();
+        assert!(
+            pixels.len() == pixel_count(width, height),
+            "Invalid buffer: its length must be equal to width x height!"
+        );
+        pixels.fill(0);
+        Self {
+            iface: ST7735IF::with_interface(di, rst, config),
+            size: (width, height),
+            buffer,
+        }
+    }
+}
+
+impl<DI, RST, BL, M, BUF, E> ST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+    BUF: BorrowMut<[u16]>,
+{
+    /// Hands the backlight over to the driver, see `set_backlight_brightness`.
+    pub fn with_backlight<B: Backlight>(self, backlight: B) -> ST7735<DI, RST, BUF, B, M> {
+        ST7735 {
+            iface: self.iface.with_backlight(backlight),
+            size: self.size,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Drives a sibling controller of the ST7735, see `model`.
+    pub fn with_model<O: Model>(self, model: O) -> ST7735<DI, RST, BUF, BL, O> {
+        ST7735 {
+            iface: self.iface.with_model(model),
+            size: self.size,
+            buffer: self.buffer,
+        }
+    }
+
+    /// Sets the backlight brightness in percent, values above 100 are clamped.
+    pub fn set_backlight_brightness(&mut self, percent: u8) {
+        self.iface.set_backlight_brightness(percent);
+    }
+
+    /// Returns the backlight brightness in percent.
+    pub fn backlight_brightness(&self) -> u8 {
+        self.iface.backlight_brightness()
+    }
+
+    /// Ramps the backlight brightness to `to_percent`, see `ST7735IF::fade_backlight`.
+    pub async fn fade_backlight<D>(&mut self, to_percent: u8, duration_ms: u32, delay: &mut D)
+    where
+        D: DelayNs,
+    {
+        self.iface
+            .fade_backlight(to_percent, duration_ms, delay)
+            .await;
+    }
+
+    /// Runs commands to initialize the display.
+    pub async fn init<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.iface.init(delay).await?;
+
+        Ok(())
+    }
+
+    /// Resets and configures the display with the output off, see `ST7735IF::power_up`.
+    pub async fn power_up<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.iface.power_up(delay).await
+    }
+
+    /// Flushes the internal buffer and turns the display output on.
+    ///
+    /// Flushing first means the first visible frame is the buffer contents
+    /// instead of uninitialized display RAM.
+    pub async fn turn_on<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.flush().await?;
+        self.iface.turn_on(delay).await
+    }
+
+    /// Whether a write failed since the last initialization, see `ST7735IF::needs_recovery`.
+    pub fn needs_recovery(&self) -> bool {
+        self.iface.needs_recovery()
+    }
+
+    /// Resets and reinitializes the display, then restores it from the internal buffer.
+    ///
+    /// Use after a communication failure, e.g. when `needs_recovery` is set.
+    pub async fn recover<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.iface.power_up(delay).await?;
+        self.turn_on(delay).await
+    }
+
+    /// Transfer the internal buffer to the LCD display.
+    ///
+    /// The buffer is borrowed for the whole transfer, for drawing during a
+    /// flush use `Frame`s with `ST7735IF::flush_double_buffered`.
+    pub async fn flush(&mut self) -> Result<(), Error<E>> {
+        self.iface
+            .start_window_write(0, 0, self.size.0 - 1, self.size.1 - 1)
+            .await?;
+        self.iface.write_words(self.buffer.borrow()).await
+    }
+
+    /// Sets the full window and starts a memory write for `flush_pixels`.
+    pub(crate) async fn begin_flush(&mut self) -> Result<(), Error<E>> {
+        self.iface
+            .start_window_write(0, 0, self.size.0 - 1, self.size.1 - 1)
+            .await
+    }
+
+    /// Writes up to `len` buffer pixels from `start` on, returns the number written.
+    pub(crate) async fn flush_pixels(
+        &mut self,
+        start: usize,
+        len: usize,
+    ) -> Result<usize, Error<E>> {
+        let words = self.buffer.borrow().get(start..).unwrap_or_default();
+        let len = len.min(words.len());
+        self.iface.write_words(&words[..len]).await?;
+        Ok(len)
+    }
+
+    /// Waits for the next sync event, then transfers the internal buffer to the LCD display.
+    pub async fn flush_synced<S>(&mut self, sync: &mut S) -> Result<(), Error<E>>
+    where
+        S: SyncSignal<Error = Infallible>,
+    {
+        sync.wait_for_sync().await.map_err(Error::Pin)?;
+        self.flush().await
+    }
+
+    /// Transfer the external buffer to the LCD display.
+    pub async fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
+        self.iface.flush_buffer(self.size.0, self.size.1, buf).await
+    }
+
+    /// Copies a `w` x `h` big endian `Rgb565` image, e.g. from `include_bytes!`, into the buffer at `(x, y)`.
+    ///
+    /// The image is clipped to the buffer.
+    pub fn draw_raw_image(&mut self, x: u16, y: u16, w: u16, h: u16, data: &[u8]) {
+        blit_raw_image(
+            self.buffer.borrow_mut(),
+            self.size,
+            (x, y),
+            (w, h, data),
+            None,
+        );
+    }
+
+    /// Like `draw_raw_image`, but pixels of the `key` color are transparent.
+    pub fn draw_raw_image_keyed(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        data: &[u8],
+        key: Rgb565,
+    ) {
+        blit_raw_image(
+            self.buffer.borrow_mut(),
+            self.size,
+            (x, y),
+            (w, h, data),
+            Some(RawU16::from(key).into_inner()),
+        );
+    }
+
+    /// Copies an uncompressed BMP image, see `bmp`, into the buffer at `(x, y)`.
+    ///
+    /// The image is clipped to the buffer.
+    pub fn draw_bmp(&mut self, x: u16, y: u16, data: &[u8]) {
+        blit_bmp(self.buffer.borrow_mut(), self.size, (x, y), data);
+    }
+
+    /// Decodes a run length encoded image, see `rle`, into the buffer at `(x, y)`.
+    ///
+    /// The image is clipped to the buffer.
+    pub fn draw_rle(&mut self, x: u16, y: u16, data: &[u8]) {
+        blit_rle(self.buffer.borrow_mut(), self.size, (x, y), data);
+    }
+
+    /// Decodes a QOI image into the buffer at `(x, y)`.
+    ///
+    /// The image is clipped to the buffer.
+    #[cfg(feature = "qoi")]
+    pub fn draw_qoi(&mut self, x: u16, y: u16, data: &[u8]) {
+        crate::qoi::blit_qoi(self.buffer.borrow_mut(), self.size, (x, y), data);
+    }
+
+    /// Sets a pixel color at the given coords, out of bounds coords are ignored.
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: u16) {
+        self.try_set_pixel(x, y, color).ok();
+    }
+
+    /// Sets a pixel color at the given coords, failing if they are out of bounds.
+    pub fn try_set_pixel(&mut self, x: u16, y: u16, color: u16) -> Result<(), OutOfBounds> {
+        let pixel = pixel_index(self.size, x, y)
+            .and_then(|idx| self.buffer.borrow_mut().get_mut(idx))
+            .ok_or(OutOfBounds { x, y })?;
+        *pixel = color;
+        Ok(())
+    }
+
+    /// Blends a color over the pixel at the given coords with `alpha` from 0 to 255.
+    pub fn blend_pixel(&mut self, x: u16, y: u16, color: Rgb565, alpha: u8) {
+        if let Some(bg) = self.get_pixel(x, y) {
+            let color = blend(bg, color, alpha);
+            self.set_pixel(x, y, RawU16::from(color).into_inner());
+        }
+    }
+
+    /// Returns the pixel color at the given coords, `None` if they are out of bounds.
+    #[must_use]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        let idx = pixel_index(self.size, x, y)?;
+        self.buffer
+            .borrow()
+            .get(idx)
+            .map(|&raw| RawU16::new(raw).into())
+    }
+
+    /// Returns the display interface.
+    pub fn interface_mut(&mut self) -> &mut DI {
+        self.iface.interface_mut()
+    }
+
+    /// Sets the global offset of the displayed image
+    pub fn set_offset(&mut self, dx: u16, dy: u16) {
+        self.iface.set_offset(dx, dy);
+    }
+
+    /// Writes the effective driver configuration, see `ST7735IF::dump_config`.
+    pub fn dump_config<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        self.iface.dump_config(w)?;
+        writeln!(w, "framebuffer: {}x{}", self.size.0, self.size.1)
+    }
+
+    /// Sets the display orientation.
+    ///
+    /// Width and height are swapped when the orientation switches between
+    /// portrait and landscape. The buffer keeps its contents, which no longer
+    /// match the new size then, so redraw it before the next flush.
+    pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
+        let was_landscape = self.iface.orientation.is_landscape();
+        let result = self.iface.set_orientation(orientation).await;
+        self.update_size(was_landscape);
+        result
+    }
+
+    /// Returns the display orientation.
+    pub fn orientation(&self) -> Orientation {
+        self.iface.orientation
+    }
+
+    /// Swaps width and height if the orientation switched between portrait and landscape.
+    fn update_size(&mut self, was_landscape: bool) {
+        if self.iface.orientation.is_landscape() != was_landscape {
+            self.size = (self.size.1, self.size.0);
+        }
+    }
+
+    /// Sets the output mirroring, applied on top of the orientation.
+    pub async fn set_output_mirror(&mut self, mirror: Mirror) -> Result<(), Error<E>> {
+        self.iface.set_output_mirror(mirror).await
+    }
+
+    /// Writes MADCTL with individually chosen bits, see `ST7735IF::set_madctl`.
+    ///
+    /// Width and height follow the MV bit like for `set_orientation`.
+    pub async fn set_madctl(&mut self, madctl: Madctl) -> Result<(), Error<E>> {
+        let was_landscape = self.iface.orientation.is_landscape();
+        let result = self.iface.set_madctl(madctl).await;
+        self.update_size(was_landscape);
+        result
+    }
+
+    /// Writes a command with its parameters, see `ST7735IF::send_command`.
+    pub async fn send_command(
+        &mut self,
+        instruction: Instruction,
+        params: &[u8],
+    ) -> Result<(), Error<E>> {
+        self.iface.send_command(instruction, params).await
+    }
+
+    /// Writes a raw command byte with its parameters, see `ST7735IF::send_raw`.
+    pub async fn send_raw(&mut self, command: u8, params: &[u8]) -> Result<(), Error<E>> {
+        self.iface.send_raw(command, params).await
+    }
+
+    /// Sets the frame rate control, see `ST7735IF::set_frame_rate`.
+    pub async fn set_frame_rate(&mut self, frame_rate: FrameRateConfig) -> Result<(), Error<E>> {
+        self.iface.set_frame_rate(frame_rate).await
+    }
+
+    /// Turns on the tearing effect output, see `ST7735IF::enable_tearing_effect`.
+    pub async fn enable_tearing_effect(&mut self, mode: TearingEffect) -> Result<(), Error<E>> {
+        self.iface.enable_tearing_effect(mode).await
+    }
+
+    /// Turns off the tearing effect output.
+    pub async fn disable_tearing_effect(&mut self) -> Result<(), Error<E>> {
+        self.iface.disable_tearing_effect().await
+    }
+
+    /// Maps a logical point to panel-native coordinates, see `transform::logical_to_physical`.
+    pub fn logical_to_physical(&self, x: u16, y: u16) -> (u16, u16) {
+        let bits = transform::madctl(self.iface.orientation, self.iface.mirror);
+        transform::logical_to_physical_bits(bits, self.size, (x, y))
+    }
+
+    /// Maps a panel-native point to logical coordinates, see `transform::physical_to_logical`.
+    pub fn physical_to_logical(&self, x: u16, y: u16) -> (u16, u16) {
+        let bits = transform::madctl(self.iface.orientation, self.iface.mirror);
+        transform::physical_to_logical_bits(bits, self.size, (x, y))
+    }
+}
+
+impl<DI, RST, BL, M, BUF, E> DrawTarget for ST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+    BUF: BorrowMut<[u16]>,
+{
+    type Error = ();
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u16, pos.y as u16, RawU16::from(color).into_inner());
+            });
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.buffer
+            .borrow_mut()
+            .fill(RawU16::from(color).into_inner());
+        Ok(())
+    }
+}
+
+impl<DI, RST, BL, M, BUF, E> OriginDimensions for ST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+    BUF: BorrowMut<[u16]>,
+{
+    fn size(&self) -> Size {
+        Size::new(u32::from(self.size.0), u32::from(self.size.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::mock::Recorder;
+
+    #[test]
+    fn borrowed_slice_sets_the_size() {
+        let recorder = Recorder::new();
+        let mut pixels = [0xFFFF; 6];
+        let mut display = ST7735::new_with_buffer(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            3,
+            2,
+            &mut pixels[..],
+        );
+        assert_eq!(display.size(), Size::new(3, 2));
+        display.set_pixel(2, 1, 0xF800);
+        block_on(display.flush()).unwrap();
+        let (_, data) = recorder.commands("DC").pop().unwrap();
+        assert_eq!(data, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xF8, 0x00]);
+    }
+
+    #[test]
+    fn flush_swaps_chunks_without_touching_the_buffer() {
+        let recorder = Recorder::new();
+        let mut display = ST7735::<_, _, [u16; 320]>::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            16,
+            20,
+        );
+        for i in 0..320 {
+            display.set_pixel(i % 16, i / 16, 0x1200 | i);
+        }
+        block_on(display.flush()).unwrap();
+        let (_, data) = recorder.commands("DC").pop().unwrap();
+        let expected: std::vec::Vec<u8> = (0..320u16)
+            .flat_map(|i| (0x1200 | i).to_be_bytes())
+            .collect();
+        assert_eq!(data, expected);
+        assert_eq!(
+            display.get_pixel(15, 19),
+            Some(RawU16::new(0x1200 | 319).into())
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn heap_driver_is_sized_at_runtime() {
+        let recorder = Recorder::new();
+        for (width, height) in [(128, 128), (160, 128)] {
+            let display = HeapST7735::new_boxed(
+                recorder.spi(),
+                recorder.pin("DC"),
+                recorder.pin("RST"),
+                Config::new(),
+                width,
+                height,
+            );
+            assert_eq!(display.size(), Size::new(width.into(), height.into()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid buffer")]
+    fn buffer_of_the_wrong_length_is_rejected() {
+        let recorder = Recorder::new();
+        let _ = ST7735::<_, _, [u16; 6]>::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            2,
+            2,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid size")]
+    fn zero_size_is_rejected() {
+        let recorder = Recorder::new();
+        let _ = ST7735::<_, _, [u16; 0]>::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            0,
+            128,
+        );
+    }
+}
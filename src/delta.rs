@@ -0,0 +1,394 @@
+//! Delta frame animations storing only the areas that changed between frames.
+//!
+//! The format is a header followed by the frames, all numbers little endian:
+//!
+//! ```text
+//! header: width u16, height u16, frame count u16
+//! frame:  duration in ms u16, rectangle count u16, rectangles
+//! rect:   x u16, y u16, width u16, height u16, width * height big endian Rgb565 pixels
+//! ```
+//!
+//! The first frame has to cover the whole animation area. Clips are created
+//! on the host with `encode`, which requires the `std` feature.
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{Backlight, Error, Model, WriteOnlyInterface, ST7735IF};
+
+const HEADER_LEN: usize = 6;
+const FRAME_HEADER_LEN: usize = 4;
+const RECT_HEADER_LEN: usize = 8;
+
+/// Error of parsing a delta animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DeltaError {
+    /// The data ends in the middle of a frame.
+    Truncated,
+    /// A rectangle lies outside of the animation area.
+    OutOfBounds,
+}
+
+impl core::fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("delta animation data is truncated"),
+            Self::OutOfBounds => f.write_str("delta rectangle outside of the animation area"),
+        }
+    }
+}
+
+impl core::error::Error for DeltaError {}
+
+/// Validated delta animation, e.g. from `include_bytes!`.
+#[derive(Clone, Copy, Debug)]
+pub struct DeltaAnimation<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    frame_count: u16,
+}
+
+impl<'a> DeltaAnimation<'a> {
+    /// Parses the header and checks that all frames are complete and within bounds.
+    pub fn new(data: &'a [u8]) -> Result<Self, DeltaError> {
+        let header = data.get(..HEADER_LEN).ok_or(DeltaError::Truncated)?;
+        let animation = Self {
+            data,
+            width: read_u16(header, 0),
+            height: read_u16(header, 2),
+            frame_count: read_u16(header, 4),
+        };
+        let mut offset = HEADER_LEN;
+        for _ in 0..animation.frame_count {
+            let (_, len) = animation.parse_frame(offset)?;
+            offset += len;
+        }
+        Ok(animation)
+    }
+
+    /// Returns the animation size as `(width, height)`.
+    #[must_use]
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Number of frames.
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        usize::from(self.frame_count)
+    }
+
+    /// Iterates over the frames.
+    #[must_use]
+    pub fn frames(&self) -> DeltaFrames<'a> {
+        DeltaFrames {
+            animation: *self,
+            offset: HEADER_LEN,
+            remaining: self.frame_count,
+        }
+    }
+
+    /// Parses the frame at `offset`, returning it and its length in bytes.
+    fn parse_frame(&self, offset: usize) -> Result<(DeltaFrame<'a>, usize), DeltaError> {
+        let header = self
+            .data
+            .get(offset..offset + FRAME_HEADER_LEN)
+            .ok_or(DeltaError::Truncated)?;
+        let duration_ms = read_u16(header, 0);
+        let rect_count = read_u16(header, 2);
+        let mut len = FRAME_HEADER_LEN;
+        for _ in 0..rect_count {
+            let rect = self
+                .data
+                .get(offset + len..offset + len + RECT_HEADER_LEN)
+                .ok_or(DeltaError::Truncated)?;
+            let (x, y) = (read_u16(rect, 0), read_u16(rect, 2));
+            let (w, h) = (read_u16(rect, 4), read_u16(rect, 6));
+            if u32::from(x) + u32::from(w) > u32::from(self.width)
+                || u32::from(y) + u32::from(h) > u32::from(self.height)
+            {
+                return Err(DeltaError::OutOfBounds);
+            }
+            len += RECT_HEADER_LEN + usize::from(w) * usize::from(h) * 2;
+        }
+        let data = self
+            .data
+            .get(offset..offset + len)
+            .ok_or(DeltaError::Truncated)?;
+        let frame = DeltaFrame {
+            duration_ms,
+            rect_count,
+            data: &data[FRAME_HEADER_LEN..],
+        };
+        Ok((frame, len))
+    }
+}
+
+/// Iterator over the frames of a `DeltaAnimation`.
+pub struct DeltaFrames<'a> {
+    animation: DeltaAnimation<'a>,
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for DeltaFrames<'a> {
+    type Item = DeltaFrame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (frame, len) = self.animation.parse_frame(self.offset).ok()?;
+        self.offset += len;
+        self.remaining -= 1;
+        Some(frame)
+    }
+}
+
+/// Frame of a `DeltaAnimation`.
+#[derive(Clone, Copy, Debug)]
+pub struct DeltaFrame<'a> {
+    duration_ms: u16,
+    rect_count: u16,
+    data: &'a [u8],
+}
+
+impl<'a> DeltaFrame<'a> {
+    /// How long the frame is shown.
+    #[must_use]
+    pub fn duration_ms(&self) -> u16 {
+        self.duration_ms
+    }
+
+    /// Iterates over the changed areas as `(x, y, width, height, pixels)`.
+    pub fn rects(&self) -> impl Iterator<Item = (u16, u16, u16, u16, &'a [u8])> {
+        let mut data = self.data;
+        (0..self.rect_count).map(move |_| {
+            let (x, y) = (read_u16(data, 0), read_u16(data, 2));
+            let (w, h) = (read_u16(data, 4), read_u16(data, 6));
+            let len = usize::from(w) * usize::from(h) * 2;
+            let pixels = &data[RECT_HEADER_LEN..RECT_HEADER_LEN + len];
+            data = &data[RECT_HEADER_LEN + len..];
+            (x, y, w, h, pixels)
+        })
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Plays a delta animation once with its top left corner at `(x, y)`.
+    ///
+    /// Only the changed areas of each frame are written to display RAM, the
+    /// display keeps the rest of the previous frame. The animation must lie
+    /// within the display.
+    pub async fn play_delta<D>(
+        &mut self,
+        animation: &DeltaAnimation<'_>,
+        x: u16,
+        y: u16,
+        delay: &mut D,
+    ) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        for frame in animation.frames() {
+            for (rx, ry, w, h, pixels) in frame.rects() {
+                self.draw_raw_image(x + rx, y + ry, w, h, pixels).await?;
+            }
+            delay.delay_ms(u32::from(frame.duration_ms)).await;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes frames of big endian `Rgb565` pixels into a delta animation.
+///
+/// `frames` are `(pixels, duration_ms)` pairs of `width` x `height` pixels
+/// each. Runs of changed rows become one rectangle spanning the changed
+/// columns of those rows.
+///
+/// # Panics
+///
+/// Panics if a frame has the wrong length or there are more than `u16::MAX` frames.
+#[cfg(any(test, feature = "std"))]
+#[must_use]
+pub fn encode(width: u16, height: u16, frames: &[(&[u8], u16)]) -> std::vec::Vec<u8> {
+    let stride = usize::from(width) * 2;
+    let mut out = std::vec::Vec::new();
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    let frame_count = u16::try_from(frames.len()).expect("too many frames");
+    out.extend_from_slice(&frame_count.to_le_bytes());
+
+    let mut previous: Option<&[u8]> = None;
+    for &(pixels, duration_ms) in frames {
+        assert_eq!(
+            pixels.len(),
+            stride * usize::from(height),
+            "wrong frame length"
+        );
+        // Changed column range of each row, `None` for unchanged rows.
+        let changed = |row: u16| -> Option<(u16, u16)> {
+            let range = usize::from(row) * stride..usize::from(row + 1) * stride;
+            let current = pixels[range.clone()].chunks_exact(2);
+            let Some(previous) = previous else {
+                return Some((0, width));
+            };
+            let mut columns = current
+                .zip(previous[range].chunks_exact(2))
+                .enumerate()
+                .filter(|(_, (a, b))| a != b)
+                .map(|(column, _)| column as u16);
+            let first = columns.next()?;
+            let last = columns.next_back().unwrap_or(first);
+            Some((first, last + 1))
+        };
+
+        let mut rects = std::vec::Vec::new();
+        let mut row = 0;
+        while row < height {
+            let Some((mut start, mut end)) = changed(row) else {
+                row += 1;
+                continue;
+            };
+            let top = row;
+            row += 1;
+            while let Some((s, e)) = (row < height).then(|| changed(row)).flatten() {
+                start = start.min(s);
+                end = end.max(e);
+                row += 1;
+            }
+            rects.push((start, top, end - start, row - top));
+        }
+
+        out.extend_from_slice(&duration_ms.to_le_bytes());
+        out.extend_from_slice(&(rects.len() as u16).to_le_bytes());
+        for (x, y, w, h) in rects {
+            for value in [x, y, w, h] {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            for row in y..y + h {
+                let start = usize::from(row) * stride + usize::from(x) * 2;
+                out.extend_from_slice(&pixels[start..start + usize::from(w) * 2]);
+            }
+        }
+        previous = Some(pixels);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+
+    const WIDTH: u16 = 4;
+    const HEIGHT: u16 = 3;
+
+    /// Frame filled with `color` except for `(x, y, color)` pixels.
+    fn frame(color: u16, pixels: &[(u16, u16, u16)]) -> Vec<u8> {
+        let mut frame: Vec<u8> = (0..WIDTH * HEIGHT)
+            .flat_map(|_| color.to_be_bytes())
+            .collect();
+        for &(x, y, color) in pixels {
+            let i = usize::from(y * WIDTH + x) * 2;
+            frame[i..i + 2].copy_from_slice(&color.to_be_bytes());
+        }
+        frame
+    }
+
+    /// Applies the rectangles of every frame and returns the resulting frames.
+    fn decode(animation: &DeltaAnimation<'_>) -> Vec<Vec<u8>> {
+        let stride = usize::from(WIDTH) * 2;
+        let mut screen = vec![0; stride * usize::from(HEIGHT)];
+        let mut frames = Vec::new();
+        for frame in animation.frames() {
+            for (x, y, w, _, pixels) in frame.rects() {
+                for (row, line) in pixels.chunks_exact(usize::from(w) * 2).enumerate() {
+                    let start = (usize::from(y) + row) * stride + usize::from(x) * 2;
+                    screen[start..start + line.len()].copy_from_slice(line);
+                }
+            }
+            frames.push(screen.clone());
+        }
+        frames
+    }
+
+    #[test]
+    fn encoded_frames_round_trip() {
+        let frames = [
+            frame(0x1234, &[]),
+            frame(0x1234, &[(2, 1, 0xF800)]),
+            frame(0x1234, &[(2, 1, 0xF800)]),
+            frame(0x1234, &[(0, 0, 0x07E0), (2, 1, 0xF800), (3, 2, 0x001F)]),
+        ];
+        let input: Vec<(&[u8], u16)> = frames.iter().map(|f| (f.as_slice(), 40)).collect();
+        let data = encode(WIDTH, HEIGHT, &input);
+        let animation = DeltaAnimation::new(&data).unwrap();
+        assert_eq!(animation.size(), (WIDTH, HEIGHT));
+        assert_eq!(animation.frame_count(), 4);
+        assert_eq!(decode(&animation), frames);
+
+        let rects: Vec<Vec<_>> = animation
+            .frames()
+            .map(|frame| frame.rects().map(|(x, y, w, h, _)| (x, y, w, h)).collect())
+            .collect();
+        assert_eq!(
+            rects,
+            [
+                vec![(0, 0, 4, 3)],
+                vec![(2, 1, 1, 1)],
+                vec![],
+                // Changed rows 0 and 2 are not adjacent.
+                vec![(0, 0, 1, 1), (3, 2, 1, 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn hostile_headers_are_rejected() {
+        let data = encode(WIDTH, HEIGHT, &[(&frame(0, &[]), 10)]);
+        for len in 0..data.len() {
+            assert_eq!(
+                DeltaAnimation::new(&data[..len]).unwrap_err(),
+                DeltaError::Truncated
+            );
+        }
+        // Frame count larger than the data
+        let mut more_frames = data.clone();
+        more_frames[4] = 2;
+        assert_eq!(
+            DeltaAnimation::new(&more_frames).unwrap_err(),
+            DeltaError::Truncated
+        );
+        // Rectangle at x 1 with the full width
+        let mut shifted = data.clone();
+        shifted[HEADER_LEN + FRAME_HEADER_LEN] = 1;
+        assert_eq!(
+            DeltaAnimation::new(&shifted).unwrap_err(),
+            DeltaError::OutOfBounds
+        );
+        // Huge rectangle must not overflow the length computation
+        let mut huge = data;
+        let rect = HEADER_LEN + FRAME_HEADER_LEN;
+        huge[rect..rect + 8].copy_from_slice(&[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(
+            DeltaAnimation::new(&huge).unwrap_err(),
+            DeltaError::OutOfBounds
+        );
+    }
+}
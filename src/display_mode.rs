@@ -0,0 +1,316 @@
+//! Display modes switching several settings at once, e.g. for day and night.
+//!
+//! A `DisplayMode` bundles inversion, the gamma curve, the backlight
+//! brightness and the UI colors, `ST7735IF::apply_mode` switches all of them
+//! in one call:
+//!
+//! ```ignore
+//! let night = DisplayMode::new(Theme::NIGHT)
+//!     .brightness(15)
+//!     .gamma(Gamma::Curve(GammaCurve::Curve3))
+//!     .fade_ms(500);
+//! display.apply_mode(night, &mut delay).await?;
+//! let theme = display.theme();
+//! Rectangle::new(Point::zero(), size)
+//!     .into_styled(PrimitiveStyle::with_fill(theme.background))
+//!     .draw(&mut frame)?;
+//! ```
+use core::convert::Infallible;
+
+use embedded_graphics_core::pixelcolor::{Rgb565, RgbColor, WebColors};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+use crate::instruction::Instruction;
+use crate::{Backlight, Error, Model, WriteOnlyInterface, ST7735IF};
+
+/// Predefined gamma curve selected with GAMSET.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum GammaCurve {
+    Curve1 = 0x01,
+    Curve2 = 0x02,
+    Curve3 = 0x04,
+    Curve4 = 0x08,
+}
+
+/// Gamma correction of a `DisplayMode`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Gamma {
+    /// Keeps the gamma programming of the model.
+    #[default]
+    Model,
+    /// Selects a predefined curve.
+    Curve(GammaCurve),
+    /// Writes the positive (GMCTRP1) and negative (GMCTRN1) correction tables,
+    /// e.g. from the panel vendor's reference code.
+    Tables {
+        positive: [u8; 16],
+        negative: [u8; 16],
+    },
+}
+
+/// Colors the application draws with in a `DisplayMode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Fill behind everything else
+    pub background: Rgb565,
+    /// Text and lines
+    pub foreground: Rgb565,
+    /// Highlights, e.g. a selected menu entry
+    pub accent: Rgb565,
+}
+
+impl Theme {
+    /// Dark on light, readable in sunlight.
+    pub const DAY: Self = Self {
+        background: Rgb565::WHITE,
+        foreground: Rgb565::BLACK,
+        accent: Rgb565::BLUE,
+    };
+
+    /// Dim red on black, keeps the eyes adapted to the dark.
+    pub const NIGHT: Self = Self {
+        background: Rgb565::BLACK,
+        foreground: Rgb565::CSS_DARK_RED,
+        accent: Rgb565::CSS_ORANGE_RED,
+    };
+}
+
+/// Logs the colors as raw `Rgb565` values.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Theme {
+    fn format(&self, f: defmt::Formatter) {
+        use embedded_graphics_core::pixelcolor::raw::{RawData, RawU16};
+        defmt::write!(
+            f,
+            "Theme {{ background: {=u16:#06x}, foreground: {=u16:#06x}, accent: {=u16:#06x} }}",
+            RawU16::from(self.background).into_inner(),
+            RawU16::from(self.foreground).into_inner(),
+            RawU16::from(self.accent).into_inner(),
+        );
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DAY
+    }
+}
+
+/// Settings `ST7735IF::apply_mode` switches together.
+///
+/// Build it with `DisplayMode::new` and the setter methods, as fields may be
+/// added in minor releases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct DisplayMode {
+    /// Colors inverted.
+    pub inverted: bool,
+    /// Gamma correction
+    pub gamma: Gamma,
+    /// Backlight brightness in percent
+    pub brightness: u8,
+    /// UI colors, see `ST7735IF::theme`
+    pub theme: Theme,
+    /// Duration of the backlight fade out and back in around the switch, 0 switches at once
+    pub fade_ms: u32,
+}
+
+impl DisplayMode {
+    /// Creates a mode with `theme`, full brightness, the model's gamma and no inversion.
+    #[must_use]
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            inverted: false,
+            gamma: Gamma::Model,
+            brightness: 100,
+            theme,
+            fade_ms: 0,
+        }
+    }
+
+    /// Sets whether colors are inverted.
+    #[must_use]
+    pub fn inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Sets the gamma correction.
+    #[must_use]
+    pub fn gamma(mut self, gamma: Gamma) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets the backlight brightness in percent, values above 100 are clamped.
+    #[must_use]
+    pub fn brightness(mut self, percent: u8) -> Self {
+        self.brightness = percent.min(100);
+        self
+    }
+
+    /// Sets the duration of the backlight fade around the switch.
+    #[must_use]
+    pub fn fade_ms(mut self, fade_ms: u32) -> Self {
+        self.fade_ms = fade_ms;
+        self
+    }
+}
+
+impl Default for DisplayMode {
+    fn default() -> Self {
+        Self::new(Theme::DAY)
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Switches inversion, gamma, brightness and theme to `mode`.
+    ///
+    /// The inversion and gamma commands are written together, see
+    /// `WriteOnlyInterface::write_commands`, between fading the backlight out
+    /// and in over `mode.fade_ms`. The mode, including its gamma, is kept
+    /// across `recover`. If a write fails the previous mode stays in effect
+    /// for the driver, see `needs_recovery`.
+    pub async fn apply_mode<D>(&mut self, mode: DisplayMode, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        let fade_ms = mode.fade_ms / 2;
+        if fade_ms > 0 {
+            self.fade_backlight(0, fade_ms, delay).await;
+        }
+        let inversion = if mode.inverted {
+            Instruction::INVON
+        } else {
+            Instruction::INVOFF
+        };
+        let mut commands: [(u8, &[u8]); 3] = [(inversion as u8, &[]); 3];
+        let len = 1 + gamma_commands(&mode.gamma, &mut commands[1..]);
+        self.write_commands(&commands[..len]).await?;
+        self.inverted = mode.inverted;
+        self.mode = mode;
+        if fade_ms > 0 {
+            self.fade_backlight(mode.brightness, fade_ms, delay).await;
+        } else {
+            self.set_backlight_brightness(mode.brightness);
+        }
+        Ok(())
+    }
+
+    /// Returns the mode last applied with `apply_mode`, inversion and
+    /// brightness may have been changed since.
+    pub fn display_mode(&self) -> &DisplayMode {
+        &self.mode
+    }
+
+    /// Returns the colors of the current display mode.
+    pub fn theme(&self) -> Theme {
+        self.mode.theme
+    }
+
+    /// Writes the gamma correction of the current mode, e.g. after the power up programming.
+    pub(crate) async fn write_gamma(&mut self) -> Result<(), Error<E>> {
+        let mut commands: [(u8, &[u8]); 2] = [(0, &[]); 2];
+        let gamma = self.mode.gamma;
+        let len = gamma_commands(&gamma, &mut commands);
+        self.write_commands(&commands[..len]).await
+    }
+}
+
+/// Fills `commands` with the commands selecting `gamma` and returns their number.
+fn gamma_commands<'a>(gamma: &'a Gamma, commands: &mut [(u8, &'a [u8])]) -> usize {
+    match gamma {
+        Gamma::Model => 0,
+        Gamma::Curve(curve) => {
+            let param: &'static [u8] = match curve {
+                GammaCurve::Curve1 => &[GammaCurve::Curve1 as u8],
+                GammaCurve::Curve2 => &[GammaCurve::Curve2 as u8],
+                GammaCurve::Curve3 => &[GammaCurve::Curve3 as u8],
+                GammaCurve::Curve4 => &[GammaCurve::Curve4 as u8],
+            };
+            commands[0] = (Instruction::GAMSET as u8, param);
+            1
+        }
+        Gamma::Tables { positive, negative } => {
+            commands[0] = (Instruction::GMCTRP1 as u8, positive);
+            commands[1] = (Instruction::GMCTRN1 as u8, negative);
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::{NoDelay, Recorder};
+    use crate::Config;
+
+    fn commands(recorder: &Recorder) -> Vec<(u8, Vec<u8>)> {
+        recorder.commands("DC")
+    }
+
+    #[test]
+    fn mode_switches_inversion_gamma_and_brightness() {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        let night = DisplayMode::new(Theme::NIGHT)
+            .inverted(true)
+            .brightness(20)
+            .gamma(Gamma::Curve(GammaCurve::Curve3));
+        block_on(display.apply_mode(night, &mut NoDelay)).unwrap();
+        assert_eq!(
+            commands(&recorder),
+            [(0x21, Vec::new()), (0x26, [0x04].to_vec())]
+        );
+        assert_eq!(display.theme(), Theme::NIGHT);
+        assert_eq!(display.backlight_brightness(), 20);
+    }
+
+    #[test]
+    fn gamma_tables_are_kept_across_recover() {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        let positive = [0x11; 16];
+        let negative = [0x22; 16];
+        let mode = DisplayMode::new(Theme::DAY)
+            .gamma(Gamma::Tables { positive, negative })
+            .fade_ms(10);
+        block_on(display.apply_mode(mode, &mut NoDelay)).unwrap();
+        assert_eq!(display.backlight_brightness(), 100);
+        recorder.clear();
+        block_on(display.recover(&mut NoDelay)).unwrap();
+        let commands = commands(&recorder);
+        let gamma: Vec<_> = commands
+            .iter()
+            .filter(|(command, _)| *command == 0xE0 || *command == 0xE1)
+            .collect();
+        assert_eq!(
+            gamma,
+            [&(0xE0, positive.to_vec()), &(0xE1, negative.to_vec())]
+        );
+    }
+}
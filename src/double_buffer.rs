@@ -0,0 +1,72 @@
+use core::mem;
+
+use embedded_graphics_core::{draw_target::DrawTarget, pixelcolor::Rgb565, prelude::*};
+
+use crate::Frame;
+
+/// Front/back pair of `Frame`s.
+///
+/// Drawing goes to the back frame while the front frame is being displayed,
+/// `swap` exchanges them once the next frame is complete. Both frames are
+/// borrowed so they can live in statics.
+pub struct DoubleBuffered<'a, const N: usize> {
+    front: &'a mut Frame<N>,
+    back: &'a mut Frame<N>,
+}
+
+impl<'a, const N: usize> DoubleBuffered<'a, N> {
+    #[must_use]
+    pub fn new(front: &'a mut Frame<N>, back: &'a mut Frame<N>) -> Self {
+        Self { front, back }
+    }
+
+    /// Frame to be displayed.
+    pub fn front(&self) -> &Frame<N> {
+        self.front
+    }
+
+    /// Frame to draw into.
+    pub fn back_mut(&mut self) -> &mut Frame<N> {
+        self.back
+    }
+
+    /// Returns the front frame for flushing together with the back frame for drawing.
+    pub fn split(&mut self) -> (&Frame<N>, &mut Frame<N>) {
+        (self.front, self.back)
+    }
+
+    /// Makes the back frame the new front frame.
+    ///
+    /// The new back frame keeps the contents of the previous front frame, use
+    /// `copy_front_to_back` when drawing incrementally on top of the last frame.
+    pub fn swap(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Copies the front frame contents into the back frame.
+    pub fn copy_front_to_back(&mut self) {
+        self.back.buffer.copy_from_slice(&self.front.buffer);
+    }
+}
+
+impl<const N: usize> DrawTarget for DoubleBuffered<'_, N> {
+    type Error = ();
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.back.draw_iter(pixels)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.back.clear(color)
+    }
+}
+
+impl<const N: usize> OriginDimensions for DoubleBuffered<'_, N> {
+    fn size(&self) -> Size {
+        self.back.size()
+    }
+}
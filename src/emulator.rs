@@ -0,0 +1,414 @@
+//! In-memory panel interpreting the command stream, requires the `std` feature.
+//!
+//! `EmulatedPanel` is a `WriteOnlyInterface` that keeps the controller GRAM
+//! and applies CASET, RASET, RAMWR, MADCTL and COLMOD like the ST7735 does, so
+//! tests can check which pixels a flush really changed:
+//!
+//! ```ignore
+//! let mut display = ST7735IF::with_interface(EmulatedPanel::default(), rst, config);
+//! display.init(&mut delay).await?;
+//! display.flush_frame(&frame).await?;
+//! assert_eq!(display.interface_mut().pixel(10, 10), Some(Rgb565::RED));
+//! ```
+use core::convert::Infallible;
+use std::vec::Vec;
+
+use embedded_graphics_core::pixelcolor::{
+    raw::{RawData, RawU16},
+    Rgb565,
+};
+
+use crate::instruction::Instruction;
+use crate::{ReadInterface, WriteOnlyInterface};
+
+/// GRAM columns and rows of the controller in its largest resolution mode.
+const GRAM_SIZE: (u16, u16) = (132, 162);
+/// MADCTL row address order.
+const MY: u8 = 0x80;
+/// MADCTL column address order.
+const MX: u8 = 0x40;
+/// MADCTL row/column exchange.
+const MV: u8 = 0x20;
+/// COLMOD value of 16 bit pixels, other values are treated as 18 bit pixels.
+const COLMOD_16_BIT: u8 = 0x05;
+/// COLMOD value after reset, 18 bit pixels.
+const COLMOD_RESET: u8 = 0x06;
+/// RDDID response: manufacturer, driver version and driver ID.
+const DISPLAY_ID: [u8; 3] = [0x7C, 0x89, 0xF0];
+
+/// Emulated controller and panel.
+///
+/// The panel shows `width` x `height` pixels of the GRAM starting at `offset`,
+/// all in panel-native portrait coordinates. Like the controller, MADCTL
+/// mirroring applies to the whole GRAM, so panels smaller than the GRAM need
+/// the matching display offset for each orientation.
+pub struct EmulatedPanel {
+    gram: Vec<u16>,
+    /// GRAM columns and rows
+    gram_size: (u16, u16),
+    width: u16,
+    height: u16,
+    offset: (u16, u16),
+    madctl: u8,
+    colmod: u8,
+    /// Column window, inclusive
+    columns: (u16, u16),
+    /// Row window, inclusive
+    rows: (u16, u16),
+    /// Logical write position within the window
+    cursor: (u16, u16),
+    /// Bytes of a pixel split across writes
+    partial: ([u8; 3], usize),
+    last_command: Option<u8>,
+    sleeping: bool,
+    display_on: bool,
+    inverted: bool,
+    /// TEON mode while the tearing effect output is on
+    tearing_effect: Option<u8>,
+}
+
+impl EmulatedPanel {
+    /// Creates a panel showing `width` x `height` native pixels at `offset` in the GRAM.
+    ///
+    /// The GRAM is 132 x 162 pixels. The panel starts in the reset state:
+    /// asleep, display off, 18 bit pixels and black GRAM.
+    #[must_use]
+    pub fn new(width: u16, height: u16, offset: (u16, u16)) -> Self {
+        Self::with_gram_size(width, height, offset, GRAM_SIZE)
+    }
+
+    /// Like `new`, but with a GRAM of `gram_size` columns and rows, e.g. 128 x 160
+    /// for controllers strapped to that resolution.
+    #[must_use]
+    pub fn with_gram_size(
+        width: u16,
+        height: u16,
+        offset: (u16, u16),
+        gram_size: (u16, u16),
+    ) -> Self {
+        Self {
+            gram: std::vec![0; usize::from(gram_size.0) * usize::from(gram_size.1)],
+            gram_size,
+            width,
+            height,
+            offset,
+            madctl: 0,
+            colmod: COLMOD_RESET,
+            columns: (0, gram_size.0 - 1),
+            rows: (0, gram_size.1 - 1),
+            cursor: (0, 0),
+            partial: ([0; 3], 0),
+            last_command: None,
+            sleeping: true,
+            display_on: false,
+            inverted: false,
+            tearing_effect: None,
+        }
+    }
+
+    /// Applies a command with its parameters, for RAMWR the parameters are pixel data.
+    pub fn apply(&mut self, command: u8, params: &[u8]) {
+        self.last_command = Some(command);
+        let address = |params: &[u8]| -> Option<(u16, u16)> {
+            let p = params.get(..4)?;
+            Some((
+                u16::from_be_bytes([p[0], p[1]]),
+                u16::from_be_bytes([p[2], p[3]]),
+            ))
+        };
+        match command {
+            c if c == Instruction::SWRESET as u8 => {
+                *self = Self::with_gram_size(self.width, self.height, self.offset, self.gram_size);
+            }
+            c if c == Instruction::SLPIN as u8 => self.sleeping = true,
+            c if c == Instruction::SLPOUT as u8 => self.sleeping = false,
+            c if c == Instruction::INVOFF as u8 => self.inverted = false,
+            c if c == Instruction::INVON as u8 => self.inverted = true,
+            c if c == Instruction::DISPOFF as u8 => self.display_on = false,
+            c if c == Instruction::DISPON as u8 => self.display_on = true,
+            c if c == Instruction::TEOFF as u8 => self.tearing_effect = None,
+            c if c == Instruction::TEON as u8 => {
+                self.tearing_effect = Some(params.first().copied().unwrap_or(0) & 0x01);
+            }
+            c if c == Instruction::CASET as u8 => {
+                self.columns = address(params).unwrap_or(self.columns);
+            }
+            c if c == Instruction::RASET as u8 => self.rows = address(params).unwrap_or(self.rows),
+            c if c == Instruction::MADCTL as u8 => {
+                self.madctl = params.first().copied().unwrap_or(self.madctl);
+            }
+            c if c == Instruction::COLMOD as u8 => {
+                self.colmod = params.first().map_or(self.colmod, |p| p & 0x07);
+            }
+            c if c == Instruction::RAMWR as u8 => {
+                self.cursor = (self.columns.0, self.rows.0);
+                self.partial.1 = 0;
+                self.write_pixels(params);
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies decoded `(command, parameters)` pairs, e.g. from `mock::Recorder::commands`.
+    pub fn replay(&mut self, commands: &[(u8, Vec<u8>)]) {
+        for (command, params) in commands {
+            self.apply(*command, params);
+        }
+    }
+
+    /// Returns the pixel the viewer sees at `(x, y)` in the orientation set through MADCTL.
+    #[must_use]
+    pub fn pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        let (width, height) = self.size();
+        if x >= width || y >= height {
+            return None;
+        }
+        let (column, row) = address_to_native(self.madctl, (self.width, self.height), (x, y))?;
+        self.memory_pixel(column + self.offset.0, row + self.offset.1)
+    }
+
+    /// Returns the GRAM pixel at panel-native `(column, row)`.
+    #[must_use]
+    pub fn memory_pixel(&self, column: u16, row: u16) -> Option<Rgb565> {
+        let (columns, rows) = self.gram_size;
+        if column >= columns || row >= rows {
+            return None;
+        }
+        let raw = self.gram[usize::from(row) * usize::from(columns) + usize::from(column)];
+        Some(RawU16::new(raw).into())
+    }
+
+    /// Returns the panel size as seen in the orientation set through MADCTL.
+    #[must_use]
+    pub fn size(&self) -> (u16, u16) {
+        if self.madctl & MV != 0 {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+
+    /// Returns the last MADCTL value.
+    #[must_use]
+    pub fn madctl(&self) -> u8 {
+        self.madctl
+    }
+
+    /// Whether the controller is in sleep mode.
+    #[must_use]
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Whether the display output is on.
+    #[must_use]
+    pub fn is_display_on(&self) -> bool {
+        self.display_on
+    }
+
+    /// Whether color inversion is on.
+    #[must_use]
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Returns the RDDST response for the emulated state.
+    fn status_bytes(&self) -> [u8; 4] {
+        let flag = |on: bool, bit: u8| u8::from(on) << bit;
+        [
+            flag(!self.sleeping, 7) | (self.madctl >> 1) & 0x7E,
+            (self.colmod & 0x07) << 4 | flag(!self.sleeping, 1) | flag(true, 0),
+            flag(self.inverted, 5)
+                | flag(self.display_on, 2)
+                | flag(self.tearing_effect.is_some(), 1),
+            flag(self.tearing_effect == Some(1), 5),
+        ]
+    }
+
+    fn write_pixels(&mut self, data: &[u8]) {
+        let pixel_len = if self.colmod == COLMOD_16_BIT { 2 } else { 3 };
+        for &byte in data {
+            let (bytes, len) = &mut self.partial;
+            bytes[*len] = byte;
+            *len += 1;
+            if *len < pixel_len {
+                continue;
+            }
+            *len = 0;
+            let color = if pixel_len == 2 {
+                RawU16::new(u16::from_be_bytes([bytes[0], bytes[1]])).into()
+            } else {
+                Rgb565::new(bytes[0] >> 3, bytes[1] >> 2, bytes[2] >> 3)
+            };
+            self.store(color);
+        }
+    }
+
+    /// Stores a pixel at the cursor and advances it, wrapping around within the window.
+    fn store(&mut self, color: Rgb565) {
+        if let Some((column, row)) = address_to_native(self.madctl, self.gram_size, self.cursor) {
+            let index = usize::from(row) * usize::from(self.gram_size.0) + usize::from(column);
+            self.gram[index] = RawU16::from(color).into_inner();
+        }
+        self.cursor.0 += 1;
+        if self.cursor.0 > self.columns.1 {
+            self.cursor.0 = self.columns.0;
+            self.cursor.1 = if self.cursor.1 >= self.rows.1 {
+                self.rows.0
+            } else {
+                self.cursor.1 + 1
+            };
+        }
+    }
+}
+
+/// Maps a column and row address to native `(column, row)` within `size` native columns and rows.
+///
+/// Follows the MADCTL description of the datasheet instead of the driver
+/// transforms, so tests can catch errors in those: MV exchanges the column
+/// and row address, then MX reverses the column order and MY the row order.
+fn address_to_native(madctl: u8, size: (u16, u16), (x, y): (u16, u16)) -> Option<(u16, u16)> {
+    let (column, row) = if madctl & MV != 0 { (y, x) } else { (x, y) };
+    if column >= size.0 || row >= size.1 {
+        return None;
+    }
+    let column = if madctl & MX != 0 {
+        size.0 - 1 - column
+    } else {
+        column
+    };
+    let row = if madctl & MY != 0 {
+        size.1 - 1 - row
+    } else {
+        row
+    };
+    Some((column, row))
+}
+
+impl WriteOnlyInterface for EmulatedPanel {
+    type Error = Infallible;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        self.apply(command, params);
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        if self.last_command == Some(Instruction::RAMWR as u8) {
+            self.write_pixels(data);
+        }
+        Ok(())
+    }
+}
+
+/// Responses are returned without dummy bits, unknown reads return zeros.
+impl ReadInterface for EmulatedPanel {
+    async fn read_command(
+        &mut self,
+        command: u8,
+        _dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.last_command = Some(command);
+        buf.fill(0);
+        if command == Instruction::RDDID as u8 {
+            for (byte, id) in buf.iter_mut().zip(DISPLAY_ID) {
+                *byte = id;
+            }
+        } else if command == Instruction::RDDSDR as u8 {
+            // Both checks pass once the controller left sleep mode.
+            if let Some(byte) = buf.first_mut() {
+                *byte = if self.sleeping { 0 } else { 0xC0 };
+            }
+        } else if command == Instruction::RDDST as u8 {
+            for (byte, status) in buf.iter_mut().zip(self.status_bytes()) {
+                *byte = status;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for EmulatedPanel {
+    /// A 128 x 160 panel covering a GRAM of the same size.
+    fn default() -> Self {
+        Self::with_gram_size(128, 160, (0, 0), (128, 160))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+
+    use super::*;
+    use embedded_graphics_core::pixelcolor::RgbColor;
+
+    use crate::mock::{NoDelay, Recorder};
+    use crate::{Config, Orientation, ST7735IF};
+
+    #[test]
+    fn madctl_moves_the_address_counter() {
+        // Native GRAM cells of the first two pixels of a window at (0, 0), from
+        // the MADCTL description of the datasheet.
+        let cases = [
+            (0x00, [(0, 0), (1, 0)]),
+            (MX | MV, [(127, 0), (127, 1)]),
+            (MY | MX, [(127, 159), (126, 159)]),
+            (MY | MV, [(0, 159), (0, 158)]),
+        ];
+        for (madctl, cells) in cases {
+            let mut panel = EmulatedPanel::default();
+            panel.apply(Instruction::COLMOD as u8, &[COLMOD_16_BIT]);
+            panel.apply(Instruction::MADCTL as u8, &[madctl]);
+            panel.apply(Instruction::CASET as u8, &[0, 0, 0, 1]);
+            panel.apply(Instruction::RASET as u8, &[0, 0, 0, 0]);
+            panel.apply(Instruction::RAMWR as u8, &[0xF8, 0x00, 0x07, 0xE0]);
+            let [first, second] = cells;
+            assert_eq!(panel.memory_pixel(first.0, first.1), Some(Rgb565::RED));
+            assert_eq!(panel.memory_pixel(second.0, second.1), Some(Rgb565::GREEN));
+        }
+    }
+
+    #[test]
+    fn driver_orientations_land_in_the_expected_gram_cells() {
+        let cases = [
+            (Orientation::Portrait, (128, 160), [(0, 0), (1, 0)]),
+            (Orientation::Landscape, (160, 128), [(127, 0), (127, 1)]),
+            (
+                Orientation::PortraitSwapped,
+                (128, 160),
+                [(127, 159), (126, 159)],
+            ),
+            (
+                Orientation::LandscapeSwapped,
+                (160, 128),
+                [(0, 159), (0, 158)],
+            ),
+        ];
+        for (orientation, (width, height), cells) in cases {
+            let recorder = Recorder::new();
+            let config = Config::new().orientation(orientation);
+            let mut display =
+                ST7735IF::with_interface(EmulatedPanel::default(), recorder.pin("RST"), config);
+            block_on(display.init(&mut NoDelay)).unwrap();
+            block_on(display.clear_screen(Rgb565::BLACK)).unwrap();
+            block_on(display.fill_rect(0, 0, 1, 1, Rgb565::RED)).unwrap();
+            block_on(display.fill_rect(1, 0, 1, 1, Rgb565::GREEN)).unwrap();
+            let panel = display.interface_mut();
+            assert_eq!(panel.size(), (width, height), "{orientation:?}");
+            let [first, second] = cells;
+            assert_eq!(
+                panel.memory_pixel(first.0, first.1),
+                Some(Rgb565::RED),
+                "{orientation:?}"
+            );
+            assert_eq!(
+                panel.memory_pixel(second.0, second.1),
+                Some(Rgb565::GREEN),
+                "{orientation:?}"
+            );
+            assert_eq!(panel.pixel(0, 0), Some(Rgb565::RED), "{orientation:?}");
+            assert_eq!(panel.pixel(1, 0), Some(Rgb565::GREEN), "{orientation:?}");
+        }
+    }
+}
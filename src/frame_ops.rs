@@ -0,0 +1,414 @@
+//! Bulk operations on `Frame` contents working on whole rows of the byte buffer.
+use core::ops::Range;
+
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawU16, Rgb565},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+use crate::{blend::blend, Frame};
+
+/// How `blit_rows` combines source and destination pixels.
+enum BlitMode {
+    Copy,
+    /// Skip source pixels of this raw color
+    Keyed([u8; 2]),
+    /// Blend source pixels with this alpha
+    Blended(u8),
+}
+
+impl<const N: usize> Frame<N> {
+    /// Byte index of the first byte of the pixel at `(x, y)`, the coords must be in bounds.
+    fn byte_index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 2
+    }
+
+    /// Number of bytes used by the pixels of the frame.
+    fn used_len(&self) -> usize {
+        (self.width as usize * self.height as usize * 2).min(N)
+    }
+
+    /// Fills a byte range of the buffer with a color.
+    fn fill_bytes(&mut self, range: Range<usize>, color: Rgb565) {
+        let color = RawU16::from(color).into_inner().to_be_bytes();
+        for pixel in self.buffer[range].chunks_exact_mut(2) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    /// Moves the contents up by `rows`, filling the exposed rows at the bottom with `fill`.
+    pub fn scroll_up(&mut self, rows: u32, fill: Rgb565) {
+        let len = self.used_len();
+        let shift = (rows.min(self.height) as usize * self.width as usize * 2).min(len);
+        self.buffer.copy_within(shift..len, 0);
+        self.fill_bytes(len - shift..len, fill);
+    }
+
+    /// Moves the contents down by `rows`, filling the exposed rows at the top with `fill`.
+    pub fn scroll_down(&mut self, rows: u32, fill: Rgb565) {
+        let len = self.used_len();
+        let shift = (rows.min(self.height) as usize * self.width as usize * 2).min(len);
+        self.buffer.copy_within(0..len - shift, shift);
+        self.fill_bytes(0..shift, fill);
+    }
+
+    /// Moves the contents left by `cols`, filling the exposed columns at the right with `fill`.
+    pub fn scroll_left(&mut self, cols: u32, fill: Rgb565) {
+        let row_len = self.width as usize * 2;
+        let shift = cols.min(self.width) as usize * 2;
+        for row in (0..self.used_len() / row_len.max(1)).map(|y| y * row_len) {
+            self.buffer.copy_within(row + shift..row + row_len, row);
+            self.fill_bytes(row + row_len - shift..row + row_len, fill);
+        }
+    }
+
+    /// Moves the contents right by `cols`, filling the exposed columns at the left with `fill`.
+    pub fn scroll_right(&mut self, cols: u32, fill: Rgb565) {
+        let row_len = self.width as usize * 2;
+        let shift = cols.min(self.width) as usize * 2;
+        for row in (0..self.used_len() / row_len.max(1)).map(|y| y * row_len) {
+            self.buffer
+                .copy_within(row..row + row_len - shift, row + shift);
+            self.fill_bytes(row..row + shift, fill);
+        }
+    }
+
+    /// Copies the `src_rect` area of `src` to `dest` in this frame.
+    ///
+    /// Both areas are clipped to their frames. Overlapping copies within one
+    /// frame are not possible, use a temporary frame instead.
+    pub fn blit<const M: usize>(&mut self, src: &Frame<M>, src_rect: Rectangle, dest: Point) {
+        self.blit_rows(src, src_rect, dest, BlitMode::Copy);
+    }
+
+    /// Like `blit`, but pixels of the `key` color are transparent and keep the destination pixel.
+    pub fn blit_keyed<const M: usize>(
+        &mut self,
+        src: &Frame<M>,
+        src_rect: Rectangle,
+        dest: Point,
+        key: Rgb565,
+    ) {
+        let key = RawU16::from(key).into_inner().to_be_bytes();
+        self.blit_rows(src, src_rect, dest, BlitMode::Keyed(key));
+    }
+
+    /// Like `blit`, but blends the source over the destination with `alpha` from 0 to 255.
+    pub fn blit_blended<const M: usize>(
+        &mut self,
+        src: &Frame<M>,
+        src_rect: Rectangle,
+        dest: Point,
+        alpha: u8,
+    ) {
+        self.blit_rows(src, src_rect, dest, BlitMode::Blended(alpha));
+    }
+
+    fn blit_rows<const M: usize>(
+        &mut self,
+        src: &Frame<M>,
+        src_rect: Rectangle,
+        dest: Point,
+        mode: BlitMode,
+    ) {
+        let offset = dest - src_rect.top_left;
+        let src_rect = src_rect.intersection(&src.bounding_box());
+        let dest_rect = Rectangle::new(src_rect.top_left + offset, src_rect.size)
+            .intersection(&self.bounding_box());
+        if dest_rect.is_zero_sized() {
+            return;
+        }
+        let row_len = dest_rect.size.width as usize * 2;
+        let dest_x = dest_rect.top_left.x;
+        for dest_y in dest_rect.rows() {
+            let src_start = src.byte_index((dest_x - offset.x) as u32, (dest_y - offset.y) as u32);
+            let dest_start = self.byte_index(dest_x as u32, dest_y as u32);
+            let src_row = src.buffer.get(src_start..src_start + row_len);
+            let dest_row = self.buffer.get_mut(dest_start..dest_start + row_len);
+            let (Some(src_row), Some(dest_row)) = (src_row, dest_row) else {
+                continue;
+            };
+            let pixels = dest_row.chunks_exact_mut(2).zip(src_row.chunks_exact(2));
+            match mode {
+                BlitMode::Copy => dest_row.copy_from_slice(src_row),
+                BlitMode::Keyed(key) => {
+                    for (d, s) in pixels.filter(|(_, s)| *s != key) {
+                        d.copy_from_slice(s);
+                    }
+                }
+                BlitMode::Blended(alpha) => {
+                    for (d, s) in pixels {
+                        let bg = RawU16::new(u16::from_be_bytes([d[0], d[1]])).into();
+                        let fg = RawU16::new(u16::from_be_bytes([s[0], s[1]])).into();
+                        let color = RawU16::from(blend(bg, fg, alpha)).into_inner();
+                        d.copy_from_slice(&color.to_be_bytes());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Mirrors the contents along the vertical axis in place.
+    pub fn flip_horizontal(&mut self) {
+        let row_len = self.width as usize * 2;
+        let len = self.used_len();
+        for row in self.buffer[..len].chunks_exact_mut(row_len.max(1)) {
+            // Reversing the bytes swaps the pixel order and the bytes of each pixel.
+            row.reverse();
+            for pixel in row.chunks_exact_mut(2) {
+                pixel.swap(0, 1);
+            }
+        }
+    }
+
+    /// Mirrors the contents along the horizontal axis in place.
+    pub fn flip_vertical(&mut self) {
+        let row_len = self.width as usize * 2;
+        let rows = self.used_len() / row_len.max(1);
+        for y in 0..rows / 2 {
+            let (top, bottom) = self.buffer.split_at_mut((rows - 1 - y) * row_len);
+            top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+
+    /// Rotates the contents by 180 degrees in place.
+    pub fn rotate_180(&mut self) {
+        let len = self.used_len();
+        self.buffer[..len].reverse();
+        for pixel in self.buffer[..len].chunks_exact_mut(2) {
+            pixel.swap(0, 1);
+        }
+    }
+
+    /// Writes the contents rotated 90 degrees clockwise into `dest`.
+    ///
+    /// `dest` gets the exchanged dimensions, pixels that don't fit its buffer are skipped.
+    pub fn rotate_90_into<const M: usize>(&self, dest: &mut Frame<M>) {
+        self.rotate_into(dest, |x, y, _, height| (height - 1 - y, x));
+    }
+
+    /// Writes the contents rotated 270 degrees clockwise into `dest`.
+    ///
+    /// `dest` gets the exchanged dimensions, pixels that don't fit its buffer are skipped.
+    pub fn rotate_270_into<const M: usize>(&self, dest: &mut Frame<M>) {
+        self.rotate_into(dest, |x, y, width, _| (y, width - 1 - x));
+    }
+
+    /// Returns a copy of the frame rotated 90 degrees clockwise.
+    #[must_use]
+    pub fn rotated_90(&self) -> Self {
+        let mut dest = Frame::new(self.height, self.width, self.orientation, [0; N]);
+        self.rotate_90_into(&mut dest);
+        dest
+    }
+
+    /// Returns a copy of the frame rotated by 180 degrees.
+    #[must_use]
+    pub fn rotated_180(&self) -> Self {
+        let mut dest = Frame::new(self.width, self.height, self.orientation, self.buffer);
+        dest.rotate_180();
+        dest
+    }
+
+    /// Returns a copy of the frame rotated 270 degrees clockwise.
+    #[must_use]
+    pub fn rotated_270(&self) -> Self {
+        let mut dest = Frame::new(self.height, self.width, self.orientation, [0; N]);
+        self.rotate_270_into(&mut dest);
+        dest
+    }
+
+    /// Copies every pixel to the destination coords returned by `map(x, y, width, height)`.
+    fn rotate_into<const M: usize, F>(&self, dest: &mut Frame<M>, map: F)
+    where
+        F: Fn(u32, u32, u32, u32) -> (u32, u32),
+    {
+        dest.width = self.height;
+        dest.height = self.width;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (dx, dy) = map(x, y, self.width, self.height);
+                let src = self.byte_index(x, y);
+                let dst = dest.byte_index(dx, dy);
+                let pixel = self.buffer.get(src..src + 2);
+                let target = dest.buffer.get_mut(dst..dst + 2);
+                if let (Some(pixel), Some(target)) = (pixel, target) {
+                    target.copy_from_slice(pixel);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{buffer_size, Orientation};
+
+    const FILL: u16 = 0xFFFF;
+
+    fn color(raw: u16) -> Rgb565 {
+        RawU16::new(raw).into()
+    }
+
+    /// Returns a `width` x `height` frame whose pixels are numbered from 1, row by row.
+    fn numbered<const N: usize>(width: u32, height: u32) -> Frame<N> {
+        let mut frame = Frame::new_black(width, height, Orientation::Landscape);
+        for y in 0..height as u16 {
+            for x in 0..width as u16 {
+                frame.set_pixel(x, y, color(y * width as u16 + x + 1));
+            }
+        }
+        frame
+    }
+
+    /// Returns the raw pixels row by row.
+    fn pixels<const N: usize>(frame: &Frame<N>) -> Vec<u16> {
+        (0..frame.height as u16)
+            .flat_map(|y| (0..frame.width as u16).map(move |x| (x, y)))
+            .map(|(x, y)| RawU16::from(frame.get_pixel(x, y).unwrap()).into_inner())
+            .collect()
+    }
+
+    type Small = Frame<{ buffer_size(3, 2) }>;
+
+    #[test]
+    fn scroll_vertical_known_answer() {
+        let mut frame: Small = numbered(3, 2);
+        frame.scroll_up(1, color(FILL));
+        assert_eq!(pixels(&frame), [4, 5, 6, FILL, FILL, FILL]);
+
+        let mut frame: Small = numbered(3, 2);
+        frame.scroll_down(1, color(FILL));
+        assert_eq!(pixels(&frame), [FILL, FILL, FILL, 1, 2, 3]);
+
+        let mut frame: Small = numbered(3, 2);
+        frame.scroll_up(0, color(FILL));
+        frame.scroll_down(0, color(FILL));
+        assert_eq!(pixels(&frame), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn scroll_horizontal_known_answer() {
+        let mut frame: Small = numbered(3, 2);
+        frame.scroll_left(1, color(FILL));
+        assert_eq!(pixels(&frame), [2, 3, FILL, 5, 6, FILL]);
+
+        let mut frame: Small = numbered(3, 2);
+        frame.scroll_right(2, color(FILL));
+        assert_eq!(pixels(&frame), [FILL, FILL, 1, FILL, FILL, 4]);
+    }
+
+    #[test]
+    fn scrolling_past_the_edge_fills_everything() {
+        for scroll in [
+            Small::scroll_up,
+            Small::scroll_down,
+            Small::scroll_left,
+            Small::scroll_right,
+        ] {
+            let mut frame: Small = numbered(3, 2);
+            scroll(&mut frame, 100, color(FILL));
+            assert_eq!(pixels(&frame), [FILL; 6]);
+        }
+        // Zero sized frames have nothing to move
+        let mut frame: Small = numbered(0, 2);
+        frame.scroll_left(1, color(FILL));
+        frame.scroll_up(1, color(FILL));
+        assert!(pixels(&frame).is_empty());
+    }
+
+    #[test]
+    fn flips_and_rotate_180_known_answer() {
+        let mut frame: Small = numbered(3, 2);
+        frame.flip_horizontal();
+        assert_eq!(pixels(&frame), [3, 2, 1, 6, 5, 4]);
+
+        let mut frame: Small = numbered(3, 2);
+        frame.flip_vertical();
+        assert_eq!(pixels(&frame), [4, 5, 6, 1, 2, 3]);
+
+        // The middle row of an odd height stays in place
+        let mut frame: Small = numbered(1, 3);
+        frame.flip_vertical();
+        assert_eq!(pixels(&frame), [3, 2, 1]);
+
+        let frame: Small = numbered(3, 2);
+        assert_eq!(pixels(&frame.rotated_180()), [6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn rotate_90_and_270_known_answer() {
+        let frame: Small = numbered(3, 2);
+        let rotated = frame.rotated_90();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(pixels(&rotated), [4, 1, 5, 2, 6, 3]);
+
+        let rotated = frame.rotated_270();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(pixels(&rotated), [3, 6, 2, 5, 1, 4]);
+        assert_eq!(pixels(&rotated.rotated_90()), pixels(&frame));
+    }
+
+    #[test]
+    fn rotating_into_a_small_buffer_skips_the_rest() {
+        let frame: Small = numbered(3, 2);
+        let mut dest = Frame::<{ buffer_size(2, 2) }>::new_black(2, 2, Orientation::Landscape);
+        frame.rotate_90_into(&mut dest);
+        assert_eq!((dest.width, dest.height), (2, 3));
+        let rows: Vec<_> = (0..2)
+            .flat_map(|y| (0..2).map(move |x| (x, y)))
+            .map(|(x, y)| RawU16::from(dest.get_pixel(x, y).unwrap()).into_inner())
+            .collect();
+        assert_eq!(rows, [4, 1, 5, 2]);
+    }
+
+    fn blit(src_rect: Rectangle, dest: Point) -> Vec<u16> {
+        let src: Small = numbered(3, 2);
+        let mut frame = Small::new_black(3, 2, Orientation::Landscape);
+        frame.blit(&src, src_rect, dest);
+        pixels(&frame)
+    }
+
+    #[test]
+    fn blit_clips_known_answer() {
+        let size = Size::new(3, 2);
+        let rect = Rectangle::new(Point::new(1, 0), Size::new(2, 2));
+        assert_eq!(blit(rect, Point::zero()), [2, 3, 0, 5, 6, 0]);
+        // Destination partly above and left of the frame
+        let rect = Rectangle::new(Point::zero(), size);
+        assert_eq!(blit(rect, Point::new(-1, -1)), [5, 6, 0, 0, 0, 0]);
+        // Destination partly below and right of the frame
+        assert_eq!(blit(rect, Point::new(2, 1)), [0, 0, 0, 0, 0, 1]);
+        // Source area partly outside the source keeps its position relative to `dest`
+        let rect = Rectangle::new(Point::new(-1, -1), size);
+        assert_eq!(blit(rect, Point::zero()), [0, 0, 0, 0, 1, 2]);
+        // Nothing visible
+        assert_eq!(blit(rect, Point::new(3, 0)), [0; 6]);
+        let rect = Rectangle::new(Point::new(3, 2), size);
+        assert_eq!(blit(rect, Point::zero()), [0; 6]);
+    }
+
+    #[test]
+    fn blit_keyed_and_blended_known_answer() {
+        let src: Small = numbered(3, 2);
+        let area = src.bounding_box();
+        let mut frame = Small::new(3, 2, Orientation::Landscape, [0xFF; buffer_size(3, 2)]);
+        frame.blit_keyed(&src, area, Point::zero(), color(2));
+        assert_eq!(pixels(&frame), [1, FILL, 3, 4, 5, 6]);
+
+        let mut frame = Small::new(3, 2, Orientation::Landscape, [0xFF; buffer_size(3, 2)]);
+        frame.blit_blended(&src, area, Point::zero(), 0);
+        assert_eq!(pixels(&frame), [FILL; 6]);
+        frame.blit_blended(&src, area, Point::zero(), 255);
+        assert_eq!(pixels(&frame), [1, 2, 3, 4, 5, 6]);
+        // Half way between black and white
+        let white = Small::new(3, 2, Orientation::Landscape, [0xFF; buffer_size(3, 2)]);
+        let mut frame = Small::new_black(3, 2, Orientation::Landscape);
+        frame.blit_blended(&white, area, Point::zero(), 128);
+        assert_eq!(frame.get_pixel(0, 0), Some(Rgb565::new(16, 32, 16)));
+    }
+}
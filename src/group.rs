@@ -0,0 +1,75 @@
+//! Several displays sharing one SPI bus.
+//!
+//! Each display gets its own `SpiDevice`, e.g. from
+//! `embassy_embedded_hal::shared_bus`, so chip select is handled per device
+//! and the DC pins are separate:
+//!
+//! ```ignore
+//! let bus = Mutex::<NoopRawMutex, _>::new(spi);
+//! let a = ST7735::new(SpiDevice::new(&bus, cs_a), dc_a, rst_a, config);
+//! let b = ST7735::new(SpiDevice::new(&bus, cs_b), dc_b, rst_b, config);
+//! let mut group = DisplayGroup::new([a, b]);
+//! group.flush_all().await?;
+//! ```
+use crate::tiled::Tile;
+
+/// Pixels written to one display before moving on to the next.
+const DEFAULT_CHUNK_PIXELS: usize = 1024;
+
+/// `D` displays on a shared bus, flushed together.
+pub struct DisplayGroup<P, const D: usize> {
+    displays: [P; D],
+    /// Pixels per display and turn of `flush_all`
+    chunk_pixels: usize,
+}
+
+impl<P: Tile, const D: usize> DisplayGroup<P, D> {
+    #[must_use]
+    pub fn new(displays: [P; D]) -> Self {
+        Self {
+            displays,
+            chunk_pixels: DEFAULT_CHUNK_PIXELS,
+        }
+    }
+
+    /// Sets the pixels written to one display before `flush_all` moves on to the next.
+    #[must_use]
+    pub fn with_chunk_pixels(mut self, chunk_pixels: usize) -> Self {
+        self.chunk_pixels = chunk_pixels.max(1);
+        self
+    }
+
+    /// Returns the displays, e.g. to initialize them or draw into them.
+    pub fn displays_mut(&mut self) -> &mut [P; D] {
+        &mut self.displays
+    }
+
+    /// Returns the displays.
+    pub fn into_inner(self) -> [P; D] {
+        self.displays
+    }
+
+    /// Transfers the framebuffers of all displays, taking turns chunk by chunk.
+    ///
+    /// Every display gets a memory write started up front, then the displays
+    /// receive `chunk_pixels` pixels each in turn until all are done. The
+    /// controllers continue a memory write across chip select cycles, so a
+    /// large or slowly clocked panel doesn't hold back the others.
+    pub async fn flush_all(&mut self) -> Result<(), P::FlushError> {
+        for display in &mut self.displays {
+            display.begin_flush().await?;
+        }
+        let mut done = [false; D];
+        let mut start = 0;
+        while done.contains(&false) {
+            for (display, done) in self.displays.iter_mut().zip(&mut done) {
+                if !*done {
+                    *done =
+                        display.flush_pixels(start, self.chunk_pixels).await? < self.chunk_pixels;
+                }
+            }
+            start += self.chunk_pixels;
+        }
+        Ok(())
+    }
+}
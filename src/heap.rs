@@ -0,0 +1,367 @@
+//! Optional heap buffers degrading to slower paths when the heap runs out,
+//! requires the `alloc` feature.
+//!
+//! `HeapFrames` always holds the frame that is flushed. The back frame for
+//! double buffering and the compositor layers are optional: if they cannot be
+//! allocated the same rendering code keeps working on the single frame, and
+//! `capabilities` reports what is missing.
+//!
+//! ```ignore
+//! let mut frames = HeapFrames::<{ buffer_size(160, 128) }>::new(160, 128, Orientation::Landscape)
+//!     .ok_or(OutOfMemory)?
+//!     .with_double_buffer()
+//!     .with_layers(2, Rgb565::MAGENTA);
+//! if frames.capabilities().is_degraded() {
+//!     defmt::warn!("{}", frames.capabilities());
+//! }
+//! loop {
+//!     frames
+//!         .flush_render(&mut display, |target| {
+//!             background.draw(target.layer_mut(0).unwrap())?;
+//!             hud.draw(target.layer_mut(1).unwrap())
+//!         })
+//!         .await?;
+//! }
+//! ```
+use alloc::alloc::{alloc_zeroed, Layout};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::mem;
+
+use embedded_graphics_core::pixelcolor::{raw::RawU16, Rgb565};
+use embedded_graphics_core::prelude::*;
+use embedded_hal::digital::OutputPin;
+
+use crate::layers::composite;
+use crate::{
+    Backlight, DoubleBuffered, Error, Frame, Model, Orientation, WriteOnlyInterface, ST7735IF,
+};
+
+/// Optional buffers a `HeapFrames` got, see `HeapFrames::capabilities`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capabilities {
+    /// Whether rendering overlaps the flush in a back frame.
+    pub double_buffer: bool,
+    /// Whether the back frame was requested.
+    pub double_buffer_requested: bool,
+    /// Number of layers with their own frame, 0 if they are drawn straight into the target.
+    pub layers: usize,
+    /// Number of layers requested.
+    pub layers_requested: usize,
+}
+
+impl Capabilities {
+    /// Whether a requested buffer could not be allocated.
+    #[must_use]
+    pub fn is_degraded(&self) -> bool {
+        self.double_buffer != self.double_buffer_requested || self.layers != self.layers_requested
+    }
+}
+
+/// Allocates a black frame straight on the heap, `None` if the heap is short.
+fn try_alloc_frame<const N: usize>(
+    width: u32,
+    height: u32,
+    orientation: Orientation,
+) -> Option<Box<Frame<N>>> {
+    let layout = Layout::new::<Frame<N>>();
+    // SAFETY: the layout is not zero sized, it holds the width and height. All
+    // zero bytes are a valid empty portrait frame, `Orientation::Portrait` is 0.
+    let mut frame = unsafe {
+        let ptr = alloc_zeroed(layout).cast::<Frame<N>>();
+        if ptr.is_null() {
+            return None;
+        }
+        Box::from_raw(ptr)
+    };
+    frame.width = width;
+    frame.height = height;
+    frame.orientation = orientation;
+    Some(frame)
+}
+
+/// Frame to flush with an optional back frame and optional layers on the heap.
+pub struct HeapFrames<const N: usize> {
+    front: Box<Frame<N>>,
+    back: Option<Box<Frame<N>>>,
+    double_buffer_requested: bool,
+    /// All requested layers or none
+    layers: Vec<Box<Frame<N>>>,
+    layers_requested: usize,
+    /// Transparent color of the layers
+    key: Rgb565,
+}
+
+impl<const N: usize> HeapFrames<N> {
+    /// Allocates the black `width` x `height` frame to flush, `None` if the heap is short.
+    #[must_use]
+    pub fn new(width: u32, height: u32, orientation: Orientation) -> Option<Self> {
+        Some(Self {
+            front: try_alloc_frame(width, height, orientation)?,
+            back: None,
+            double_buffer_requested: false,
+            layers: Vec::new(),
+            layers_requested: 0,
+            key: Rgb565::BLACK,
+        })
+    }
+
+    /// Tries to allocate a back frame, so rendering overlaps the flush.
+    ///
+    /// Without it `flush_render` renders after the flush into the same frame.
+    #[must_use]
+    pub fn with_double_buffer(self) -> Self {
+        let Frame {
+            width,
+            height,
+            orientation,
+            ..
+        } = *self.front;
+        self.with_back(|| try_alloc_frame(width, height, orientation))
+    }
+
+    fn with_back(mut self, alloc: impl FnOnce() -> Option<Box<Frame<N>>>) -> Self {
+        self.double_buffer_requested = true;
+        self.back = alloc();
+        self
+    }
+
+    /// Tries to allocate `count` layers, cleared to the transparent `key` color
+    /// and composited bottom to top.
+    ///
+    /// Either all layers are allocated or none. Without them `layer_mut`
+    /// returns the target frame, so the layers are drawn straight into it, in
+    /// order, and have to be redrawn for every frame.
+    #[must_use]
+    pub fn with_layers(self, count: usize, key: Rgb565) -> Self {
+        let Frame {
+            width,
+            height,
+            orientation,
+            ..
+        } = *self.front;
+        self.with_layer_frames(count, key, || try_alloc_frame(width, height, orientation))
+    }
+
+    fn with_layer_frames(
+        mut self,
+        count: usize,
+        key: Rgb565,
+        mut alloc: impl FnMut() -> Option<Box<Frame<N>>>,
+    ) -> Self {
+        self.layers_requested = count;
+        self.key = key;
+        let mut layers = Vec::new();
+        if layers.try_reserve_exact(count).is_ok() {
+            while layers.len() < count {
+                let Some(mut layer) = alloc() else {
+                    layers = Vec::new();
+                    break;
+                };
+                let key = RawU16::from(key).into_inner().to_be_bytes();
+                for pixel in layer.buffer.chunks_exact_mut(2) {
+                    pixel.copy_from_slice(&key);
+                }
+                layers.push(layer);
+            }
+        }
+        self.layers = layers;
+        self
+    }
+
+    /// Reports the optional buffers that were allocated.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            double_buffer: self.back.is_some(),
+            double_buffer_requested: self.double_buffer_requested,
+            layers: self.layers.len(),
+            layers_requested: self.layers_requested,
+        }
+    }
+
+    /// Frame the next `flush_render` flushes.
+    pub fn front(&self) -> &Frame<N> {
+        &self.front
+    }
+
+    /// Flushes the frame rendered by the previous call and renders the next one.
+    ///
+    /// With a back frame `render` draws into it while the front frame is in
+    /// flight, see `ST7735IF::flush_double_buffered`. Without it `render` runs
+    /// after the flush, into the frame just flushed. Either way the result of
+    /// `render` is shown by the next call.
+    pub async fn flush_render<DI, RST, BL, M, E, F>(
+        &mut self,
+        display: &mut ST7735IF<DI, RST, BL, M>,
+        render: F,
+    ) -> Result<(), Error<E>>
+    where
+        DI: WriteOnlyInterface<Error = E>,
+        RST: OutputPin<Error = Infallible>,
+        BL: Backlight,
+        M: Model,
+        F: FnOnce(&mut HeapTarget<'_, N>),
+    {
+        let layers = &mut self.layers;
+        let layers_requested = self.layers_requested;
+        let key = self.key;
+        let render = |frame: &mut Frame<N>| {
+            let mut target = HeapTarget {
+                frame,
+                layers,
+                layers_requested,
+            };
+            render(&mut target);
+            target.compose(key);
+        };
+        match &mut self.back {
+            Some(back) => {
+                let mut frames = DoubleBuffered::new(&mut *self.front, &mut **back);
+                display.flush_double_buffered(&mut frames, render).await?;
+                mem::swap(&mut self.front, back);
+                Ok(())
+            }
+            None => {
+                display.flush_frame(&self.front).await?;
+                render(&mut self.front);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Frames `render` draws into, see `HeapFrames::flush_render`.
+pub struct HeapTarget<'a, const N: usize> {
+    frame: &'a mut Frame<N>,
+    layers: &'a mut [Box<Frame<N>>],
+    layers_requested: usize,
+}
+
+impl<const N: usize> HeapTarget<'_, N> {
+    /// Frame shown by the next flush, drawn over by the layers.
+    pub fn frame_mut(&mut self) -> &mut Frame<N> {
+        self.frame
+    }
+
+    /// Frame of layer `index`, the target frame itself if the layers are
+    /// degraded, `None` if there is no such layer.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Frame<N>> {
+        if index >= self.layers_requested {
+            return None;
+        }
+        match self.layers.get_mut(index) {
+            Some(layer) => Some(layer),
+            None => Some(self.frame),
+        }
+    }
+
+    /// Composites the layers over the target frame, starting from black.
+    fn compose(&mut self, key: Rgb565) {
+        if self.layers.is_empty() {
+            return;
+        }
+        self.frame.buffer.fill(0);
+        for layer in self.layers.iter() {
+            composite(self.frame, layer, 255, Some(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use embedded_graphics_core::pixelcolor::RgbColor;
+
+    use super::*;
+    use crate::mock::Recorder;
+    use crate::{buffer_size, Config};
+
+    const N: usize = buffer_size(4, 2);
+
+    /// Returns the data written after the last RAMWR.
+    fn flushed(recorder: &Recorder) -> std::vec::Vec<u8> {
+        recorder.commands("DC").pop().unwrap().1
+    }
+
+    fn frames() -> HeapFrames<N> {
+        HeapFrames::new(4, 2, Orientation::Landscape).unwrap()
+    }
+
+    fn draw(target: &mut HeapTarget<'_, N>) {
+        target.layer_mut(0).unwrap().set_pixel(0, 0, Rgb565::BLUE);
+        target.layer_mut(1).unwrap().set_pixel(1, 0, Rgb565::RED);
+    }
+
+    fn check_renders_layers(mut frames: HeapFrames<N>) {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        block_on(frames.flush_render(&mut display, draw)).unwrap();
+        block_on(frames.flush_render(&mut display, draw)).unwrap();
+        let data = flushed(&recorder);
+        assert_eq!(data[..6], [0x00, 0x1F, 0xF8, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn all_buffers_allocated() {
+        let frames = frames()
+            .with_double_buffer()
+            .with_layers(2, Rgb565::MAGENTA);
+        assert!(!frames.capabilities().is_degraded());
+        check_renders_layers(frames);
+    }
+
+    #[test]
+    fn missing_buffers_degrade_to_the_single_frame() {
+        let frames = frames()
+            .with_back(|| None)
+            .with_layer_frames(2, Rgb565::MAGENTA, {
+                // Only the first layer fits.
+                let mut allocated = 0;
+                move || {
+                    allocated += 1;
+                    if allocated > 1 {
+                        return None;
+                    }
+                    try_alloc_frame(4, 2, Orientation::Landscape)
+                }
+            });
+        assert_eq!(
+            frames.capabilities(),
+            Capabilities {
+                double_buffer: false,
+                double_buffer_requested: true,
+                layers: 0,
+                layers_requested: 2,
+            }
+        );
+        assert!(frames.capabilities().is_degraded());
+        check_renders_layers(frames);
+    }
+
+    #[test]
+    fn layer_key_is_transparent() {
+        let mut frames = frames().with_layers(2, Rgb565::MAGENTA);
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        let render = |target: &mut HeapTarget<'_, N>| {
+            target.layer_mut(0).unwrap().clear(Rgb565::GREEN).unwrap();
+            target.layer_mut(1).unwrap().set_pixel(3, 1, Rgb565::RED);
+            assert!(target.layer_mut(2).is_none());
+        };
+        block_on(frames.flush_render(&mut display, render)).unwrap();
+        assert_eq!(frames.front().get_pixel(0, 0), Some(Rgb565::GREEN));
+        assert_eq!(frames.front().get_pixel(3, 1), Some(Rgb565::RED));
+    }
+}
@@ -1,25 +1,42 @@
 /// ST7735 instructions.
-#[derive(Debug, Clone, Copy)]
+///
+/// Covers the system and panel commands of the ST7735R and ST7735S,
+/// including the ones the driver does not issue itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     NOP = 0x00,
     SWRESET = 0x01,
     RDDID = 0x04,
     RDDST = 0x09,
+    RDDPM = 0x0A,
+    RDDMADCTL = 0x0B,
+    RDDCOLMOD = 0x0C,
+    RDDIM = 0x0D,
+    RDDSM = 0x0E,
+    RDDSDR = 0x0F,
     SLPIN = 0x10,
     SLPOUT = 0x11,
     PTLON = 0x12,
     NORON = 0x13,
     INVOFF = 0x20,
     INVON = 0x21,
+    GAMSET = 0x26,
     DISPOFF = 0x28,
     DISPON = 0x29,
     CASET = 0x2A,
     RASET = 0x2B,
     RAMWR = 0x2C,
+    RGBSET = 0x2D,
     RAMRD = 0x2E,
     PTLAR = 0x30,
-    COLMOD = 0x3A,
+    VSCRDEF = 0x33,
+    TEOFF = 0x34,
+    TEON = 0x35,
     MADCTL = 0x36,
+    VSCSAD = 0x37,
+    IDMOFF = 0x38,
+    IDMON = 0x39,
+    COLMOD = 0x3A,
     FRMCTR1 = 0xB1,
     FRMCTR2 = 0xB2,
     FRMCTR3 = 0xB3,
@@ -31,11 +48,18 @@ pub enum Instruction {
     PWCTR4 = 0xC3,
     PWCTR5 = 0xC4,
     VMCTR1 = 0xC5,
+    VMOFCTR = 0xC7,
+    WRID2 = 0xD1,
+    WRID3 = 0xD2,
+    NVCTR1 = 0xD9,
     RDID1 = 0xDA,
     RDID2 = 0xDB,
     RDID3 = 0xDC,
     RDID4 = 0xDD,
-    PWCTR6 = 0xFC,
+    NVCTR2 = 0xDE,
+    NVCTR3 = 0xDF,
     GMCTRP1 = 0xE0,
     GMCTRN1 = 0xE1,
+    EXTCTRL = 0xF0,
+    PWCTR6 = 0xFC,
 }
@@ -0,0 +1,115 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use super::{
+    raw_read_len, strip_dummy_bits, ChunkedWrite, ReadInterface, WriteOnlyInterface, MAX_READ_LEN,
+};
+use crate::DcTiming;
+
+/// 4-wire SPI transport over a blocking `embedded_hal::spi::SpiDevice`.
+///
+/// Behaves like `SpiInterface`, but every write blocks until the transfer is
+/// done, so the driver futures complete on their first poll. Used by
+/// `BlockingST7735` for applications without an async executor.
+pub struct BlockingSpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+{
+    spi: SPI,
+    dc: DC,
+    /// Delays around data/command phase boundaries
+    dc_timing: DcTiming,
+    /// Maximum number of bytes per SPI write
+    max_chunk_size: Option<usize>,
+}
+
+impl<SPI, DC> BlockingSpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+{
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self {
+            spi,
+            dc,
+            dc_timing: DcTiming::default(),
+            max_chunk_size: None,
+        }
+    }
+
+    /// Sets the delays inserted around data/command phase boundaries.
+    #[must_use]
+    pub fn with_dc_timing(mut self, dc_timing: DcTiming) -> Self {
+        self.dc_timing = dc_timing;
+        self
+    }
+
+    /// Sets the maximum number of bytes per SPI write, see `Config::max_chunk_size`.
+    #[must_use]
+    pub fn with_max_chunk_size(mut self, max_chunk_size: Option<usize>) -> Self {
+        self.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    /// Writes bytes in as few transactions as possible, applying the configured
+    /// `DcTiming` and `max_chunk_size`.
+    fn write(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
+        let Some(mut write) = ChunkedWrite::new(data, self.dc_timing, self.max_chunk_size) else {
+            return self.spi.write(data);
+        };
+        let mut ops = ChunkedWrite::operations();
+        while let Some(len) = write.next_transaction(&mut ops) {
+            self.spi.transaction(&mut ops[..len])?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, DC> WriteOnlyInterface for BlockingSpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+{
+    type Error = SPI::Error;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_low().ok();
+        self.write(&[command])?;
+        if !params.is_empty() {
+            self.dc.set_high().ok();
+            self.write(params)?;
+        }
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_high().ok();
+        self.write(data)
+    }
+}
+
+/// Command and response are read in one transaction, so DC stays low while
+/// the controller drives the data line.
+impl<SPI, DC> ReadInterface for BlockingSpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+{
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let mut raw = [0; MAX_READ_LEN + 1];
+        let raw = &mut raw[..raw_read_len(buf.len(), dummy_bits)];
+        self.dc.set_low().ok();
+        self.spi
+            .transaction(&mut [Operation::Write(&[command]), Operation::Read(raw)])?;
+        strip_dummy_bits(raw, dummy_bits, buf);
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! Transports the display controller is driven through.
+use core::iter::Peekable;
+use core::slice::Chunks;
+
+use embedded_hal::spi::Operation;
+
+use crate::DcTiming;
+
+mod blocking_spi;
+mod parallel;
+mod retry;
+mod spi;
+mod spi_3wire;
+mod spi_bus;
+#[cfg(feature = "blocking-spi")]
+mod yielding_spi;
+
+pub use blocking_spi::BlockingSpiInterface;
+pub use parallel::{DataBus8, ParallelInterface, PortWriter};
+pub use retry::{RetryError, RetryInterface, RetryPolicy};
+pub use spi::SpiInterface;
+pub use spi_3wire::Spi3WireInterface;
+pub use spi_bus::SpiBusInterface;
+#[cfg(feature = "blocking-spi")]
+pub use yielding_spi::YieldingSpi;
+
+/// Longest response `ReadInterface::read_command` reads, in bytes.
+pub const MAX_READ_LEN: usize = 8;
+
+/// Upper bound of chunked writes issued within one SPI transaction.
+const MAX_CHUNKS_PER_TRANSACTION: usize = 16;
+
+/// Write-only transport to the display controller.
+///
+/// Implementations take care of the data/command signalling, the driver only
+/// deals in command bytes, parameters and pixel data.
+#[allow(async_fn_in_trait)]
+pub trait WriteOnlyInterface {
+    type Error;
+
+    /// Writes a command byte followed by its parameter bytes.
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes data bytes, continuing the data phase of the last command.
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes several commands with their parameters, e.g. an address window update.
+    ///
+    /// Transports that can frame them in one transaction override this, the
+    /// default writes them one by one. The last command may be followed by
+    /// `write_data`, e.g. after RAMWR.
+    async fn write_commands(&mut self, commands: &[(u8, &[u8])]) -> Result<(), Self::Error> {
+        for &(command, params) in commands {
+            self.write_command(command, params).await?;
+        }
+        Ok(())
+    }
+
+    /// Whether `error` means the interface gave up, the driver reports it as `Error::Timeout`.
+    fn is_timeout(error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
+}
+
+/// Transport that can also read command responses from the controller.
+///
+/// Needs the controller's serial data output wired to the MCU, which many
+/// modules leave unconnected.
+#[allow(async_fn_in_trait)]
+pub trait ReadInterface: WriteOnlyInterface {
+    /// Writes `command` and reads up to `MAX_READ_LEN` response bytes into `buf`.
+    ///
+    /// The controller clocks out `dummy_bits` (fewer than 8) before the
+    /// response, e.g. one for the 24 and 32 bit reads over serial, these are
+    /// skipped.
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Number of raw bytes to clock in for a response of `len` bytes after `dummy_bits`.
+fn raw_read_len(len: usize, dummy_bits: u8) -> usize {
+    len.min(MAX_READ_LEN) + usize::from(dummy_bits > 0)
+}
+
+/// Shifts the leading `dummy_bits` out of `raw` and stores the response in `buf`.
+fn strip_dummy_bits(raw: &[u8], dummy_bits: u8, buf: &mut [u8]) {
+    let shift = u32::from(dummy_bits.min(7));
+    for (i, byte) in buf.iter_mut().take(MAX_READ_LEN).enumerate() {
+        let wide = u16::from_be_bytes([raw[i], raw.get(i + 1).copied().unwrap_or(0)]);
+        *byte = ((wide << shift) >> 8) as u8;
+    }
+}
+
+/// Operations of one transaction of a `ChunkedWrite`.
+type ChunkedOperations<'a> = [Operation<'a, u8>; MAX_CHUNKS_PER_TRANSACTION + 2];
+
+/// Write split into `SpiDevice` transactions of at most
+/// `MAX_CHUNKS_PER_TRANSACTION` chunks, each framed by the `DcTiming` delays.
+///
+/// Shared by the async and blocking SPI interfaces, which only differ in how
+/// they issue the transactions.
+struct ChunkedWrite<'a> {
+    chunks: Peekable<Chunks<'a, u8>>,
+    dc_timing: DcTiming,
+}
+
+impl<'a> ChunkedWrite<'a> {
+    /// Returns `None` if `data` goes out as one plain write, without delays or chunking.
+    fn new(data: &'a [u8], dc_timing: DcTiming, max_chunk_size: Option<usize>) -> Option<Self> {
+        let chunk_size = max_chunk_size.unwrap_or(data.len()).max(1);
+        if dc_timing.setup_ns == 0 && dc_timing.hold_ns == 0 && data.len() <= chunk_size {
+            return None;
+        }
+        Some(Self {
+            chunks: data.chunks(chunk_size).peekable(),
+            dc_timing,
+        })
+    }
+
+    /// Buffer for `next_transaction`.
+    fn operations() -> ChunkedOperations<'a> {
+        core::array::from_fn(|_| Operation::DelayNs(0))
+    }
+
+    /// Fills `ops` with the next transaction and returns its number of
+    /// operations, `None` once all chunks are written.
+    fn next_transaction(&mut self, ops: &mut ChunkedOperations<'a>) -> Option<usize> {
+        self.chunks.peek()?;
+        let DcTiming { setup_ns, hold_ns } = self.dc_timing;
+        let mut len = 0;
+        if setup_ns > 0 {
+            ops[len] = Operation::DelayNs(setup_ns);
+            len += 1;
+        }
+        for chunk in self.chunks.by_ref().take(MAX_CHUNKS_PER_TRANSACTION) {
+            ops[len] = Operation::Write(chunk);
+            len += 1;
+        }
+        if hold_ns > 0 {
+            ops[len] = Operation::DelayNs(hold_ns);
+            len += 1;
+        }
+        Some(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_write_needs_no_transaction() {
+        assert!(ChunkedWrite::new(&[0; 64], DcTiming::default(), None).is_none());
+        assert!(ChunkedWrite::new(&[0; 64], DcTiming::default(), Some(64)).is_none());
+    }
+
+    #[test]
+    fn chunks_are_framed_by_the_dc_delays() {
+        let data = [0u8; 40];
+        let dc_timing = DcTiming {
+            setup_ns: 10,
+            hold_ns: 20,
+        };
+        let mut write = ChunkedWrite::new(&data, dc_timing, Some(2)).unwrap();
+        let mut ops = ChunkedWrite::operations();
+        let mut transactions = 0;
+        let mut written = 0;
+        while let Some(len) = write.next_transaction(&mut ops) {
+            let ops = &ops[..len];
+            assert!(matches!(ops[0], Operation::DelayNs(10)));
+            assert!(matches!(ops[len - 1], Operation::DelayNs(20)));
+            for op in &ops[1..len - 1] {
+                let Operation::Write(chunk) = op else {
+                    panic!("expected a write");
+                };
+                written += chunk.len();
+            }
+            transactions += 1;
+        }
+        // 20 chunks, at most 16 per transaction
+        assert_eq!(transactions, 2);
+        assert_eq!(written, 40);
+    }
+}
@@ -0,0 +1,100 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+
+use super::WriteOnlyInterface;
+
+/// 8-bit data bus of an 8080-style parallel interface.
+pub trait DataBus8 {
+    /// Puts a byte on the data lines.
+    fn set_value(&mut self, value: u8);
+}
+
+/// Data lines as individual pins, `D0` first.
+impl<P> DataBus8 for [P; 8]
+where
+    P: OutputPin<Error = Infallible>,
+{
+    fn set_value(&mut self, value: u8) {
+        for (bit, pin) in self.iter_mut().enumerate() {
+            if value & (1 << bit) != 0 {
+                pin.set_high().ok();
+            } else {
+                pin.set_low().ok();
+            }
+        }
+    }
+}
+
+/// Data lines written through a closure, e.g. a single GPIO port register write.
+pub struct PortWriter<F>(pub F)
+where
+    F: FnMut(u8);
+
+impl<F> DataBus8 for PortWriter<F>
+where
+    F: FnMut(u8),
+{
+    fn set_value(&mut self, value: u8) {
+        (self.0)(value);
+    }
+}
+
+/// 8080-style 8-bit parallel transport: data bus, data/command and write strobe.
+///
+/// Bytes are latched on the rising edge of WR. Chip select is not driven,
+/// tie it low or assert it before handing the pins over. Writing is done by
+/// bit-banging and never fails.
+pub struct ParallelInterface<BUS, DC, WR>
+where
+    BUS: DataBus8,
+    DC: OutputPin<Error = Infallible>,
+    WR: OutputPin<Error = Infallible>,
+{
+    bus: BUS,
+    dc: DC,
+    wr: WR,
+}
+
+impl<BUS, DC, WR> ParallelInterface<BUS, DC, WR>
+where
+    BUS: DataBus8,
+    DC: OutputPin<Error = Infallible>,
+    WR: OutputPin<Error = Infallible>,
+{
+    pub fn new(bus: BUS, dc: DC, mut wr: WR) -> Self {
+        wr.set_high().ok();
+        Self { bus, dc, wr }
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.wr.set_low().ok();
+            self.bus.set_value(byte);
+            self.wr.set_high().ok();
+        }
+    }
+}
+
+impl<BUS, DC, WR> WriteOnlyInterface for ParallelInterface<BUS, DC, WR>
+where
+    BUS: DataBus8,
+    DC: OutputPin<Error = Infallible>,
+    WR: OutputPin<Error = Infallible>,
+{
+    type Error = Infallible;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_low().ok();
+        self.write(&[command]);
+        self.dc.set_high().ok();
+        self.write(params);
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.dc.set_high().ok();
+        self.write(data);
+        Ok(())
+    }
+}
@@ -0,0 +1,432 @@
+use embedded_hal_async::delay::DelayNs;
+
+use super::{ReadInterface, WriteOnlyInterface};
+use crate::instruction::Instruction;
+
+/// How often and how patiently `RetryInterface` retries failed writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RetryPolicy {
+    /// Retries after the first failed attempt
+    pub retries: u8,
+    /// Delay before the first retry in microseconds, doubled for every further retry
+    pub backoff_us: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff_us: 100,
+        }
+    }
+}
+
+/// Error of a `RetryInterface`, wrapping the last error of the inner interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RetryError<E> {
+    /// All retries of the `RetryPolicy` failed, reported as `Error::Timeout`.
+    Exhausted(E),
+    /// The write was not retried, e.g. with no retries configured or a memory
+    /// write into a window the interface has not seen.
+    NotRetried(E),
+}
+
+impl<E> RetryError<E> {
+    /// Returns the error of the inner interface.
+    pub fn into_inner(self) -> E {
+        match self {
+            Self::Exhausted(error) | Self::NotRetried(error) => error,
+        }
+    }
+}
+
+/// Data phase following the last command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DataPhase {
+    /// Parameters, retried whole
+    Params,
+    /// Memory write into the tracked window, `written` bytes in
+    Memory { written: usize },
+    /// Memory write into a window set before the interface was wrapped
+    UnknownMemory,
+}
+
+/// Transport retrying failed writes, e.g. on a shared bus with transient errors.
+///
+/// A write that still fails after all retries of the `RetryPolicy` is
+/// reported as `RetryError::Exhausted`, which the driver maps to
+/// `Error::Timeout`. Commands and parameters are retried whole. A failed
+/// chunk of a memory write is not written again where the failed attempt
+/// left the controller, the write is restarted at the chunk's first pixel
+/// with a window of CASET, RASET and RAMWR, assuming 16 bit pixels. The
+/// window of the next command is then restored.
+pub struct RetryInterface<DI, D> {
+    di: DI,
+    delay: D,
+    policy: RetryPolicy,
+    /// Last CASET and RASET values `[start, end]`, if known
+    columns: Option<[u16; 2]>,
+    rows: Option<[u16; 2]>,
+    /// Whether a restarted memory write left another window in the controller
+    window_changed: bool,
+    data_phase: DataPhase,
+}
+
+impl<DI, D> RetryInterface<DI, D>
+where
+    DI: WriteOnlyInterface,
+    D: DelayNs,
+{
+    pub fn new(di: DI, delay: D, policy: RetryPolicy) -> Self {
+        Self {
+            di,
+            delay,
+            policy,
+            columns: None,
+            rows: None,
+            window_changed: false,
+            data_phase: DataPhase::Params,
+        }
+    }
+
+    /// Returns the wrapped interface.
+    ///
+    /// Writes through it are not tracked, so memory writes are not retried
+    /// until the next window is set.
+    pub fn inner_mut(&mut self) -> &mut DI {
+        self.columns = None;
+        self.rows = None;
+        self.window_changed = false;
+        self.data_phase = DataPhase::UnknownMemory;
+        &mut self.di
+    }
+
+    /// Delays for the backoff of the retry following `attempt`.
+    async fn backoff(&mut self, attempt: u8) {
+        let backoff_us = self.policy.backoff_us.saturating_mul(1 << attempt.min(31));
+        self.delay.delay_us(backoff_us).await;
+    }
+
+    /// Wraps the error of the last attempt.
+    fn error(&self, error: DI::Error, attempt: u8) -> RetryError<DI::Error> {
+        if attempt > 0 {
+            RetryError::Exhausted(error)
+        } else {
+            RetryError::NotRetried(error)
+        }
+    }
+
+    /// Updates the tracked window and data phase after `command` was written.
+    fn track(&mut self, command: u8, params: &[u8]) {
+        let range = |params: &[u8]| match *params {
+            [s0, s1, e0, e1] => Some([u16::from_be_bytes([s0, s1]), u16::from_be_bytes([e0, e1])]),
+            _ => None,
+        };
+        self.data_phase = DataPhase::Params;
+        if command == Instruction::CASET as u8 {
+            self.columns = range(params);
+        } else if command == Instruction::RASET as u8 {
+            self.rows = range(params);
+        } else if command == Instruction::RAMWR as u8 {
+            self.data_phase = match (self.columns, self.rows) {
+                (Some(_), Some(_)) => DataPhase::Memory { written: 0 },
+                _ => DataPhase::UnknownMemory,
+            };
+        }
+    }
+
+    /// Writes the tracked window again if a restarted memory write replaced it.
+    async fn restore_window(&mut self) -> Result<(), DI::Error> {
+        if let (true, Some(columns), Some(rows)) = (self.window_changed, self.columns, self.rows) {
+            self.write_window(columns, rows, false).await?;
+            self.window_changed = false;
+        }
+        Ok(())
+    }
+
+    async fn write_window(
+        &mut self,
+        [xs, xe]: [u16; 2],
+        [ys, ye]: [u16; 2],
+        memory_write: bool,
+    ) -> Result<(), DI::Error> {
+        let [xs0, xs1] = xs.to_be_bytes();
+        let [xe0, xe1] = xe.to_be_bytes();
+        let [ys0, ys1] = ys.to_be_bytes();
+        let [ye0, ye1] = ye.to_be_bytes();
+        let commands: [(u8, &[u8]); 3] = [
+            (Instruction::CASET as u8, &[xs0, xs1, xe0, xe1]),
+            (Instruction::RASET as u8, &[ys0, ys1, ye0, ye1]),
+            (Instruction::RAMWR as u8, &[]),
+        ];
+        let len = if memory_write { 3 } else { 2 };
+        self.di.write_commands(&commands[..len]).await
+    }
+
+    /// Writes `data` again as part of the memory write into `columns` x `rows`
+    /// starting `written` bytes in.
+    ///
+    /// A chunk starting within a row first fills the rest of that row through
+    /// a one row window, the rest goes into a window from the next row down.
+    async fn rewrite_memory(
+        &mut self,
+        [xs, xe]: [u16; 2],
+        [ys, ye]: [u16; 2],
+        written: usize,
+        mut data: &[u8],
+    ) -> Result<(), DI::Error> {
+        let width = usize::from(xe.saturating_sub(xs)) + 1;
+        let height = usize::from(ye.saturating_sub(ys)) + 1;
+        let pixel = written / 2 % (width * height);
+        let (row, column) = (pixel / width, pixel % width);
+        let mut y = ys + row as u16;
+        self.window_changed = true;
+        if column > 0 {
+            let len = data.len().min((width - column) * 2);
+            self.write_window([xs + column as u16, xe], [y, y], true)
+                .await?;
+            self.di.write_data(&data[..len]).await?;
+            data = &data[len..];
+            y = if y < ye { y + 1 } else { ys };
+        }
+        self.write_window([xs, xe], [y, ye], true).await?;
+        if !data.is_empty() {
+            self.di.write_data(data).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<DI, D> WriteOnlyInterface for RetryInterface<DI, D>
+where
+    DI: WriteOnlyInterface,
+    D: DelayNs,
+{
+    type Error = RetryError<DI::Error>;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.restore_window().await {
+                Ok(()) => self.di.write_command(command, params).await,
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(()) => break,
+                Err(_) if attempt < self.policy.retries => self.backoff(attempt).await,
+                Err(error) => return Err(self.error(error, attempt)),
+            }
+            attempt += 1;
+        }
+        self.track(command, params);
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let mut result = self.di.write_data(data).await;
+        let mut attempt = 0;
+        while let Err(error) = result {
+            let window = (self.columns, self.rows);
+            let resume = match (self.data_phase, window) {
+                (DataPhase::Params, _) => None,
+                (DataPhase::Memory { written }, (Some(columns), Some(rows)))
+                    if written % 2 == 0 =>
+                {
+                    Some((columns, rows, written))
+                }
+                // The position in controller memory is unknown.
+                _ => return Err(RetryError::NotRetried(error)),
+            };
+            if attempt >= self.policy.retries {
+                return Err(self.error(error, attempt));
+            }
+            self.backoff(attempt).await;
+            attempt += 1;
+            result = match resume {
+                Some((columns, rows, written)) => {
+                    self.rewrite_memory(columns, rows, written, data).await
+                }
+                None => self.di.write_data(data).await,
+            };
+        }
+        if let DataPhase::Memory { written } = &mut self.data_phase {
+            *written += data.len();
+        }
+        Ok(())
+    }
+
+    async fn write_commands(&mut self, commands: &[(u8, &[u8])]) -> Result<(), Self::Error> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.restore_window().await {
+                Ok(()) => self.di.write_commands(commands).await,
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(()) => break,
+                Err(_) if attempt < self.policy.retries => self.backoff(attempt).await,
+                Err(error) => return Err(self.error(error, attempt)),
+            }
+            attempt += 1;
+        }
+        for &(command, params) in commands {
+            self.track(command, params);
+        }
+        Ok(())
+    }
+
+    /// Errors left after all retries are timeouts, others are classified by the inner interface.
+    fn is_timeout(error: &Self::Error) -> bool {
+        match error {
+            RetryError::Exhausted(_) => true,
+            RetryError::NotRetried(error) => DI::is_timeout(error),
+        }
+    }
+}
+
+impl<DI, D> ReadInterface for RetryInterface<DI, D>
+where
+    DI: ReadInterface,
+    D: DelayNs,
+{
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let mut attempt = 0;
+        loop {
+            let result = match self.restore_window().await {
+                Ok(()) => self.di.read_command(command, dummy_bits, buf).await,
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(()) => break,
+                Err(_) if attempt < self.policy.retries => self.backoff(attempt).await,
+                Err(error) => return Err(self.error(error, attempt)),
+            }
+            attempt += 1;
+        }
+        self.track(command, &[]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use embassy_futures::block_on;
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::{NoDelay, Recorder};
+    use crate::{Config, Error, SpiInterface, ST7735IF};
+
+    /// Fails the writes whose number is in `failing`, counting from 0.
+    struct Flaky<'a> {
+        di: SpiInterface<crate::mock::RecordingSpi<'a>, crate::mock::RecordingPin<'a>>,
+        writes: Cell<usize>,
+        failing: Vec<usize>,
+    }
+
+    impl Flaky<'_> {
+        fn fails(&self) -> bool {
+            let write = self.writes.get();
+            self.writes.set(write + 1);
+            self.failing.contains(&write)
+        }
+    }
+
+    impl WriteOnlyInterface for Flaky<'_> {
+        type Error = ();
+
+        async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), ()> {
+            if self.fails() {
+                return Err(());
+            }
+            self.di.write_command(command, params).await.map_err(drop)
+        }
+
+        async fn write_data(&mut self, data: &[u8]) -> Result<(), ()> {
+            if self.fails() {
+                // Part of the chunk gets through before the failure.
+                let _: Result<(), Infallible> = self.di.write_data(&data[..1]).await;
+                return Err(());
+            }
+            self.di.write_data(data).await.map_err(drop)
+        }
+    }
+
+    fn retrying(recorder: &Recorder, failing: Vec<usize>) -> RetryInterface<Flaky<'_>, NoDelay> {
+        let di = Flaky {
+            di: SpiInterface::new(recorder.spi(), recorder.pin("DC")),
+            writes: Cell::new(0),
+            failing,
+        };
+        RetryInterface::new(di, NoDelay, RetryPolicy::default())
+    }
+
+    #[test]
+    fn failed_chunk_restarts_the_window_at_its_first_pixel() {
+        let recorder = Recorder::new();
+        // CASET, RASET, RAMWR, first chunk, failing second chunk
+        let mut di = retrying(&recorder, vec![4]);
+        let commands: [(u8, &[u8]); 3] =
+            [(0x2A, &[0, 2, 0, 5]), (0x2B, &[0, 10, 0, 11]), (0x2C, &[])];
+        block_on(di.write_commands(&commands)).unwrap();
+        block_on(di.write_data(&[1; 2])).unwrap();
+        block_on(di.write_data(&[2; 10])).unwrap();
+        block_on(di.write_command(0x2C, &[])).unwrap();
+        assert_eq!(
+            recorder.commands("DC"),
+            [
+                (0x2A, vec![0, 2, 0, 5]),
+                (0x2B, vec![0, 10, 0, 11]),
+                (0x2C, vec![1, 1, 2]),
+                // Rest of the first row from column 3
+                (0x2A, vec![0, 3, 0, 5]),
+                (0x2B, vec![0, 10, 0, 10]),
+                (0x2C, vec![2; 6]),
+                (0x2A, vec![0, 2, 0, 5]),
+                (0x2B, vec![0, 11, 0, 11]),
+                (0x2C, vec![2; 4]),
+                // Window restored for the next memory write
+                (0x2A, vec![0, 2, 0, 5]),
+                (0x2B, vec![0, 10, 0, 11]),
+                (0x2C, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_exhausted_retries_time_out() {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::with_interface(
+            retrying(&recorder, (0..4).collect()),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        let result = block_on(display.send_raw(0x00, &[]));
+        assert_eq!(result, Err(Error::Timeout(RetryError::Exhausted(()))));
+        assert!(block_on(display.send_raw(0x00, &[])).is_ok());
+
+        let recorder = Recorder::new();
+        let di = retrying(&recorder, vec![0]);
+        let policy = RetryPolicy {
+            retries: 0,
+            ..RetryPolicy::default()
+        };
+        let mut display = ST7735IF::with_interface(
+            RetryInterface::new(di.di, NoDelay, policy),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        let result = block_on(display.send_raw(0x00, &[]));
+        assert_eq!(result, Err(Error::Comm(RetryError::NotRetried(()))));
+    }
+}
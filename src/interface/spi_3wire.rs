@@ -0,0 +1,166 @@
+use embedded_hal_async::spi::SpiDevice;
+
+use super::WriteOnlyInterface;
+
+/// Number of 9-bit words packed into one SPI write, a multiple of 8 so that
+/// only the last write of a command needs padding.
+const WORDS_PER_CHUNK: usize = 512;
+const CHUNK_BYTES: usize = WORDS_PER_CHUNK * 9 / 8;
+
+/// 3-wire SPI transport for modules without a data/command pin.
+///
+/// The controller is expected to be strapped for 9-bit serial mode, where
+/// every word starts with the D/C bit followed by the data byte. The words are
+/// bit-packed into a regular 8-bit byte stream, MSB first. A write that does
+/// not end on a byte boundary is padded with zero bits, the incomplete word is
+/// discarded by the controller when chip select is deasserted at the end of
+/// the transaction.
+///
+/// Unlike `SpiInterface`, a command and its parameters go out in the same
+/// transaction because no GPIO has to be toggled in between.
+pub struct Spi3WireInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    spi: SPI,
+}
+
+impl<SPI> Spi3WireInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Returns the SPI device.
+    pub fn spi_mut(&mut self) -> &mut SPI {
+        &mut self.spi
+    }
+
+    async fn write(&mut self, command: Option<u8>, data: &[u8]) -> Result<(), SPI::Error> {
+        let words = command
+            .map(|command| (false, command))
+            .into_iter()
+            .chain(data.iter().map(|&byte| (true, byte)));
+        self.write_words(words).await
+    }
+
+    /// Packs `(dc, byte)` words and writes them, one transaction per `WORDS_PER_CHUNK` words.
+    async fn write_words(
+        &mut self,
+        words: impl Iterator<Item = (bool, u8)>,
+    ) -> Result<(), SPI::Error> {
+        let mut words = words.peekable();
+        let mut buf = [0u8; CHUNK_BYTES];
+        while words.peek().is_some() {
+            buf.fill(0);
+            let mut bits = 0;
+            for (dc, byte) in words.by_ref().take(WORDS_PER_CHUNK) {
+                let word = (u16::from(dc) << 8) | u16::from(byte);
+                let shift = bits % 8;
+                let packed = word << (7 - shift);
+                buf[bits / 8] |= (packed >> 8) as u8;
+                buf[bits / 8 + 1] |= packed as u8;
+                bits += 9;
+            }
+            self.spi.write(&buf[..bits.div_ceil(8)]).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI> WriteOnlyInterface for Spi3WireInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    type Error = SPI::Error;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        self.write(Some(command), params).await
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.write(None, data).await
+    }
+
+    /// Packs all commands into one transaction.
+    async fn write_commands(&mut self, commands: &[(u8, &[u8])]) -> Result<(), Self::Error> {
+        let words = commands.iter().flat_map(|&(command, params)| {
+            core::iter::once((false, command)).chain(params.iter().map(|&byte| (true, byte)))
+        });
+        self.write_words(words).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::{Event, Recorder};
+
+    fn writes(recorder: &Recorder) -> Vec<Vec<u8>> {
+        recorder
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Write(bytes) => Some(bytes),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn command_and_params_are_packed_known_answer() {
+        let recorder = Recorder::new();
+        let mut di = Spi3WireInterface::new(recorder.spi());
+        // 0_00101010 1_00000000 1_00000001, padded with 5 zero bits
+        block_on(di.write_command(0x2A, &[0x00, 0x01])).unwrap();
+        // 0_00010001, padded with 7 zero bits
+        block_on(di.write_command(0x11, &[])).unwrap();
+        // 1_10100101, padded with 7 zero bits
+        block_on(di.write_data(&[0xA5])).unwrap();
+        assert_eq!(
+            writes(&recorder),
+            [
+                vec![0x15, 0x40, 0x20, 0x20],
+                vec![0x08, 0x80],
+                vec![0xD2, 0x80],
+            ]
+        );
+    }
+
+    #[test]
+    fn batched_commands_share_one_write() {
+        let recorder = Recorder::new();
+        let mut di = Spi3WireInterface::new(recorder.spi());
+        // 0_00111010 1_00000101 0_00101001, padded with 5 zero bits
+        block_on(di.write_commands(&[(0x3A, &[0x05]), (0x29, &[])])).unwrap();
+        assert_eq!(writes(&recorder), [vec![0x1D, 0x41, 0x45, 0x20]]);
+        let transactions = recorder
+            .events()
+            .iter()
+            .filter(|event| **event == Event::TransactionStart)
+            .count();
+        assert_eq!(transactions, 1);
+    }
+
+    #[test]
+    fn whole_chunks_need_no_padding() {
+        let recorder = Recorder::new();
+        let mut di = Spi3WireInterface::new(recorder.spi());
+        // 8 words fill exactly 9 bytes
+        block_on(di.write_data(&[0xFF; 8])).unwrap();
+        assert_eq!(writes(&recorder), [vec![0xFF; 9]]);
+        recorder.clear();
+        // One word more than a chunk spills into a padded second write
+        block_on(di.write_data(&[0xFF; WORDS_PER_CHUNK + 1])).unwrap();
+        assert_eq!(
+            writes(&recorder),
+            [vec![0xFF; CHUNK_BYTES], vec![0xFF, 0x80]]
+        );
+    }
+}
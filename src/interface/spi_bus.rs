@@ -0,0 +1,181 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{raw_read_len, strip_dummy_bits, ReadInterface, WriteOnlyInterface, MAX_READ_LEN};
+
+/// 4-wire SPI transport owning the bus and driving chip select itself.
+///
+/// Chip select is asserted when a command starts and stays asserted through
+/// its parameters and all following data, e.g. a whole frame, until the next
+/// command. The bus is flushed before every DC transition.
+pub struct SpiBusInterface<BUS, DC, CS>
+where
+    BUS: SpiBus,
+    DC: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    bus: BUS,
+    dc: DC,
+    cs: CS,
+    /// Whether DC is currently high
+    data_phase: bool,
+    /// Maximum number of bytes per SPI write
+    max_chunk_size: Option<usize>,
+}
+
+impl<BUS, DC, CS> SpiBusInterface<BUS, DC, CS>
+where
+    BUS: SpiBus,
+    DC: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    pub fn new(bus: BUS, mut dc: DC, mut cs: CS) -> Self {
+        cs.set_high().ok();
+        // Matches `data_phase`, DC may have been created high.
+        dc.set_low().ok();
+        Self {
+            bus,
+            dc,
+            cs,
+            data_phase: false,
+            max_chunk_size: None,
+        }
+    }
+
+    /// Sets the maximum number of bytes per SPI write, see `Config::max_chunk_size`.
+    #[must_use]
+    pub fn with_max_chunk_size(mut self, max_chunk_size: Option<usize>) -> Self {
+        self.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    /// Finishes pending transfers and deasserts chip select.
+    pub async fn release(&mut self) -> Result<(), BUS::Error> {
+        self.bus.flush().await?;
+        self.cs.set_high().ok();
+        Ok(())
+    }
+
+    async fn set_data_phase(&mut self, data_phase: bool) -> Result<(), BUS::Error> {
+        if self.data_phase != data_phase {
+            self.bus.flush().await?;
+            if data_phase {
+                self.dc.set_high().ok();
+            } else {
+                self.dc.set_low().ok();
+            }
+            self.data_phase = data_phase;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), BUS::Error> {
+        let chunk_size = self.max_chunk_size.unwrap_or(data.len()).max(1);
+        for chunk in data.chunks(chunk_size) {
+            self.bus.write(chunk).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<BUS, DC, CS> WriteOnlyInterface for SpiBusInterface<BUS, DC, CS>
+where
+    BUS: SpiBus,
+    DC: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    type Error = BUS::Error;
+
+    async fn write_command(&mut self, command: u8, params: &[u8]) -> Result<(), Self::Error> {
+        self.release().await?;
+        self.cs.set_low().ok();
+        self.set_data_phase(false).await?;
+        self.write(&[command]).await?;
+        if !params.is_empty() {
+            self.set_data_phase(true).await?;
+            self.write(params).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.set_data_phase(true).await?;
+        self.write(data).await
+    }
+
+    /// Keeps chip select asserted across all commands.
+    async fn write_commands(&mut self, commands: &[(u8, &[u8])]) -> Result<(), Self::Error> {
+        self.release().await?;
+        self.cs.set_low().ok();
+        for &(command, params) in commands {
+            self.set_data_phase(false).await?;
+            self.write(&[command]).await?;
+            if !params.is_empty() {
+                self.set_data_phase(true).await?;
+                self.write(params).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Chip select is deasserted after the response, ending the read.
+impl<BUS, DC, CS> ReadInterface for SpiBusInterface<BUS, DC, CS>
+where
+    BUS: SpiBus,
+    DC: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+{
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let mut raw = [0; MAX_READ_LEN + 1];
+        let raw = &mut raw[..raw_read_len(buf.len(), dummy_bits)];
+        self.write_command(command, &[]).await?;
+        self.set_data_phase(true).await?;
+        self.bus.read(raw).await?;
+        self.release().await?;
+        strip_dummy_bits(raw, dummy_bits, buf);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec;
+
+    use super::*;
+    use crate::mock::{Event, Recorder};
+
+    fn pin(name: &'static str, high: bool) -> Event {
+        Event::Pin { name, high }
+    }
+
+    #[test]
+    fn command_and_params_share_one_chip_select() {
+        let recorder = Recorder::new();
+        let mut di = SpiBusInterface::new(recorder.spi(), recorder.pin("DC"), recorder.pin("CS"));
+        recorder.clear();
+        block_on(di.write_command(0x2A, &[0x00, 0x02, 0x00, 0x81])).unwrap();
+        block_on(di.write_data(&[0xFF; 4])).unwrap();
+        block_on(di.release()).unwrap();
+        assert_eq!(
+            recorder.events(),
+            [
+                pin("CS", true),
+                pin("CS", false),
+                Event::Write(vec![0x2A]),
+                pin("DC", true),
+                Event::Write(vec![0x00, 0x02, 0x00, 0x81]),
+                Event::Write(vec![0xFF; 4]),
+                pin("CS", true),
+            ]
+        );
+    }
+}
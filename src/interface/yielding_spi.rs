@@ -0,0 +1,38 @@
+use embassy_futures::yield_now;
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+
+/// Async `SpiDevice` over a blocking `embedded_hal::spi::SpiDevice`, requires the `blocking-spi` feature.
+///
+/// Each transaction blocks until it is done and then yields to the executor,
+/// so other tasks get to run between the transactions of a long flush. The
+/// SPI interfaces put at most 16 writes of `Config::max_chunk_size` bytes into
+/// one transaction, set it to bound the time between yields.
+pub struct YieldingSpi<SPI> {
+    spi: SPI,
+}
+
+impl<SPI: SpiDevice> YieldingSpi<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Returns the blocking SPI device.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI: SpiDevice> ErrorType for YieldingSpi<SPI> {
+    type Error = SPI::Error;
+}
+
+impl<SPI: SpiDevice> embedded_hal_async::spi::SpiDevice for YieldingSpi<SPI> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let result = self.spi.transaction(operations);
+        yield_now().await;
+        result
+    }
+}
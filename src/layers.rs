@@ -0,0 +1,116 @@
+//! Compositing of several `Frame`s, e.g. background, content and overlay.
+use core::convert::Infallible;
+
+use embedded_graphics_core::pixelcolor::{
+    raw::{RawData, RawU16},
+    Rgb565,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{blend::blend, Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+/// A frame taking part in compositing.
+pub struct Layer<'a, const N: usize> {
+    pub frame: &'a mut Frame<N>,
+    /// Hidden layers are skipped.
+    pub visible: bool,
+    /// Opacity from 0 (transparent) to 255 (opaque).
+    pub alpha: u8,
+    /// Pixels of this color are transparent.
+    pub key: Option<Rgb565>,
+}
+
+impl<'a, const N: usize> Layer<'a, N> {
+    /// Creates a visible, opaque layer without color key.
+    #[must_use]
+    pub fn new(frame: &'a mut Frame<N>) -> Self {
+        Self {
+            frame,
+            visible: true,
+            alpha: 255,
+            key: None,
+        }
+    }
+}
+
+/// Stack of `L` layers composited bottom to top.
+///
+/// Every layer keeps its own contents, so changing one layer only requires
+/// redrawing that layer before compositing again. All layers must have the
+/// size of the output frame.
+pub struct Layers<'a, const N: usize, const L: usize> {
+    layers: [Layer<'a, N>; L],
+}
+
+impl<'a, const N: usize, const L: usize> Layers<'a, N, L> {
+    /// Creates a layer stack, the first layer is the bottom one.
+    #[must_use]
+    pub fn new(layers: [Layer<'a, N>; L]) -> Self {
+        Self { layers }
+    }
+
+    /// Returns layer `index`, `None` if it does not exist.
+    pub fn layer(&self, index: usize) -> Option<&Layer<'a, N>> {
+        self.layers.get(index)
+    }
+
+    /// Returns layer `index` for drawing or changing its settings, `None` if it does not exist.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer<'a, N>> {
+        self.layers.get_mut(index)
+    }
+
+    /// Composites the visible layers into `target`, starting from black.
+    pub fn compose(&self, target: &mut Frame<N>) {
+        target.buffer.fill(0);
+        for layer in self.layers.iter().filter(|layer| layer.visible) {
+            composite(target, layer.frame, layer.alpha, layer.key);
+        }
+    }
+}
+
+/// Draws `layer` over `target` with `alpha`, pixels of the `key` color are transparent.
+pub(crate) fn composite<const N: usize>(
+    target: &mut Frame<N>,
+    layer: &Frame<N>,
+    alpha: u8,
+    key: Option<Rgb565>,
+) {
+    let key = key.map(|key| RawU16::from(key).into_inner());
+    let pixels = target
+        .buffer
+        .chunks_exact_mut(2)
+        .zip(layer.buffer.chunks_exact(2));
+    for (dest, src) in pixels {
+        let color = u16::from_be_bytes([src[0], src[1]]);
+        if key == Some(color) {
+            continue;
+        }
+        let color = match alpha {
+            255 => color,
+            alpha => {
+                let bg = u16::from_be_bytes([dest[0], dest[1]]);
+                let blended = blend(RawU16::new(bg).into(), RawU16::new(color).into(), alpha);
+                RawU16::from(blended).into_inner()
+            }
+        };
+        dest.copy_from_slice(&color.to_be_bytes());
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Composites the layers into `target` and transfers it to the LCD display.
+    pub async fn flush_layers<const N: usize, const L: usize>(
+        &mut self,
+        layers: &Layers<'_, N, L>,
+        target: &mut Frame<N>,
+    ) -> Result<(), Error<E>> {
+        layers.compose(target);
+        self.flush_frame(target).await
+    }
+}
@@ -1,10 +1,110 @@
 #![no_std]
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+pub mod animation;
+pub mod backlight;
+pub mod blend;
+pub mod blocking;
+pub mod bmp;
+pub mod budget;
+#[cfg(feature = "buffered")]
+mod buffered;
+pub mod delta;
+pub mod display_mode;
+pub mod double_buffer;
+#[cfg(any(test, feature = "std"))]
+pub mod emulator;
+mod frame_ops;
+#[cfg(feature = "buffered")]
+pub mod group;
+#[cfg(feature = "alloc")]
+pub mod heap;
 pub mod instruction;
+pub mod interface;
+pub mod layers;
+pub mod madctl;
+pub mod metrics;
+#[cfg(any(test, feature = "std"))]
+pub mod mock;
+pub mod model;
+pub mod mono;
+pub mod pacing;
+pub mod palette;
+pub mod pipeline;
+#[cfg(feature = "qoi")]
+pub mod qoi;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod readback;
+pub mod remote;
+pub mod rle;
+pub mod shared;
+pub mod sized_frame;
+pub mod sprite;
+#[cfg(target_has_atomic = "8")]
+pub mod static_frame;
+pub mod sync;
+#[cfg(any(test, feature = "std"))]
+pub mod test_util;
+#[cfg(feature = "buffered")]
+pub mod tiled;
+pub mod transform;
+#[cfg(feature = "widgets")]
+pub mod widgets;
+pub use crate::animation::{Animation, AnimationFrame};
+pub use crate::backlight::{Backlight, BacklightPin, NoBacklight, PwmBacklight};
+#[cfg(feature = "buffered")]
+pub use crate::blocking::BlockingBufferedST7735;
+pub use crate::blocking::BlockingST7735;
+pub use crate::bmp::BmpImage;
+pub use crate::budget::{render_budgeted, BudgetReport, Job};
+#[cfg(feature = "alloc")]
+pub use crate::buffered::HeapST7735;
+#[cfg(feature = "buffered")]
+pub use crate::buffered::ST7735;
+pub use crate::delta::{DeltaAnimation, DeltaError};
+pub use crate::display_mode::{DisplayMode, Gamma, GammaCurve, Theme};
+pub use crate::double_buffer::DoubleBuffered;
+#[cfg(feature = "buffered")]
+pub use crate::group::DisplayGroup;
+#[cfg(feature = "alloc")]
+pub use crate::heap::{HeapFrames, HeapTarget};
 use crate::instruction::Instruction;
+#[cfg(feature = "blocking-spi")]
+pub use crate::interface::YieldingSpi;
+pub use crate::interface::{
+    BlockingSpiInterface, ParallelInterface, ReadInterface, RetryError, RetryInterface,
+    RetryPolicy, Spi3WireInterface, SpiBusInterface, SpiInterface, WriteOnlyInterface,
+};
+pub use crate::layers::{Layer, Layers};
+pub use crate::madctl::Madctl;
+pub use crate::metrics::{
+    EnergyMeter, EnergyModel, EnergyTotals, FlushTimer, FlushTiming, MeteredSpi, Metrics,
+};
+use crate::model::InitCommand;
+pub use crate::model::{ChipVariant, Model};
+pub use crate::mono::{mono_buffer_size, MonoFramebuffer};
+pub use crate::pacing::{Paced, PacingStats};
+pub use crate::palette::{palette_buffer_size, PaletteFramebuffer, PaletteIndex};
+pub use crate::pipeline::{FramePipe, Renderer};
+pub use crate::readback::{DisplayStatus, SelfDiagnostic};
+pub use crate::rle::RleImage;
+pub use crate::shared::SharedDisplay;
+pub use crate::sized_frame::SizedFrame;
+pub use crate::sprite::{Flip, Sprite, SpriteSheet};
+#[cfg(target_has_atomic = "8")]
+pub use crate::static_frame::{StaticBuffer, StaticFrame};
+pub use crate::sync::SyncSignal;
+#[cfg(feature = "buffered")]
+pub use crate::tiled::{Tile, TiledDisplay};
 use core::convert::Infallible;
+use embassy_futures::join::join;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::delay::DelayNs;
-use embedded_hal_async::spi::SpiDevice;
+use embedded_hal_async::spi::{SpiBus, SpiDevice};
 
 /// Calculates the required buffer size.
 /// Inspired by `embedded-graphics`-`FrameBuffer` <https://docs.rs/embedded-graphics/latest/embedded_graphics/framebuffer/struct.Framebuffer.html>
@@ -14,8 +114,18 @@ pub const fn buffer_size(width: u16, height: u16) -> usize {
     width as usize * height as usize * 2
 }
 
+/// Calculates the required pixel count of the `ST7735` internal buffer.
+#[must_use]
+pub const fn pixel_count(width: u16, height: u16) -> usize {
+    width as usize * height as usize
+}
+
+/// Most power up commands `power_up` hands to the interface at once.
+const INIT_BATCH_LEN: usize = 16;
+
 /// Display Pixel Color Mode
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum PixelColor {
     /// Red, Green, Blue,
@@ -24,19 +134,30 @@ pub enum PixelColor {
     BGR = 0x08,
 }
 
-/// Async ST7735 LCD display driver.
-pub struct ST7735IF<SPI, DC, RST>
+/// Async ST7735 LCD display driver without an internal framebuffer.
+///
+/// Pixel data is streamed straight from `Frame`s, `MonoFramebuffer`s,
+/// `PaletteFramebuffer`s or external byte buffers, so this interface is
+/// usable with the `buffered` feature disabled.
+pub struct ST7735IF<DI, RST, BL = NoBacklight, M = model::ST7735>
 where
-    SPI: SpiDevice,
-    DC: OutputPin<Error = Infallible>,
+    DI: WriteOnlyInterface,
     RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
 {
-    /// SPI
-    spi: SPI,
-    /// Data/command pin.
-    dc: DC,
+    /// Display interface.
+    di: DI,
+    /// Whether a write failed, leaving the controller in an unknown state
+    faulted: bool,
+    /// Controller model.
+    model: M,
     /// Reset pin.
     rst: RST,
+    /// Backlight.
+    backlight: BL,
+    /// Backlight brightness in percent
+    brightness: u8,
     /// Whether the display is RGB or BGR
     rgb: PixelColor,
     /// Whether the colours are inverted (true) or not (false)
@@ -44,29 +165,180 @@ where
     /// Global image offset
     dx: u16,
     dy: u16,
+    /// Offsets replacing the global offset on orientation changes
+    orientation_offsets: Option<OrientationOffsets>,
     orientation: Orientation,
+    /// Output mirroring
+    mirror: Mirror,
+    /// MADCTL refresh order bits
+    refresh_order: Madctl,
+    /// Frame rate control replacing the one of the model
+    frame_rate: Option<FrameRateConfig>,
+    /// Power control replacing the one of the model
+    power: Option<PowerConfig>,
+    /// Last CASET and RASET values in controller addresses, if known
+    window: Option<[u16; 4]>,
+    /// Last written MADCTL value, if known
+    written_madctl: Option<Madctl>,
+    /// Display mode applied last, its gamma is written again by `power_up`
+    mode: DisplayMode,
+}
+
+/// Splits the scan bits of `madctl` into an orientation and output mirroring.
+///
+/// Keeps `current` if it matches the MV bit, otherwise picks the unmirrored
+/// portrait or landscape orientation.
+fn split_madctl(madctl: Madctl, current: Orientation) -> (Orientation, Mirror) {
+    let landscape = madctl.contains(Madctl::MV);
+    let orientation = if current.is_landscape() == landscape {
+        current
+    } else if landscape {
+        Orientation::Landscape
+    } else {
+        Orientation::Portrait
+    };
+    let mirror = Mirror((madctl & Madctl::SCAN).bits() ^ orientation as u8);
+    (orientation, mirror)
 }
-pub struct ST7735<SPI, DC, RST, const WIDTH: u16, const HEIGHT: u16, const N: usize>
+
+/// Returns the framebuffer index of a pixel, `None` if it is out of bounds.
+///
+/// Framebuffers are stored row by row in logical coordinates for every
+/// orientation, the controller maps them to the panel through MADCTL.
+#[cfg(feature = "buffered")]
+fn pixel_index(size: (u16, u16), x: u16, y: u16) -> Option<usize> {
+    let (width, height) = size;
+    if x >= width || y >= height {
+        return None;
+    }
+    Some(usize::from(y) * usize::from(width) + usize::from(x))
+}
+
+/// Copies a `w` x `h` big endian `Rgb565` image into a framebuffer of `size` at `(x, y)`.
+///
+/// The image is clipped to the framebuffer, missing trailing data is skipped.
+/// Pixels of the `key` color, if any, are transparent.
+#[cfg(feature = "buffered")]
+fn blit_raw_image(
+    buffer: &mut [u16],
+    size: (u16, u16),
+    pos: (u16, u16),
+    image: (u16, u16, &[u8]),
+    key: Option<u16>,
+) {
+    let (width, height) = size;
+    let (x, y) = pos;
+    let (w, h, data) = image;
+    if x >= width {
+        return;
+    }
+    let cols = usize::from(w.min(width - x));
+    let rows = data.chunks(usize::from(w) * 2).take(usize::from(h));
+    for (dy, row) in (y..height).zip(rows) {
+        let start = usize::from(dy) * usize::from(width) + usize::from(x);
+        let Some(dest) = buffer.get_mut(start..start + cols) else {
+            return;
+        };
+        for (pixel, bytes) in dest.iter_mut().zip(row.chunks_exact(2)) {
+            let color = u16::from_be_bytes([bytes[0], bytes[1]]);
+            if key != Some(color) {
+                *pixel = color;
+            }
+        }
+    }
+}
+
+/// Copies `pixels` of an image `width` pixels wide into a framebuffer of `size` at `(x, y)`.
+///
+/// The pixels are native `Rgb565` words row by row, the image is clipped to the framebuffer.
+#[cfg(feature = "buffered")]
+fn blit_pixels<I>(buffer: &mut [u16], size: (u16, u16), pos: (u16, u16), width: u16, pixels: I)
 where
-    SPI: SpiDevice,
-    DC: OutputPin<Error = Infallible>,
-    RST: OutputPin<Error = Infallible>,
+    I: Iterator<Item = u16>,
 {
-    iface: ST7735IF<SPI, DC, RST>,
-    buffer: [u8; N],
+    let width = usize::from(width.max(1));
+    for (i, color) in pixels.enumerate() {
+        let x = usize::from(pos.0) + i % width;
+        let y = usize::from(pos.1) + i / width;
+        if y >= usize::from(size.1) {
+            return;
+        }
+        if x < usize::from(size.0) {
+            buffer[y * usize::from(size.0) + x] = color;
+        }
+    }
 }
 
 /// Display orientation.
-#[derive(Clone, Copy)]
+///
+/// The mirrored variants show the image of their unmirrored counterpart
+/// mirrored along the logical x axis, e.g. for panels viewed through a
+/// mirror. Together they cover all eight MADCTL scan directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum Orientation {
     Portrait = 0x00,
     Landscape = 0x60,
     PortraitSwapped = 0xC0,
     LandscapeSwapped = 0xA0,
+    PortraitMirrored = 0x40,
+    LandscapeMirrored = 0x20,
+    PortraitSwappedMirrored = 0x80,
+    LandscapeSwappedMirrored = 0xE0,
+}
+
+/// Output mirroring applied on top of the display orientation.
+///
+/// Combine with `|`, e.g. `Mirror::X | Mirror::Y`. Mirroring is done by the
+/// controller through MADCTL, so drawing code keeps using normal coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mirror(u8);
+
+impl Mirror {
+    /// No mirroring.
+    pub const NONE: Self = Self(0x00);
+    /// Mirror horizontally, i.e. along the logical x axis.
+    pub const X: Self = Self(0x40);
+    /// Mirror vertically, i.e. along the logical y axis.
+    pub const Y: Self = Self(0x80);
+
+    /// MADCTL bits to toggle for this mirroring.
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl core::ops::BitOr for Mirror {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Signalling on the tearing effect (TE) output, the TEON parameter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum TearingEffect {
+    /// TE is high during vertical blanking only.
+    #[default]
+    VBlank = 0x00,
+    /// TE is high during vertical and horizontal blanking.
+    VAndHBlank = 0x01,
 }
 
 /// Display Settings
+///
+/// Build it from `Config::new()` or a preset with the setter methods, e.g.
+/// `Config::new().rgb(PixelColor::BGR).inverted(true)`, as fields may be
+/// added in minor releases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct Config {
     /// `PixelColor`
     pub rgb: PixelColor,
@@ -74,6 +346,32 @@ pub struct Config {
     pub inverted: bool,
     /// Display orientation
     pub orientation: Orientation,
+    /// Output mirroring
+    pub mirror: Mirror,
+    /// MADCTL value written at init instead of the one composed from
+    /// `orientation`, `mirror` and `rgb`, which are derived from it like for
+    /// `ST7735IF::set_madctl`. Framebuffer sizes refer to the derived orientation.
+    pub madctl: Option<Madctl>,
+    /// Global image offset `(dx, dy)`
+    pub offset: (u16, u16),
+    /// Offsets for panels placed differently in GRAM per orientation, replaces `offset`
+    pub orientation_offsets: Option<OrientationOffsets>,
+    /// ST7735 variant, selects the power up programming of the default model
+    pub chip: ChipVariant,
+    /// Frame rate control replacing the one of the power up programming
+    pub frame_rate: Option<FrameRateConfig>,
+    /// Power control replacing the one of the power up programming, e.g. against banding
+    pub power: Option<PowerConfig>,
+    /// Data/command phase timing, applied by `SpiInterface`
+    pub dc_timing: DcTiming,
+    /// Maximum number of bytes per SPI write, for DMA backends with a transfer limit.
+    ///
+    /// Larger writes are split into chunks. The `SpiDevice` interfaces put up
+    /// to 16 chunks into one transaction, so chip select may be deasserted
+    /// between groups of 16 chunks. `None` passes buffers of any size to the
+    /// `SpiDevice` in one write.
+    /// Applied by the SPI interfaces created by the `new` constructors.
+    pub max_chunk_size: Option<usize>,
 }
 
 impl Default for Config {
@@ -82,118 +380,597 @@ impl Default for Config {
             rgb: PixelColor::RGB,
             inverted: false,
             orientation: Orientation::Landscape,
+            mirror: Mirror::NONE,
+            madctl: None,
+            offset: (0, 0),
+            orientation_offsets: None,
+            chip: ChipVariant::ST7735R,
+            frame_rate: None,
+            power: None,
+            dc_timing: DcTiming::default(),
+            max_chunk_size: None,
         }
     }
 }
 
-struct Command<'a> {
-    instruction: Instruction,
-    params: &'a [u8],
-    delay_time: u32,
+impl Config {
+    /// Creates the default configuration, see `Default`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the pixel color order.
+    #[must_use]
+    pub fn rgb(mut self, rgb: PixelColor) -> Self {
+        self.rgb = rgb;
+        self
+    }
+
+    /// Sets whether colors are inverted.
+    #[must_use]
+    pub fn inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Sets the display orientation.
+    #[must_use]
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the output mirroring.
+    #[must_use]
+    pub fn mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Sets a MADCTL value overriding `orientation`, `mirror` and `rgb`, e.g. for refresh order bits.
+    #[must_use]
+    pub fn madctl(mut self, madctl: Madctl) -> Self {
+        self.madctl = Some(madctl);
+        self
+    }
+
+    /// Sets the global image offset.
+    #[must_use]
+    pub fn offset(mut self, dx: u16, dy: u16) -> Self {
+        self.offset = (dx, dy);
+        self
+    }
+
+    /// Sets per orientation offsets, replacing `offset`.
+    #[must_use]
+    pub fn orientation_offsets(mut self, offsets: OrientationOffsets) -> Self {
+        self.orientation_offsets = Some(offsets);
+        self
+    }
+
+    /// Sets the ST7735 variant.
+    #[must_use]
+    pub fn chip(mut self, chip: ChipVariant) -> Self {
+        self.chip = chip;
+        self
+    }
+
+    /// Sets the frame rate control, e.g. to trade refresh rate for power or against flicker.
+    #[must_use]
+    pub fn frame_rate(mut self, frame_rate: FrameRateConfig) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    /// Sets the power control, for panels that need other booster settings.
+    #[must_use]
+    pub fn power(mut self, power: PowerConfig) -> Self {
+        self.power = Some(power);
+        self
+    }
+
+    /// Sets the data/command phase timing.
+    #[must_use]
+    pub fn dc_timing(mut self, dc_timing: DcTiming) -> Self {
+        self.dc_timing = dc_timing;
+        self
+    }
+
+    /// Sets the maximum number of bytes per SPI write.
+    #[must_use]
+    pub fn max_chunk_size(mut self, max_chunk_size: Option<usize>) -> Self {
+        self.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    /// Preset for 1.8" 128x160 modules with a BGR panel in its native portrait scan.
+    ///
+    /// Most of these modules need inverted colors. Use with a 128 x 160 logical
+    /// size, e.g. `ST7735::new(spi, dc, rst, config, 128, 160)`.
+    #[must_use]
+    pub fn portrait_bgr_128x160() -> Self {
+        Self::new()
+            .rgb(PixelColor::BGR)
+            .inverted(true)
+            .orientation(Orientation::Portrait)
+    }
+
+    /// Preset for 1.44" 128x128 modules with a BGR panel in its native portrait scan.
+    ///
+    /// The panel does not start at the GRAM origin, the offsets of
+    /// `OrientationOffsets::GREEN_TAB_128X128` follow orientation changes. Use
+    /// with a 128 x 128 logical size, e.g.
+    /// `ST7735::new(spi, dc, rst, config, 128, 128)`.
+    #[must_use]
+    pub fn square_128x128() -> Self {
+        Self::new()
+            .rgb(PixelColor::BGR)
+            .orientation(Orientation::Portrait)
+            .orientation_offsets(OrientationOffsets::GREEN_TAB_128X128)
+    }
 }
 
-impl<'a> Command<'a> {
-    fn new(instruction: Instruction, params: &'a [u8], delay_time: u32) -> Self {
+/// Global image offsets per orientation.
+///
+/// Panels smaller than the controller GRAM sit at an offset from the GRAM
+/// origin, which moves with the MADCTL scan direction. Output mirroring
+/// is not accounted for, adjust with `set_offset` after `set_output_mirror`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OrientationOffsets {
+    /// Offset `(dx, dy)` in `Orientation::Portrait`
+    pub portrait: (u16, u16),
+    /// Offset `(dx, dy)` in `Orientation::Landscape`
+    pub landscape: (u16, u16),
+    /// Offset `(dx, dy)` in `Orientation::PortraitSwapped`
+    pub portrait_swapped: (u16, u16),
+    /// Offset `(dx, dy)` in `Orientation::LandscapeSwapped`
+    pub landscape_swapped: (u16, u16),
+}
+
+impl OrientationOffsets {
+    /// 1.44" 128x128 "green tab" modules.
+    pub const GREEN_TAB_128X128: Self = Self {
+        portrait: (2, 1),
+        landscape: (1, 2),
+        portrait_swapped: (2, 3),
+        landscape_swapped: (3, 2),
+    };
+
+    /// Returns the offset for `orientation`.
+    ///
+    /// Mirrored orientations take the column offset and the row offset from
+    /// the unmirrored orientations with the same column and row address order.
+    #[must_use]
+    pub const fn get(&self, orientation: Orientation) -> (u16, u16) {
+        let bits = orientation as u8;
+        let my = bits & Madctl::MY.bits() != 0;
+        let mx = bits & Madctl::MX.bits() != 0;
+        if orientation.is_landscape() {
+            let dx = if my {
+                self.landscape_swapped.0
+            } else {
+                self.landscape.0
+            };
+            let dy = if mx {
+                self.landscape.1
+            } else {
+                self.landscape_swapped.1
+            };
+            (dx, dy)
+        } else {
+            let dx = if mx {
+                self.portrait_swapped.0
+            } else {
+                self.portrait.0
+            };
+            let dy = if my {
+                self.portrait_swapped.1
+            } else {
+                self.portrait.1
+            };
+            (dx, dy)
+        }
+    }
+}
+
+/// Timing of the data/command phase boundaries of `SpiInterface`.
+///
+/// Every SPI write is issued as its own transaction in which the DC pin is
+/// stable, so each command or data phase is a whole number of bytes framed
+/// by one chip select assertion. The delays below are inserted inside that
+/// transaction, which helps with marginal level shifters that corrupt the
+/// first bit clocked after a DC transition.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DcTiming {
+    /// Delay between the start of a phase and its first clocked bit, in nanoseconds.
+    pub setup_ns: u32,
+    /// Delay between the last clocked bit of a phase and its end, in nanoseconds.
+    pub hold_ns: u32,
+}
+
+/// Power control (PWCTR1-5) and VCOM (VMCTR1) register values of the ST7735 family.
+///
+/// Each field holds the parameters of the register of the same name, see the
+/// controller datasheet. Wrong values can damage the panel, start from a preset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerConfig {
+    /// AVDD, GVDD and GVCL levels and mode
+    pub pwctr1: [u8; 3],
+    /// VGH and VGL supply levels
+    pub pwctr2: u8,
+    /// Op-amp current and booster frequency in normal mode
+    pub pwctr3: [u8; 2],
+    /// Op-amp current and booster frequency in idle mode
+    pub pwctr4: [u8; 2],
+    /// Op-amp current and booster frequency in partial mode
+    pub pwctr5: [u8; 2],
+    /// VCOM voltage
+    pub vmctr1: u8,
+}
+
+impl PowerConfig {
+    /// Values of the ST7735R power up programming.
+    pub const ST7735R: Self = Self {
+        pwctr1: [0xA2, 0x02, 0x84],
+        pwctr2: 0xC5,
+        pwctr3: [0x0A, 0x00],
+        pwctr4: [0x8A, 0x2A],
+        pwctr5: [0x8A, 0xEE],
+        vmctr1: 0x0E,
+    };
+    /// Values of the ST7735S power up programming.
+    pub const ST7735S: Self = Self {
+        pwctr1: [0x28, 0x08, 0x04],
+        pwctr2: 0xC0,
+        pwctr3: [0x0D, 0x00],
+        pwctr4: [0x8D, 0x2A],
+        pwctr5: [0x8D, 0xEE],
+        vmctr1: 0x1A,
+    };
+}
+
+/// Frame rate parameters of one display mode, see `FrameRateConfig`.
+///
+/// The frame rate falls with longer line periods and porches, e.g. on the
+/// ST7735R it is 625 kHz / ((`rtna` + 20) x (lines + `fpa` + `bpa`)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameRate {
+    /// Line period, RTNA
+    pub rtna: u8,
+    /// Front porch lines, FPA
+    pub fpa: u8,
+    /// Back porch lines, BPA
+    pub bpa: u8,
+}
+
+/// Frame rate control (FRMCTR1/2/3) of the ST7735 family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameRateConfig {
+    /// Normal mode, full colors
+    pub normal: FrameRate,
+    /// Idle mode, 8 colors
+    pub idle: FrameRate,
+    /// Partial mode, full colors
+    pub partial: FrameRate,
+}
+
+impl FrameRateConfig {
+    /// Values of the ST7735R power up programming.
+    pub const ST7735R: Self = Self::uniform(FrameRate {
+        rtna: 0x01,
+        fpa: 0x2C,
+        bpa: 0x2D,
+    });
+    /// Values of the ST7735S power up programming.
+    pub const ST7735S: Self = Self::uniform(FrameRate {
+        rtna: 0x05,
+        fpa: 0x3C,
+        bpa: 0x3C,
+    });
+
+    /// Uses `rate` in all display modes.
+    #[must_use]
+    pub const fn uniform(rate: FrameRate) -> Self {
         Self {
-            instruction,
-            params,
-            delay_time,
+            normal: rate,
+            idle: rate,
+            partial: rate,
         }
     }
 }
 
-impl<SPI, DC, RST, E> ST7735IF<SPI, DC, RST>
+impl<SPI, DC, RST> ST7735IF<SpiInterface<SPI, DC>, RST>
 where
-    SPI: SpiDevice<Error = E>,
+    SPI: SpiDevice,
     DC: OutputPin<Error = Infallible>,
     RST: OutputPin<Error = Infallible>,
 {
     /// Creates a new driver instance that uses hardware SPI.
     pub fn new(spi: SPI, dc: DC, rst: RST, config: Config) -> Self {
+        let di = SpiInterface::new(spi, dc)
+            .with_dc_timing(config.dc_timing)
+            .with_max_chunk_size(config.max_chunk_size);
+        Self::with_interface(di, rst, config)
+    }
+}
+
+#[cfg(feature = "blocking-spi")]
+impl<SPI, DC, RST> ST7735IF<SpiInterface<YieldingSpi<SPI>, DC>, RST>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance over blocking hardware SPI, see `YieldingSpi`.
+    pub fn new_blocking_spi(spi: SPI, dc: DC, rst: RST, config: Config) -> Self {
+        Self::new(YieldingSpi::new(spi), dc, rst, config)
+    }
+}
+
+impl<BUS, DC, CS, RST> ST7735IF<SpiBusInterface<BUS, DC, CS>, RST>
+where
+    BUS: SpiBus,
+    DC: OutputPin<Error = Infallible>,
+    CS: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that owns the SPI bus and drives chip select itself.
+    pub fn new_spi_bus(bus: BUS, dc: DC, cs: CS, rst: RST, config: Config) -> Self {
+        let di = SpiBusInterface::new(bus, dc, cs).with_max_chunk_size(config.max_chunk_size);
+        Self::with_interface(di, rst, config)
+    }
+}
+
+impl<SPI, RST> ST7735IF<Spi3WireInterface<SPI>, RST>
+where
+    SPI: SpiDevice,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance for a module wired for 9-bit serial without a DC pin.
+    pub fn new_3wire(spi: SPI, rst: RST, config: Config) -> Self {
+        Self::with_interface(Spi3WireInterface::new(spi), rst, config)
+    }
+}
+
+impl<DI, RST, E> ST7735IF<DI, RST>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+{
+    /// Creates a new driver instance that uses the given display interface.
+    ///
+    /// Transport specific settings like `Config::dc_timing` have to be set on
+    /// the interface itself.
+    pub fn with_interface(di: DI, rst: RST, config: Config) -> Self {
+        let (orientation, mirror, rgb, refresh_order) = match config.madctl {
+            Some(madctl) => {
+                let (orientation, mirror) = split_madctl(madctl, config.orientation);
+                let refresh_order = madctl & Madctl::REFRESH_ORDER;
+                (orientation, mirror, madctl.pixel_color(), refresh_order)
+            }
+            None => (config.orientation, config.mirror, config.rgb, Madctl::EMPTY),
+        };
+        let (dx, dy) = config.orientation_offsets.map_or(config.offset, |offsets| {
+            let scan = transform::madctl(orientation, mirror);
+            offsets.get(Orientation::from_scan_bits(scan))
+        });
         Self {
-            spi,
-            dc,
+            di,
             rst,
-            rgb: config.rgb,
+            rgb,
             inverted: config.inverted,
-            orientation: config.orientation,
-            dx: 0,
-            dy: 0,
+            orientation,
+            mirror,
+            refresh_order,
+            dx,
+            dy,
+            orientation_offsets: config.orientation_offsets,
+            frame_rate: config.frame_rate,
+            power: config.power,
+            backlight: NoBacklight,
+            brightness: 100,
+            faulted: false,
+            window: None,
+            written_madctl: None,
+            mode: DisplayMode::default().inverted(config.inverted),
+            model: model::ST7735(config.chip),
+        }
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Hands the backlight over to the driver, see `set_backlight_brightness`.
+    pub fn with_backlight<B: Backlight>(self, backlight: B) -> ST7735IF<DI, RST, B, M> {
+        ST7735IF {
+            di: self.di,
+            rst: self.rst,
+            backlight,
+            brightness: self.brightness,
+            faulted: self.faulted,
+            rgb: self.rgb,
+            inverted: self.inverted,
+            dx: self.dx,
+            dy: self.dy,
+            orientation_offsets: self.orientation_offsets,
+            orientation: self.orientation,
+            mirror: self.mirror,
+            refresh_order: self.refresh_order,
+            window: self.window,
+            written_madctl: self.written_madctl,
+            frame_rate: self.frame_rate,
+            power: self.power,
+            mode: self.mode,
+            model: self.model,
+        }
+    }
+
+    /// Drives a sibling controller of the ST7735, see `model`.
+    pub fn with_model<N: Model>(self, model: N) -> ST7735IF<DI, RST, BL, N> {
+        ST7735IF {
+            di: self.di,
+            rst: self.rst,
+            backlight: self.backlight,
+            brightness: self.brightness,
+            faulted: self.faulted,
+            rgb: self.rgb,
+            inverted: self.inverted,
+            dx: self.dx,
+            dy: self.dy,
+            orientation_offsets: self.orientation_offsets,
+            orientation: self.orientation,
+            mirror: self.mirror,
+            refresh_order: self.refresh_order,
+            window: self.window,
+            written_madctl: self.written_madctl,
+            frame_rate: self.frame_rate,
+            power: self.power,
+            mode: self.mode,
+            model,
+        }
+    }
+
+    /// Returns the controller model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Sets the backlight brightness in percent, values above 100 are clamped.
+    pub fn set_backlight_brightness(&mut self, percent: u8) {
+        self.brightness = percent.min(100);
+        self.backlight.set_brightness(self.brightness);
+    }
+
+    /// Returns the backlight brightness in percent.
+    pub fn backlight_brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Ramps the backlight brightness linearly to `to_percent` over `duration_ms`.
+    ///
+    /// The brightness is stepped one percent at a time, a pin backlight
+    /// switches when the ramp reaches or leaves zero.
+    pub async fn fade_backlight<D>(&mut self, to_percent: u8, duration_ms: u32, delay: &mut D)
+    where
+        D: DelayNs,
+    {
+        let to_percent = to_percent.min(100);
+        let steps = u32::from(self.brightness.abs_diff(to_percent));
+        if steps == 0 {
+            return;
+        }
+        let step_us = duration_ms.saturating_mul(1000) / steps;
+        while self.brightness != to_percent {
+            if self.brightness < to_percent {
+                self.brightness += 1;
+            } else {
+                self.brightness -= 1;
+            }
+            self.backlight.set_brightness(self.brightness);
+            delay.delay_us(step_us).await;
         }
     }
 
-    /// Runs commands to initialize the display.
+    /// Runs commands to initialize the display, `power_up` followed by `turn_on`.
     pub async fn init<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
     where
         D: DelayNs,
     {
-        self.hard_reset(delay).await?;
-        let dc = &mut self.dc;
-        let inverted = self.inverted;
-        let rgb = &[self.rgb as u8];
-
-        let commands = [
-            Command::new(Instruction::SWRESET, &[], 200),
-            Command::new(Instruction::SLPOUT, &[], 200),
-            Command::new(Instruction::FRMCTR1, &[0x01, 0x2C, 0x2D], 0),
-            Command::new(Instruction::FRMCTR2, &[0x01, 0x2C, 0x2D], 0),
-            Command::new(
-                Instruction::FRMCTR3,
-                &[0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D],
-                0,
-            ),
-            Command::new(Instruction::INVCTR, &[0x07], 0),
-            Command::new(Instruction::PWCTR1, &[0xA2, 0x02, 0x84], 0),
-            Command::new(Instruction::PWCTR2, &[0xC5], 0),
-            Command::new(Instruction::PWCTR3, &[0x0A, 0x00], 0),
-            Command::new(Instruction::PWCTR4, &[0x8A, 0x2A], 0),
-            Command::new(Instruction::PWCTR5, &[0x8A, 0xEE], 0),
-            Command::new(Instruction::VMCTR1, &[0x0E], 0),
-            Command::new(
-                if inverted {
-                    Instruction::INVON
-                } else {
-                    Instruction::INVOFF
-                },
-                &[],
-                0,
-            ),
-            Command::new(Instruction::MADCTL, rgb, 0),
-            Command::new(Instruction::COLMOD, &[0x05], 0),
-            Command::new(Instruction::DISPON, &[], 200),
-        ];
+        self.power_up(delay).await?;
+        self.turn_on(delay).await
+    }
 
-        for Command {
-            instruction,
+    /// Resets the display, wakes it from sleep and configures it, leaving the output off.
+    ///
+    /// Takes roughly 400 ms of mandatory delays and can run early during boot,
+    /// the display RAM can be written before calling `turn_on`. Consecutive
+    /// commands without a delay are handed to the interface together, see
+    /// `WriteOnlyInterface::write_commands`.
+    pub async fn power_up<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.hard_reset(delay).await?;
+        self.faulted = false;
+        let mut batch: [(u8, &[u8]); INIT_BATCH_LEN] = [(0, &[]); INIT_BATCH_LEN];
+        let mut len = 0;
+        for &InitCommand {
+            command,
             params,
-            delay_time,
-        } in commands
+            delay_ms,
+        } in self.model.init_sequence()
         {
-            dc.set_low().ok();
-            let data = &[instruction as u8];
-            self.spi.write(data).await.map_err(Error::Comm)?;
-            if !params.is_empty() {
-                dc.set_high().ok();
-                let mut buf = [0_u8; 8];
-                buf[..params.len()].copy_from_slice(params);
-                self.spi
-                    .write(&buf[..params.len()])
-                    .await
-                    .map_err(Error::Comm)?;
+            // Replaced by the configured values below.
+            if self.is_overridden(command) {
+                continue;
             }
-            if delay_time > 0 {
-                delay.delay_ms(delay_time).await;
+            batch[len] = (command, params);
+            len += 1;
+            if delay_ms > 0 || len == INIT_BATCH_LEN {
+                let result = self.di.write_commands(&batch[..len]).await;
+                self.faulted |= result.is_err();
+                result.map_err(Error::from_interface::<DI>)?;
+                len = 0;
             }
+            if delay_ms > 0 {
+                delay.delay_ms(delay_ms).await;
+            }
+        }
+        if len > 0 {
+            let result = self.di.write_commands(&batch[..len]).await;
+            self.faulted |= result.is_err();
+            result.map_err(Error::from_interface::<DI>)?;
+        }
+        if let Some(frame_rate) = self.frame_rate {
+            self.write_frame_rate(&frame_rate).await?;
+        }
+        if let Some(power) = self.power {
+            self.write_power(&power).await?;
         }
+        self.write_gamma().await?;
+        let inversion = if self.inverted {
+            Instruction::INVON
+        } else {
+            Instruction::INVOFF
+        };
+        self.write_command(inversion, &[]).await?;
+        self.write_madctl(Madctl::from_bits(self.rgb as u8)).await?;
+        let colmod = self.model.colmod_16bit();
+        self.write_command(Instruction::COLMOD, &[colmod]).await?;
 
         self.set_orientation(self.orientation).await?;
         Ok(())
     }
 
+    /// Turns the display output on after `power_up` and applies the backlight brightness.
+    pub async fn turn_on<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.write_command(Instruction::DISPON, &[]).await?;
+        delay.delay_ms(200).await;
+        self.backlight.set_brightness(self.brightness);
+        Ok(())
+    }
+
+    /// Resets the display through the reset pin.
     pub async fn hard_reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
     where
         D: DelayNs,
     {
+        self.window = None;
+        self.written_madctl = None;
         self.rst.set_high().map_err(Error::Pin)?;
         delay.delay_ms(10).await;
         self.rst.set_low().map_err(Error::Pin)?;
@@ -201,46 +978,214 @@ where
         self.rst.set_high().map_err(Error::Pin)
     }
 
+    /// Sets the display orientation.
+    ///
+    /// MADCTL is only written if the value changes.
     pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
-        self.write_command(Instruction::MADCTL, &[orientation as u8 | self.rgb as u8])
-            .await?;
+        let madctl = Madctl::new(orientation, self.mirror, self.rgb) | self.refresh_order;
+        self.write_madctl(madctl).await?;
 
+        if let Some(offsets) = self.orientation_offsets {
+            let scan = transform::madctl(orientation, self.mirror);
+            (self.dx, self.dy) = offsets.get(Orientation::from_scan_bits(scan));
+        }
         self.orientation = orientation;
         Ok(())
     }
 
+    /// Sets the output mirroring, applied on top of the orientation.
+    pub async fn set_output_mirror(&mut self, mirror: Mirror) -> Result<(), Error<E>> {
+        self.mirror = mirror;
+        self.set_orientation(self.orientation).await
+    }
+
+    /// Writes MADCTL with individually chosen bits, e.g. for panels mounted mirrored.
+    ///
+    /// The scan bits are taken over as the orientation, keeping the current
+    /// one if it matches MV, plus an output mirror, so drawing, `mirror` and
+    /// later orientation changes stay consistent with the written value.
+    pub async fn set_madctl(&mut self, madctl: Madctl) -> Result<(), Error<E>> {
+        let orientation;
+        (orientation, self.mirror) = split_madctl(madctl, self.orientation);
+        self.rgb = madctl.pixel_color();
+        self.refresh_order = madctl & Madctl::REFRESH_ORDER;
+        self.set_orientation(orientation).await
+    }
+
+    /// Writes MADCTL unless it already holds `madctl`.
+    async fn write_madctl(&mut self, madctl: Madctl) -> Result<(), Error<E>> {
+        if self.written_madctl == Some(madctl) {
+            return Ok(());
+        }
+        let result = self
+            .write_command(Instruction::MADCTL, &[madctl.bits()])
+            .await;
+        self.written_madctl = result.is_ok().then_some(madctl);
+        result
+    }
+
+    /// Returns the MADCTL value for the current orientation, mirroring and pixel order.
+    pub fn madctl(&self) -> Madctl {
+        Madctl::new(self.orientation, self.mirror, self.rgb) | self.refresh_order
+    }
+
+    /// Sets the frame rate control (FRMCTR1/2/3), kept across `recover`.
+    ///
+    /// Models without these registers, see `Model::has_frame_rate_control`,
+    /// only keep the value.
+    pub async fn set_frame_rate(&mut self, frame_rate: FrameRateConfig) -> Result<(), Error<E>> {
+        self.frame_rate = Some(frame_rate);
+        self.write_frame_rate(&frame_rate).await
+    }
+
+    async fn write_frame_rate(&mut self, frame_rate: &FrameRateConfig) -> Result<(), Error<E>> {
+        if !self.model.has_frame_rate_control() {
+            return Ok(());
+        }
+        let FrameRateConfig {
+            normal,
+            idle,
+            partial,
+        } = *frame_rate;
+        let partial = [partial.rtna, partial.fpa, partial.bpa];
+        let mut params = [0; 6];
+        params[..3].copy_from_slice(&partial);
+        params[3..].copy_from_slice(&partial);
+        self.write_commands(&[
+            (
+                Instruction::FRMCTR1 as u8,
+                &[normal.rtna, normal.fpa, normal.bpa],
+            ),
+            (Instruction::FRMCTR2 as u8, &[idle.rtna, idle.fpa, idle.bpa]),
+            (Instruction::FRMCTR3 as u8, &params),
+        ])
+        .await
+    }
+
+    async fn write_power(&mut self, power: &PowerConfig) -> Result<(), Error<E>> {
+        if !self.model.has_power_control() {
+            return Ok(());
+        }
+        self.write_commands(&[
+            (Instruction::PWCTR1 as u8, &power.pwctr1),
+            (Instruction::PWCTR2 as u8, &[power.pwctr2]),
+            (Instruction::PWCTR3 as u8, &power.pwctr3),
+            (Instruction::PWCTR4 as u8, &power.pwctr4),
+            (Instruction::PWCTR5 as u8, &power.pwctr5),
+            (Instruction::VMCTR1 as u8, &[power.vmctr1]),
+        ])
+        .await
+    }
+
+    /// Whether the power up programming of `command` is replaced by a configured value.
+    fn is_overridden(&self, command: u8) -> bool {
+        let frame_rate = [
+            Instruction::FRMCTR1,
+            Instruction::FRMCTR2,
+            Instruction::FRMCTR3,
+        ];
+        let power = [
+            Instruction::PWCTR1,
+            Instruction::PWCTR2,
+            Instruction::PWCTR3,
+            Instruction::PWCTR4,
+            Instruction::PWCTR5,
+            Instruction::VMCTR1,
+        ];
+        let matches =
+            |instructions: &[Instruction]| instructions.iter().any(|&i| i as u8 == command);
+        (self.frame_rate.is_some() && self.model.has_frame_rate_control() && matches(&frame_rate))
+            || (self.power.is_some() && self.model.has_power_control() && matches(&power))
+    }
+
+    /// Turns on the tearing effect output (TEON).
+    ///
+    /// Independent of `flush_frame_synced`, e.g. to feed the TE signal to a
+    /// timer capture or external logic.
+    pub async fn enable_tearing_effect(&mut self, mode: TearingEffect) -> Result<(), Error<E>> {
+        self.write_command(Instruction::TEON, &[mode as u8]).await
+    }
+
+    /// Turns off the tearing effect output (TEOFF), TE stays low.
+    pub async fn disable_tearing_effect(&mut self) -> Result<(), Error<E>> {
+        self.write_command(Instruction::TEOFF, &[]).await
+    }
+
+    /// Writes a command with its parameters, e.g. for registers the driver does not wrap.
+    ///
+    /// The driver does not track the effect, so commands changing state it
+    /// keeps, e.g. MADCTL or COLMOD, leave it out of sync. Only the cached
+    /// address window and MADCTL value are dropped, so they are written again.
+    pub async fn send_command(
+        &mut self,
+        instruction: Instruction,
+        params: &[u8],
+    ) -> Result<(), Error<E>> {
+        self.window = None;
+        self.written_madctl = None;
+        self.write_command(instruction, params).await
+    }
+
+    /// Like `send_command`, but with a raw command byte, e.g. for vendor specific commands.
+    pub async fn send_raw(&mut self, command: u8, params: &[u8]) -> Result<(), Error<E>> {
+        self.window = None;
+        self.written_madctl = None;
+        self.write_raw_command(command, params).await
+    }
+
     async fn write_command(
         &mut self,
         instruction: Instruction,
         params: &[u8],
     ) -> Result<(), Error<E>> {
-        let dc = &mut self.dc;
-        dc.set_low().ok();
-        let data = &[instruction as u8];
-        self.spi.write(data).await.map_err(Error::Comm)?;
-        if !params.is_empty() {
-            dc.set_high().ok();
-            let mut buf = [0_u8; 8];
-            buf[..params.len()].copy_from_slice(params);
-            self.spi
-                .write(&buf[..params.len()])
-                .await
-                .map_err(Error::Comm)?;
-        }
-        Ok(())
+        self.write_raw_command(instruction as u8, params).await
+    }
+
+    async fn write_raw_command(&mut self, command: u8, params: &[u8]) -> Result<(), Error<E>> {
+        let result = self.di.write_command(command, params).await;
+        self.faulted |= result.is_err();
+        result.map_err(Error::from_interface::<DI>)
     }
 
-    fn start_data(&mut self) -> Result<(), Error<E>> {
-        self.dc.set_high().map_err(Error::Pin)
+    async fn write_commands(&mut self, commands: &[(u8, &[u8])]) -> Result<(), Error<E>> {
+        let result = self.di.write_commands(commands).await;
+        self.faulted |= result.is_err();
+        result.map_err(Error::from_interface::<DI>)
     }
 
     async fn write_data(&mut self, data: &[u8]) -> Result<(), Error<E>> {
-        let mut buf = [0_u8; 8];
-        buf[..data.len()].copy_from_slice(data);
-        self.spi
-            .write(&buf[..data.len()])
-            .await
-            .map_err(Error::Comm)
+        let result = self.di.write_data(data).await;
+        self.faulted |= result.is_err();
+        result.map_err(Error::from_interface::<DI>)
+    }
+
+    /// Whether a write failed since the last initialization.
+    ///
+    /// A command or pixel data may have been cut off, so the controller
+    /// configuration and display RAM are unknown until `recover` or `init`.
+    pub fn needs_recovery(&self) -> bool {
+        self.faulted
+    }
+
+    /// Resets and reinitializes the display after a communication failure.
+    ///
+    /// Display RAM is not restored, redraw it afterwards or use the `recover`
+    /// of a buffered driver.
+    pub async fn recover<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.init(delay).await
+    }
+
+    /// Returns the display interface.
+    ///
+    /// Drops the cached address window and MADCTL value, as writes through it
+    /// bypass the driver.
+    pub fn interface_mut(&mut self) -> &mut DI {
+        self.window = None;
+        self.written_madctl = None;
+        &mut self.di
     }
 
     /// Sets the global offset of the displayed image
@@ -249,6 +1194,56 @@ where
         self.dy = dy;
     }
 
+    /// Returns the global offset of the displayed image.
+    pub fn offset(&self) -> (u16, u16) {
+        (self.dx, self.dy)
+    }
+
+    /// Returns the current display orientation.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns the current output mirroring.
+    pub fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Writes the effective driver configuration, e.g. for bug reports.
+    pub fn dump_config<W: core::fmt::Write>(&self, w: &mut W) -> core::fmt::Result {
+        let madctl = self.madctl().bits();
+        writeln!(
+            w,
+            "{} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        )?;
+        writeln!(w, "model: {}", self.model.name())?;
+        writeln!(w, "orientation: {:?}", self.orientation)?;
+        writeln!(w, "mirror: 0x{:02X}", self.mirror.bits())?;
+        writeln!(w, "madctl: 0x{madctl:02X}")?;
+        writeln!(w, "pixel order: {:?}", self.rgb)?;
+        let colmod = self.model.colmod_16bit();
+        writeln!(w, "pixel format: 0x{colmod:02X} (16 bit)")?;
+        writeln!(w, "inverted: {}", self.inverted)?;
+        if let Some(frame_rate) = self.frame_rate {
+            writeln!(w, "frame rate: {:?}", frame_rate.normal)?;
+        }
+        writeln!(w, "offset: ({}, {})", self.dx, self.dy)?;
+        writeln!(w, "backlight: {}%", self.brightness)?;
+        write!(w, "features:")?;
+        for (name, enabled) in [
+            ("buffered", cfg!(feature = "buffered")),
+            ("alloc", cfg!(feature = "alloc")),
+            ("widgets", cfg!(feature = "widgets")),
+        ] {
+            if enabled {
+                write!(w, " {name}")?;
+            }
+        }
+        writeln!(w)
+    }
+
     /// Sets the address window for the display.
     pub async fn set_address_window(
         &mut self,
@@ -257,115 +1252,282 @@ where
         ex: u16,
         ey: u16,
     ) -> Result<(), Error<E>> {
-        self.write_command(Instruction::CASET, &[]).await?;
-        self.start_data()?;
-        let sx_bytes = (sx + self.dx).to_be_bytes();
-        let ex_bytes = (ex + self.dx).to_be_bytes();
-        self.write_data(&[sx_bytes[0], sx_bytes[1], ex_bytes[0], ex_bytes[1]])
-            .await?;
-        self.write_command(Instruction::RASET, &[]).await?;
-        self.start_data()?;
-        let sy_bytes = (sy + self.dy).to_be_bytes();
-        let ey_bytes = (ey + self.dy).to_be_bytes();
-        self.write_data(&[sy_bytes[0], sy_bytes[1], ey_bytes[0], ey_bytes[1]])
-            .await
+        let start = transform::window_address((self.dx, self.dy), (sx, sy));
+        let end = transform::window_address((self.dx, self.dy), (ex, ey));
+        self.write_window(start, end, false).await
     }
 
-    pub async fn flush_frame<const N: usize>(&mut self, frame: &Frame<N>) -> Result<(), Error<E>> {
-        self.set_address_window(0, 0, frame.width as u16 - 1, frame.height as u16 - 1)
+    /// Writes CASET and RASET for a window in controller addresses, without offset.
+    ///
+    /// With `memory_write` RAMWR follows, all in one `write_commands` batch.
+    /// CASET and RASET are skipped if the window is unchanged since the last call.
+    async fn write_window(
+        &mut self,
+        (sx, sy): (u16, u16),
+        (ex, ey): (u16, u16),
+        memory_write: bool,
+    ) -> Result<(), Error<E>> {
+        let [sx0, sx1] = sx.to_be_bytes();
+        let [ex0, ex1] = ex.to_be_bytes();
+        let [sy0, sy1] = sy.to_be_bytes();
+        let [ey0, ey1] = ey.to_be_bytes();
+        let commands: [(u8, &[u8]); 3] = [
+            (Instruction::CASET as u8, &[sx0, sx1, ex0, ex1]),
+            (Instruction::RASET as u8, &[sy0, sy1, ey0, ey1]),
+            (Instruction::RAMWR as u8, &[]),
+        ];
+        let window = [sx, sy, ex, ey];
+        let first = if self.window == Some(window) { 2 } else { 0 };
+        let last = if memory_write { 3 } else { 2 };
+        if first == last {
+            return Ok(());
+        }
+        let result = self.di.write_commands(&commands[first..last]).await;
+        self.faulted |= result.is_err();
+        self.window = result.is_ok().then_some(window);
+        result.map_err(Error::from_interface::<DI>)
+    }
+
+    /// Sets the address window and starts a memory write into it, see `write_memory`.
+    ///
+    /// Batches CASET, RASET and RAMWR, so transports like `Spi3WireInterface`
+    /// send them in one transaction.
+    pub async fn start_window_write(
+        &mut self,
+        sx: u16,
+        sy: u16,
+        ex: u16,
+        ey: u16,
+    ) -> Result<(), Error<E>> {
+        let start = transform::window_address((self.dx, self.dy), (sx, sy));
+        let end = transform::window_address((self.dx, self.dy), (ex, ey));
+        self.write_window(start, end, true).await
+    }
+
+    /// Starts a memory write into the current address window.
+    ///
+    /// Follow up with `write_memory` calls, the controller wraps around within
+    /// the window set by `set_address_window`.
+    pub async fn start_memory_write(&mut self) -> Result<(), Error<E>> {
+        self.write_command(Instruction::RAMWR, &[]).await
+    }
+
+    /// Streams big endian `Rgb565` data into a memory write started by `start_memory_write`.
+    pub async fn write_memory(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        self.write_data(data).await
+    }
+
+    /// Fills the whole display RAM with `color` without a framebuffer.
+    ///
+    /// Covers all of `Model::gram_size`, so it also blanks RAM outside the
+    /// visible area of panels with an offset.
+    pub async fn clear_screen(&mut self, color: Rgb565) -> Result<(), Error<E>> {
+        let (columns, rows) = transform::logical_size(self.orientation, self.model.gram_size());
+        self.write_window((0, 0), (columns - 1, rows - 1), true)
             .await?;
-        self.write_command(Instruction::RAMWR, &[]).await?;
-        self.start_data()?;
-        self.spi.write(&frame.buffer).await.map_err(Error::Comm)
+        self.write_repeated(color, pixel_count(columns, rows)).await
     }
-}
 
-impl<SPI, DC, RST, E, const WIDTH: u16, const HEIGHT: u16, const N: usize>
-    ST7735<SPI, DC, RST, WIDTH, HEIGHT, N>
-where
-    SPI: SpiDevice<Error = E>,
-    DC: OutputPin<Error = Infallible>,
-    RST: OutputPin<Error = Infallible>,
-{
-    #[allow(dead_code)]
-    const BUFFER_SIZE: usize = buffer_size(WIDTH, HEIGHT);
-
-    #[allow(dead_code)]
-    /// Static assertion that N is correct.
-    // MSRV: remove N when constant generic expressions are stabilized
-    // See <https://github.com/rust-lang/rust/issues/76560>
-    const CHECK_N: () = assert!(
-        N == Self::BUFFER_SIZE,
-        "Invalid N: see N must be equal to WIDTH x HEIGHT x 2!"
-    );
+    /// Fills a `w` x `h` rectangle at `(x, y)` with `color` straight in display RAM.
+    ///
+    /// The rectangle must lie within the display, it is not clipped.
+    pub async fn fill_rect(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        color: Rgb565,
+    ) -> Result<(), Error<E>> {
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        self.start_window_write(x, y, x + w - 1, y + h - 1).await?;
+        self.write_repeated(color, pixel_count(w, h)).await
+    }
 
-    /// Creates a new driver instance that uses hardware SPI.
-    pub fn new(spi: SPI, dc: DC, rst: RST, config: Config) -> Self {
-        Self {
-            iface: ST7735IF::new(spi, dc, rst, config),
-            buffer: [0; N],
+    /// Draws a horizontal line of `len` pixels from `(x, y)` to the right, see `fill_rect`.
+    pub async fn hline(&mut self, x: u16, y: u16, len: u16, color: Rgb565) -> Result<(), Error<E>> {
+        self.fill_rect(x, y, len, 1, color).await
+    }
+
+    /// Draws a vertical line of `len` pixels from `(x, y)` downwards, see `fill_rect`.
+    pub async fn vline(&mut self, x: u16, y: u16, len: u16, color: Rgb565) -> Result<(), Error<E>> {
+        self.fill_rect(x, y, 1, len, color).await
+    }
+
+    /// Starts a memory write and streams `colors` into the window set by `set_address_window`.
+    ///
+    /// Pixels are converted through a small chunk buffer, so they can be
+    /// computed on the fly without storing a frame.
+    pub async fn write_pixels<I>(&mut self, colors: I) -> Result<(), Error<E>>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        self.start_memory_write().await?;
+        self.write_colors(colors.into_iter()).await
+    }
+
+    /// Writes a `w` x `h` big endian `Rgb565` image straight to display RAM at `(x, y)`.
+    ///
+    /// The image must lie within the display, it is not clipped. Missing
+    /// trailing data leaves the rest of the area unchanged.
+    pub async fn draw_raw_image(
+        &mut self,
+        x: u16,
+        y: u16,
+        w: u16,
+        h: u16,
+        data: &[u8],
+    ) -> Result<(), Error<E>> {
+        if w == 0 || h == 0 {
+            return Ok(());
         }
+        let len = data.len().min(pixel_count(w, h) * 2);
+        self.start_window_write(x, y, x + w - 1, y + h - 1).await?;
+        self.write_data(&data[..len]).await
     }
 
-    /// Runs commands to initialize the display.
-    pub async fn init<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    /// Transfer an external buffer of `width` x `height` pixels to the LCD display.
+    pub async fn flush_buffer(
+        &mut self,
+        width: u16,
+        height: u16,
+        buf: &[u8],
+    ) -> Result<(), Error<E>> {
+        self.start_window_write(0, 0, width - 1, height - 1).await?;
+        self.write_data(buf).await
+    }
+
+    /// Transfer a frame to the LCD display.
+    pub async fn flush_frame<const N: usize>(&mut self, frame: &Frame<N>) -> Result<(), Error<E>> {
+        if !self.start_frame_write(frame.width, frame.height).await? {
+            return Ok(());
+        }
+        self.write_data(&frame.buffer).await
+    }
+
+    /// Starts a memory write of a `width` x `height` frame at the origin.
+    ///
+    /// Returns `false` without writing anything if the frame is empty.
+    async fn start_frame_write(&mut self, width: u32, height: u32) -> Result<bool, Error<E>> {
+        if width == 0 || height == 0 {
+            return Ok(false);
+        }
+        self.start_window_write(0, 0, width as u16 - 1, height as u16 - 1)
+            .await?;
+        Ok(true)
+    }
+
+    /// Waits for the next sync event, then transfers a frame to the LCD display.
+    pub async fn flush_frame_synced<const N: usize, S>(
+        &mut self,
+        frame: &Frame<N>,
+        sync: &mut S,
+    ) -> Result<(), Error<E>>
     where
-        D: DelayNs,
+        S: SyncSignal<Error = Infallible>,
     {
-        self.iface.init(delay).await?;
+        sync.wait_for_sync().await.map_err(Error::Pin)?;
+        self.flush_frame(frame).await
+    }
 
+    /// Flushes the front frame while `render` draws the next one into the back frame, then swaps them.
+    ///
+    /// The address window is set first, then `render` runs while the pixel data
+    /// of the front frame is in flight, so with a DMA backed `SpiDevice`
+    /// drawing overlaps the transfer.
+    pub async fn flush_double_buffered<const N: usize, F>(
+        &mut self,
+        frames: &mut DoubleBuffered<'_, N>,
+        render: F,
+    ) -> Result<(), Error<E>>
+    where
+        F: FnOnce(&mut Frame<N>),
+    {
+        let (front, back) = frames.split();
+        if self.start_frame_write(front.width, front.height).await? {
+            let (result, ()) = join(self.write_data(&front.buffer), async { render(back) }).await;
+            result?;
+        } else {
+            render(back);
+        }
+        frames.swap();
         Ok(())
     }
 
-    /// Transfer the internal buffer to the LCD display.
-    pub async fn flush(&mut self) -> Result<(), Error<E>> {
-        self.iface
-            .set_address_window(0, 0, WIDTH - 1, HEIGHT - 1)
-            .await?;
-        self.iface.write_command(Instruction::RAMWR, &[]).await?;
-        self.iface.start_data()?;
-        let buf = &self.buffer;
-        self.iface.spi.write(buf).await.map_err(Error::Comm)
+    /// Expands a 1 bit per pixel framebuffer to `Rgb565` and transfers it to the LCD display.
+    pub async fn flush_mono<const N: usize>(
+        &mut self,
+        frame: &MonoFramebuffer<N>,
+    ) -> Result<(), Error<E>> {
+        if !self.start_frame_write(frame.width, frame.height).await? {
+            return Ok(());
+        }
+        self.write_colors(frame.colors()).await
     }
 
-    /// Transfer the external buffer to the LCD display.
-    pub async fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
-        self.iface
-            .set_address_window(0, 0, WIDTH - 1, HEIGHT - 1)
-            .await?;
-        self.iface.write_command(Instruction::RAMWR, &[]).await?;
-        self.iface.start_data()?;
-        self.iface.spi.write(buf).await.map_err(Error::Comm)
-    }
-
-    /// Sets a pixel color at the given coords.
-    pub fn set_pixel(&mut self, x: u16, y: u16, color: u16) {
-        let idx = match self.iface.orientation {
-            Orientation::Landscape | Orientation::LandscapeSwapped => {
-                if x >= WIDTH {
-                    return;
-                }
-                (usize::from(y) * usize::from(WIDTH)) + usize::from(x)
-            }
+    /// Expands a 4 bit indexed framebuffer through its palette and transfers it to the LCD display.
+    pub async fn flush_palette<const N: usize>(
+        &mut self,
+        frame: &PaletteFramebuffer<N>,
+    ) -> Result<(), Error<E>> {
+        if !self.start_frame_write(frame.width, frame.height).await? {
+            return Ok(());
+        }
+        self.write_colors(frame.colors()).await
+    }
 
-            Orientation::Portrait | Orientation::PortraitSwapped => {
-                if y >= WIDTH {
-                    return;
-                }
-                (usize::from(y) * usize::from(HEIGHT)) + usize::from(x)
+    /// Writes native `Rgb565` words as big endian data, swapped chunk by chunk
+    /// into a small buffer.
+    #[cfg(feature = "buffered")]
+    async fn write_words(&mut self, words: &[u16]) -> Result<(), Error<E>> {
+        let mut chunk = [0_u8; 512];
+        for words in words.chunks(chunk.len() / 2) {
+            for (bytes, word) in chunk.chunks_exact_mut(2).zip(words) {
+                bytes.copy_from_slice(&word.to_be_bytes());
             }
-        } * 2;
+            self.write_data(&chunk[..words.len() * 2]).await?;
+        }
+        Ok(())
+    }
 
-        // Split 16 bit value into two bytes
-        if let Some(pixel) = self.buffer.get_mut(idx..=idx + 1) {
-            let c = color.to_be_bytes();
-            pixel.copy_from_slice(c.as_slice())
+    /// Streams `count` pixels of one color by writing a chunk filled with it repeatedly.
+    async fn write_repeated(&mut self, color: Rgb565, count: usize) -> Result<(), Error<E>> {
+        let mut chunk = [0_u8; 512];
+        let bytes = RawU16::from(color).into_inner().to_be_bytes();
+        for pixel in chunk.chunks_exact_mut(2) {
+            pixel.copy_from_slice(&bytes);
+        }
+        let mut remaining = count * 2;
+        while remaining > 0 {
+            let len = remaining.min(chunk.len());
+            self.write_data(&chunk[..len]).await?;
+            remaining -= len;
         }
+        Ok(())
     }
 
-    /// Sets the global offset of the displayed image
-    pub fn set_offset(&mut self, dx: u16, dy: u16) {
-        self.iface.set_offset(dx, dy);
+    /// Streams colors as big endian `Rgb565` data through a small chunk buffer.
+    async fn write_colors<I>(&mut self, colors: I) -> Result<(), Error<E>>
+    where
+        I: Iterator<Item = Rgb565>,
+    {
+        let mut chunk = [0_u8; 512];
+        let mut len = 0;
+        for color in colors {
+            let c = RawU16::from(color).into_inner().to_be_bytes();
+            chunk[len..len + 2].copy_from_slice(c.as_slice());
+            len += 2;
+            if len == chunk.len() {
+                self.write_data(&chunk).await?;
+                len = 0;
+            }
+        }
+        if len > 0 {
+            self.write_data(&chunk[..len]).await?;
+        }
+        Ok(())
     }
 }
 
@@ -379,60 +1541,103 @@ use self::embedded_graphics_core::{
     prelude::*,
 };
 
-impl<SPI, DC, RST, E, const WIDTH: u16, const HEIGHT: u16, const N: usize> DrawTarget
-    for ST7735<SPI, DC, RST, WIDTH, HEIGHT, N>
-where
-    SPI: SpiDevice<Error = E>,
-    DC: OutputPin<Error = Infallible>,
-    RST: OutputPin<Error = Infallible>,
-{
-    type Error = ();
-    type Color = Rgb565;
-
-    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
-    where
-        I: IntoIterator<Item = Pixel<Self::Color>>,
-    {
-        let bb = self.bounding_box();
-
-        pixels
-            .into_iter()
-            .filter(|Pixel(pos, _color)| bb.contains(*pos))
-            .for_each(|Pixel(pos, color)| {
-                self.set_pixel(pos.x as u16, pos.y as u16, RawU16::from(color).into_inner());
-            });
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E = ()> {
+    /// Communication error
+    Comm(E),
+    /// Pin setting error
+    Pin(Infallible),
+    /// Communication kept failing after retrying, see `RetryInterface`
+    Timeout(E),
+}
 
-        Ok(())
+impl<E> Error<E> {
+    /// Wraps an error of the interface `DI`, timeouts become `Error::Timeout`.
+    fn from_interface<DI: WriteOnlyInterface<Error = E>>(error: E) -> Self {
+        if DI::is_timeout(&error) {
+            Self::Timeout(error)
+        } else {
+            Self::Comm(error)
+        }
     }
+}
 
-    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        let c = RawU16::from(color).into_inner();
-        let c = c.to_be_bytes();
-        for pixel in self.buffer.chunks_exact_mut(2) {
-            pixel.copy_from_slice(c.as_slice())
+impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Comm(e) => write!(f, "display communication error: {e:?}"),
+            Self::Pin(e) => match *e {},
+            Self::Timeout(e) => write!(f, "display communication timed out: {e:?}"),
         }
-        Ok(())
     }
 }
 
-impl<SPI, DC, RST, E, const WIDTH: u16, const HEIGHT: u16, const N: usize> OriginDimensions
-    for ST7735<SPI, DC, RST, WIDTH, HEIGHT, N>
-where
-    SPI: SpiDevice<Error = E>,
-    DC: OutputPin<Error = Infallible>,
-    RST: OutputPin<Error = Infallible>,
-{
-    fn size(&self) -> Size {
-        Size::new(u32::from(WIDTH), u32::from(HEIGHT))
+impl<E: core::fmt::Debug> core::error::Error for Error<E> {}
+
+/// Error of the `try_set_pixel` methods, the coords are outside of the framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutOfBounds {
+    pub x: u16,
+    pub y: u16,
+}
+
+impl core::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "pixel ({}, {}) is out of bounds", self.x, self.y)
     }
 }
 
-#[derive(Debug)]
-pub enum Error<E = ()> {
-    /// Communication error
-    Comm(E),
-    /// Pin setting error
-    Pin(Infallible),
+impl core::error::Error for OutOfBounds {}
+
+/// Error of `Frame::try_new`, the buffer does not hold `width` x `height` pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameSizeMismatch {
+    pub width: u32,
+    pub height: u32,
+    pub len: usize,
+}
+
+impl core::fmt::Display for FrameSizeMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}x{} frame needs {} bytes, the buffer has {}",
+            self.width,
+            self.height,
+            u64::from(self.width) * u64::from(self.height) * 2,
+            self.len
+        )
+    }
+}
+
+impl core::error::Error for FrameSizeMismatch {}
+
+/// Landscape sizes of common panels, picked by `Frame::default` from the buffer size.
+const FRAME_SIZES: [(u32, u32); 7] = [
+    (160, 128),
+    (160, 80),
+    (128, 128),
+    (240, 240),
+    (320, 240),
+    (240, 135),
+    (320, 170),
+];
+
+/// Returns the common panel size with `len` bytes, a single row of `len / 2`
+/// pixels if there is none.
+const fn frame_size(len: usize) -> (u32, u32) {
+    let mut i = 0;
+    while i < FRAME_SIZES.len() {
+        let (width, height) = FRAME_SIZES[i];
+        if width as usize * height as usize * 2 == len {
+            return (width, height);
+        }
+        i += 1;
+    }
+    ((len / 2) as u32, 1)
 }
 
 pub struct Frame<const N: usize> {
@@ -443,8 +1648,9 @@ pub struct Frame<const N: usize> {
 }
 
 impl<const N: usize> Frame<N> {
+    /// Creates a frame without checking the size, see `try_new`.
     #[must_use]
-    pub fn new(width: u32, height: u32, orientation: Orientation, buffer: [u8; N]) -> Self {
+    pub const fn new(width: u32, height: u32, orientation: Orientation, buffer: [u8; N]) -> Self {
         Self {
             width,
             height,
@@ -452,39 +1658,114 @@ impl<const N: usize> Frame<N> {
             buffer,
         }
     }
+
+    /// Creates a frame, failing unless `buffer` holds exactly `width` x `height` pixels.
+    pub const fn try_new(
+        width: u32,
+        height: u32,
+        orientation: Orientation,
+        buffer: [u8; N],
+    ) -> Result<Self, FrameSizeMismatch> {
+        if width as u64 * height as u64 * 2 != N as u64 {
+            return Err(FrameSizeMismatch {
+                width,
+                height,
+                len: N,
+            });
+        }
+        Ok(Self::new(width, height, orientation, buffer))
+    }
+
+    /// Size picked by `Default`, evaluated at compile time.
+    const DEFAULT_SIZE: (u32, u32) = frame_size(N);
+
+    /// Creates a black frame, usable in `static` initializers.
+    #[must_use]
+    pub const fn new_black(width: u32, height: u32, orientation: Orientation) -> Self {
+        Self::new(width, height, orientation, [0; N])
+    }
+
+    /// Sets a pixel color at the given coords, out of bounds coords are ignored.
     pub fn set_pixel(&mut self, x: u16, y: u16, color: Rgb565) {
-        let color = RawU16::from(color).into_inner();
-        let idx = match self.orientation {
-            Orientation::Landscape | Orientation::LandscapeSwapped => {
-                if u32::from(x) >= self.width {
-                    return;
-                }
-                (usize::from(y) * (self.width as usize)) + usize::from(x)
-            }
+        self.try_set_pixel(x, y, color).ok();
+    }
 
-            Orientation::Portrait | Orientation::PortraitSwapped => {
-                if u32::from(y) >= self.height {
-                    return;
-                }
-                ((y as usize) * self.height as usize) + (x as usize)
-            }
-        } * 2;
+    /// Sets a pixel color at the given coords, failing if they are out of bounds.
+    pub fn try_set_pixel(&mut self, x: u16, y: u16, color: Rgb565) -> Result<(), OutOfBounds> {
+        let color = RawU16::from(color).into_inner();
+        if u32::from(x) >= self.width || u32::from(y) >= self.height {
+            return Err(OutOfBounds { x, y });
+        }
+        let idx = (usize::from(y) * self.width as usize + usize::from(x)) * 2;
 
         // Split 16 bit value into two bytes
-        if let Some(pixel) = self.buffer.get_mut(idx..=idx + 1) {
-            let c = color.to_be_bytes();
-            pixel.copy_from_slice(c.as_slice())
+        let pixel = self
+            .buffer
+            .get_mut(idx..=idx + 1)
+            .ok_or(OutOfBounds { x, y })?;
+        pixel.copy_from_slice(color.to_be_bytes().as_slice());
+        Ok(())
+    }
+
+    /// Blends a color over the pixel at the given coords with `alpha` from 0 to 255.
+    pub fn blend_pixel(&mut self, x: u16, y: u16, color: Rgb565, alpha: u8) {
+        if let Some(bg) = self.get_pixel(x, y) {
+            self.set_pixel(x, y, blend::blend(bg, color, alpha));
         }
     }
+
+    /// Returns the pixel color at the given coords, `None` if they are out of bounds.
+    #[must_use]
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        if u32::from(x) >= self.width || u32::from(y) >= self.height {
+            return None;
+        }
+        let idx = (usize::from(y) * self.width as usize + usize::from(x)) * 2;
+        let pixel = self.buffer.get(idx..=idx + 1)?;
+        Some(RawU16::new(u16::from_be_bytes([pixel[0], pixel[1]])).into())
+    }
+
+    /// Copies the frame to another draw target at its origin, e.g. an
+    /// `embedded-graphics-simulator` display to preview a UI on the desktop.
+    ///
+    /// Pixels are read with the same layout `flush_frame` sends to the panel.
+    pub fn present<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let colors = self
+            .buffer
+            .chunks_exact(2)
+            .take(self.width as usize * self.height as usize)
+            .map(|bytes| RawU16::new(u16::from_be_bytes([bytes[0], bytes[1]])).into());
+        target.fill_contiguous(&self.bounding_box(), colors)
+    }
 }
+
+/// Logs the frame metadata, not the pixel data.
+#[cfg(feature = "defmt")]
+impl<const N: usize> defmt::Format for Frame<N> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Frame {{ width: {}, height: {}, orientation: {}, bytes: {} }}",
+            self.width,
+            self.height,
+            self.orientation,
+            N
+        );
+    }
+}
+
+/// Black landscape frame sized after `N`.
+///
+/// Picks the common panel size with `N` bytes, e.g. 160x128 for
+/// `buffer_size(160, 128)`. Any other `N` gets a single row covering the
+/// buffer, use `Frame::new_black` or `SizedFrame` to pick the size.
 impl<const N: usize> Default for Frame<N> {
     fn default() -> Self {
-        Self {
-            width: 160,
-            height: 128,
-            orientation: Orientation::Landscape,
-            buffer: [0; N],
-        }
+        let (width, height) = Self::DEFAULT_SIZE;
+        Self::new_black(width, height, Orientation::Landscape)
     }
 }
 
@@ -519,3 +1800,235 @@ impl<const N: usize> OriginDimensions for Frame<N> {
         Size::new(self.width, self.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::Recorder;
+
+    /// Orientations with their MADCTL byte and `GREEN_TAB_128X128` offset.
+    const ORIENTATIONS: [(Orientation, u8, (u16, u16)); 4] = [
+        (Orientation::Portrait, 0x00, (2, 1)),
+        (Orientation::Landscape, 0x60, (1, 2)),
+        (Orientation::PortraitSwapped, 0xC0, (2, 3)),
+        (Orientation::LandscapeSwapped, 0xA0, (3, 2)),
+    ];
+
+    /// Returns the data written after the last RAMWR.
+    #[cfg(feature = "buffered")]
+    fn memory_write(recorder: &Recorder) -> Vec<u8> {
+        recorder
+            .commands("DC")
+            .into_iter()
+            .rev()
+            .find(|(command, _)| *command == Instruction::RAMWR as u8)
+            .map(|(_, data)| data)
+            .unwrap()
+    }
+
+    #[test]
+    fn orientation_writes_madctl_and_offset_window() {
+        for (orientation, madctl, (dx, dy)) in ORIENTATIONS {
+            let recorder = Recorder::new();
+            let config = Config::new()
+                .orientation(Orientation::Portrait)
+                .orientation_offsets(OrientationOffsets::GREEN_TAB_128X128);
+            let mut display = ST7735IF::new(
+                recorder.spi(),
+                recorder.pin("DC"),
+                recorder.pin("RST"),
+                config,
+            );
+            block_on(display.set_orientation(orientation)).unwrap();
+            block_on(display.set_address_window(0, 0, 9, 19)).unwrap();
+            let commands = recorder.commands("DC");
+            let madctl_writes: Vec<_> = commands
+                .iter()
+                .filter(|(command, _)| *command == Instruction::MADCTL as u8)
+                .collect();
+            // Written once, also for the configured orientation, as the cache starts empty.
+            assert_eq!(madctl_writes, [&(0x36, vec![madctl])], "{orientation:?}");
+            let [dx0, dx1] = dx.to_be_bytes();
+            let [ex0, ex1] = (9 + dx).to_be_bytes();
+            let [dy0, dy1] = dy.to_be_bytes();
+            let [ey0, ey1] = (19 + dy).to_be_bytes();
+            assert_eq!(
+                commands[1..],
+                [
+                    (0x2A, vec![dx0, dx1, ex0, ex1]),
+                    (0x2B, vec![dy0, dy1, ey0, ey1]),
+                ],
+                "{orientation:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn frame_set_pixel_is_row_major_in_every_orientation() {
+        for (orientation, _, _) in ORIENTATIONS {
+            let (width, height) = if orientation.is_landscape() {
+                (160, 128)
+            } else {
+                (128, 160)
+            };
+            let mut frame = Frame::<{ buffer_size(128, 160) }>::new_black(
+                u32::from(width),
+                u32::from(height),
+                orientation,
+            );
+            for (x, y) in [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ] {
+                frame.set_pixel(x, y, Rgb565::WHITE);
+                let index = (usize::from(y) * usize::from(width) + usize::from(x)) * 2;
+                assert_eq!(
+                    frame.buffer[index..index + 2],
+                    [0xFF, 0xFF],
+                    "{orientation:?}"
+                );
+                assert_eq!(frame.get_pixel(x, y), Some(Rgb565::WHITE));
+            }
+            assert_eq!(frame.get_pixel(width, 0), None);
+            assert_eq!(frame.get_pixel(0, height), None);
+        }
+    }
+
+    /// Flushes `ST7735` pixels set near the corners and checks their position in the transfer.
+    #[cfg(feature = "buffered")]
+    fn check_buffered_set_pixel<const WIDTH: u16, const HEIGHT: u16, const N: usize>(
+        orientation: Orientation,
+    ) {
+        let recorder = Recorder::new();
+        let config = Config::new().orientation(orientation);
+        let mut display: ST7735<_, _, [u16; N]> = ST7735::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            config,
+            WIDTH,
+            HEIGHT,
+        );
+        let points = [
+            (1, 2),
+            (WIDTH - 1, 0),
+            (0, HEIGHT - 1),
+            (WIDTH - 1, HEIGHT - 1),
+        ];
+        for (x, y) in points {
+            display.set_pixel(x, y, 0xF800);
+        }
+        block_on(display.flush()).unwrap();
+        let data = memory_write(&recorder);
+        assert_eq!(data.len(), N * 2, "{orientation:?}");
+        for (x, y) in points {
+            let index = (usize::from(y) * usize::from(WIDTH) + usize::from(x)) * 2;
+            assert_eq!(data[index..index + 2], [0xF8, 0x00], "{orientation:?}");
+        }
+    }
+
+    #[cfg(feature = "buffered")]
+    #[test]
+    fn buffered_set_pixel_is_row_major_in_every_orientation() {
+        const N: usize = pixel_count(128, 160);
+        check_buffered_set_pixel::<128, 160, N>(Orientation::Portrait);
+        check_buffered_set_pixel::<160, 128, N>(Orientation::Landscape);
+        check_buffered_set_pixel::<128, 160, N>(Orientation::PortraitSwapped);
+        check_buffered_set_pixel::<160, 128, N>(Orientation::LandscapeSwapped);
+    }
+
+    #[test]
+    fn portrait_bgr_128x160_init_sequence() {
+        let recorder = Recorder::new();
+        let config = Config::portrait_bgr_128x160();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            config,
+        );
+        block_on(display.init(&mut crate::mock::NoDelay)).unwrap();
+        let expected: [(u8, &[u8]); 16] = [
+            (0x01, &[]),
+            (0x11, &[]),
+            (0xB1, &[0x01, 0x2C, 0x2D]),
+            (0xB2, &[0x01, 0x2C, 0x2D]),
+            (0xB3, &[0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D]),
+            (0xB4, &[0x07]),
+            (0xC0, &[0xA2, 0x02, 0x84]),
+            (0xC1, &[0xC5]),
+            (0xC2, &[0x0A, 0x00]),
+            (0xC3, &[0x8A, 0x2A]),
+            (0xC4, &[0x8A, 0xEE]),
+            (0xC5, &[0x0E]),
+            // Inverted colors, BGR in the native portrait scan, 16 bit pixels
+            (0x21, &[]),
+            (0x36, &[0x08]),
+            (0x3A, &[0x05]),
+            (0x29, &[]),
+        ];
+        let commands = recorder.commands("DC");
+        let commands: Vec<(u8, &[u8])> = commands
+            .iter()
+            .map(|(command, params)| (*command, params.as_slice()))
+            .collect();
+        assert_eq!(commands, expected);
+    }
+
+    #[test]
+    fn init_batches_commands_between_delays() {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new_3wire(recorder.spi(), recorder.pin("RST"), Config::new());
+        block_on(display.power_up(&mut crate::mock::NoDelay)).unwrap();
+        let transactions = recorder
+            .events()
+            .iter()
+            .filter(|event| **event == crate::mock::Event::TransactionStart)
+            .count();
+        // SWRESET and SLPOUT are followed by delays, the other 10 commands of
+        // the sequence go out together, then INVOFF, MADCTL, COLMOD and the
+        // landscape MADCTL.
+        assert_eq!(transactions, 7);
+    }
+
+    #[test]
+    fn portrait_bgr_128x160_maps_corners_to_gram_corners() {
+        let recorder = Recorder::new();
+        let panel = crate::emulator::EmulatedPanel::default();
+        let config = Config::portrait_bgr_128x160();
+        let mut display = ST7735IF::with_interface(panel, recorder.pin("RST"), config);
+        block_on(display.init(&mut crate::mock::NoDelay)).unwrap();
+        let mut frame =
+            Frame::<{ buffer_size(128, 160) }>::new_black(128, 160, Orientation::Portrait);
+        let corners = [
+            ((0, 0), Rgb565::RED),
+            ((127, 0), Rgb565::GREEN),
+            ((0, 159), Rgb565::BLUE),
+            ((127, 159), Rgb565::WHITE),
+        ];
+        for ((x, y), color) in corners {
+            frame.set_pixel(x, y, color);
+        }
+        block_on(display.flush_frame(&frame)).unwrap();
+        let panel = display.interface_mut();
+        for ((x, y), color) in corners {
+            assert_eq!(panel.memory_pixel(x, y), Some(color));
+        }
+        assert_eq!(panel.memory_pixel(1, 0), Some(Rgb565::BLACK));
+    }
+
+    #[test]
+    fn frame_default_is_sized_after_n() {
+        let frame = Frame::<{ buffer_size(160, 80) }>::default();
+        assert_eq!(frame.size(), Size::new(160, 80));
+        // No common panel has 100 pixels, the frame is one row.
+        let frame = Frame::<200>::default();
+        assert_eq!(frame.size(), Size::new(100, 1));
+    }
+}
@@ -0,0 +1,108 @@
+//! Memory data access control (MADCTL) register bits.
+use core::ops::{BitAnd, BitOr, BitOrAssign, BitXor};
+
+use crate::{Mirror, Orientation, PixelColor};
+
+/// MADCTL parameter, combine the bits with `|`.
+///
+/// MY, MX and MV set the scan direction and thereby the orientation, RGB the
+/// pixel order, ML and MH the refresh order of the panel, which only matters
+/// for tearing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Madctl(u8);
+
+impl Madctl {
+    /// No bits set: portrait scan, RGB order, top to bottom and left to right refresh.
+    pub const EMPTY: Self = Self(0x00);
+    /// Row address order, mirrors along the native y axis.
+    pub const MY: Self = Self(0x80);
+    /// Column address order, mirrors along the native x axis.
+    pub const MX: Self = Self(0x40);
+    /// Row/column exchange, the landscape scan.
+    pub const MV: Self = Self(0x20);
+    /// Vertical refresh order, bottom to top when set.
+    pub const ML: Self = Self(0x10);
+    /// Pixel order, BGR when set.
+    pub const RGB: Self = Self(0x08);
+    /// Horizontal refresh order, right to left when set.
+    pub const MH: Self = Self(0x04);
+    /// The scan direction bits MY, MX and MV.
+    pub const SCAN: Self = Self(0xE0);
+    /// The refresh order bits ML and MH.
+    pub const REFRESH_ORDER: Self = Self(0x14);
+
+    /// Creates the value from raw bits, the unused low bits are dropped.
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & 0xFC)
+    }
+
+    /// Composes the value for an orientation with output mirroring and pixel order.
+    #[must_use]
+    pub const fn new(orientation: Orientation, mirror: Mirror, rgb: PixelColor) -> Self {
+        Self::from_bits(crate::transform::madctl(orientation, mirror) | rgb as u8)
+    }
+
+    /// Returns the raw register value.
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether all bits of `other` are set.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the value with the bits of `other` set or cleared.
+    #[must_use]
+    pub const fn with(self, other: Self, set: bool) -> Self {
+        if set {
+            Self(self.0 | other.0)
+        } else {
+            Self(self.0 & !other.0)
+        }
+    }
+
+    /// Returns the pixel order of the RGB bit.
+    #[must_use]
+    pub const fn pixel_color(self) -> PixelColor {
+        if self.contains(Self::RGB) {
+            PixelColor::BGR
+        } else {
+            PixelColor::RGB
+        }
+    }
+}
+
+impl BitOr for Madctl {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Madctl {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Madctl {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitXor for Madctl {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
@@ -0,0 +1,338 @@
+//! Transfer metrics for measuring SPI bandwidth use.
+//!
+//! `MeteredSpi` wraps the `SpiDevice` handed to the driver and counts what
+//! goes over the bus, `FlushTimer` measures how long whole flushes take:
+//!
+//! ```ignore
+//! let spi = MeteredSpi::with_clock(spi, || Instant::now().as_micros());
+//! let mut display = ST7735::new(spi, dc, rst, config, 160, 128);
+//! let mut timer = FlushTimer::new(|| Instant::now().as_micros());
+//! timer.flush(&mut display).await?;
+//! let metrics = display.interface_mut().spi_mut().take_metrics();
+//! let timing = timer.take_timing();
+//! ```
+//!
+//! `EnergyMeter` turns the counters of each update into an energy estimate
+//! with coefficients measured on the device:
+//!
+//! ```ignore
+//! let mut energy = EnergyMeter::new(EnergyModel { nj_per_byte: 12, active_uw: 9_000 });
+//! energy.record(&metrics, timing.last_us);
+//! let totals = energy.totals();
+//! ```
+use core::future::Future;
+
+use embedded_hal_async::spi::{ErrorType, Operation, SpiDevice};
+
+/// Counters of a `MeteredSpi`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Metrics {
+    /// Bytes written, including the write half of transfers.
+    pub bytes: u64,
+    /// Completed and failed transactions.
+    pub transactions: u32,
+    /// Failed transactions.
+    pub errors: u32,
+    /// Time spent in transactions in microseconds, 0 without a clock.
+    ///
+    /// Only counts the bus being busy, gaps between transactions are left
+    /// out, see `FlushTimer` for the duration of whole flushes.
+    pub busy_us: u64,
+}
+
+impl Metrics {
+    /// Average throughput while busy in bytes per second, `None` without busy time.
+    #[must_use]
+    pub fn bytes_per_second(&self) -> Option<u64> {
+        (self.busy_us > 0).then(|| self.bytes.saturating_mul(1_000_000) / self.busy_us)
+    }
+}
+
+fn no_clock() -> u64 {
+    0
+}
+
+/// `SpiDevice` counting bytes, transactions and busy time.
+pub struct MeteredSpi<SPI, C = fn() -> u64> {
+    spi: SPI,
+    /// Monotonic clock in microseconds
+    clock: C,
+    metrics: Metrics,
+}
+
+impl<SPI: SpiDevice> MeteredSpi<SPI> {
+    /// Wraps `spi` without a clock, `Metrics::busy_us` stays 0.
+    pub fn new(spi: SPI) -> Self {
+        Self::with_clock(spi, no_clock)
+    }
+}
+
+impl<SPI: SpiDevice, C: FnMut() -> u64> MeteredSpi<SPI, C> {
+    /// Wraps `spi`, timing transactions with a monotonic microsecond `clock`.
+    pub fn with_clock(spi: SPI, clock: C) -> Self {
+        Self {
+            spi,
+            clock,
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Returns the counters.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns the counters and resets them, e.g. once per frame.
+    pub fn take_metrics(&mut self) -> Metrics {
+        core::mem::take(&mut self.metrics)
+    }
+
+    /// Returns the wrapped SPI device.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI: SpiDevice, C> ErrorType for MeteredSpi<SPI, C> {
+    type Error = SPI::Error;
+}
+
+impl<SPI: SpiDevice, C: FnMut() -> u64> SpiDevice for MeteredSpi<SPI, C> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let bytes: usize = operations
+            .iter()
+            .map(|operation| match operation {
+                Operation::Write(words) => words.len(),
+                Operation::Transfer(_, words) => words.len(),
+                Operation::TransferInPlace(words) => words.len(),
+                Operation::Read(_) | Operation::DelayNs(_) => 0,
+            })
+            .sum();
+        let start = (self.clock)();
+        let result = self.spi.transaction(operations).await;
+        let metrics = &mut self.metrics;
+        metrics.busy_us += (self.clock)().saturating_sub(start);
+        metrics.bytes += bytes as u64;
+        metrics.transactions += 1;
+        if result.is_err() {
+            metrics.errors += 1;
+        }
+        result
+    }
+}
+
+/// Wall-clock durations recorded by a `FlushTimer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlushTiming {
+    /// Timed flushes, including failed ones.
+    pub flushes: u32,
+    /// Sum of the flush durations in microseconds.
+    pub total_us: u64,
+    /// Longest flush in microseconds.
+    pub max_us: u64,
+    /// Duration of the last flush in microseconds.
+    pub last_us: u64,
+}
+
+impl FlushTiming {
+    /// Average flush duration in microseconds, `None` before the first flush.
+    #[must_use]
+    pub fn average_us(&self) -> Option<u64> {
+        (self.flushes > 0).then(|| self.total_us / u64::from(self.flushes))
+    }
+}
+
+/// Measures flushes from their start to their end with a monotonic microsecond clock.
+///
+/// Unlike `Metrics::busy_us` this includes the gaps between transactions,
+/// e.g. setting the window, chunking and delays.
+pub struct FlushTimer<C> {
+    clock: C,
+    timing: FlushTiming,
+}
+
+impl<C: FnMut() -> u64> FlushTimer<C> {
+    /// Creates a timer reading the monotonic microsecond `clock`.
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            timing: FlushTiming::default(),
+        }
+    }
+
+    /// Awaits `flush` and records its duration, e.g. `timer.time(display.flush_frame(&frame))`.
+    pub async fn time<F: Future>(&mut self, flush: F) -> F::Output {
+        let start = (self.clock)();
+        let result = flush.await;
+        let elapsed = (self.clock)().saturating_sub(start);
+        let timing = &mut self.timing;
+        timing.flushes += 1;
+        timing.total_us += elapsed;
+        timing.max_us = timing.max_us.max(elapsed);
+        timing.last_us = elapsed;
+        result
+    }
+
+    /// Flushes the framebuffer of `display` and records the duration.
+    #[cfg(feature = "buffered")]
+    pub async fn flush<T: crate::Tile>(&mut self, display: &mut T) -> Result<(), T::FlushError> {
+        self.time(display.flush()).await
+    }
+
+    /// Returns the recorded durations.
+    pub fn timing(&self) -> FlushTiming {
+        self.timing
+    }
+
+    /// Returns the recorded durations and resets them.
+    pub fn take_timing(&mut self) -> FlushTiming {
+        core::mem::take(&mut self.timing)
+    }
+}
+
+/// Coefficients of an energy estimate, e.g. from current measurements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnergyModel {
+    /// Energy per transferred byte in nanojoules.
+    pub nj_per_byte: u32,
+    /// Power drawn while the panel is active, e.g. during a flush, in microwatts.
+    pub active_uw: u32,
+}
+
+impl EnergyModel {
+    /// Estimated energy of `bytes` transferred over `active_us` in nanojoules.
+    #[must_use]
+    pub fn estimate_nj(&self, bytes: u64, active_us: u64) -> u64 {
+        let transfer = bytes.saturating_mul(u64::from(self.nj_per_byte));
+        // 1 uW for 1 us is 1 pJ
+        let active = active_us.saturating_mul(u64::from(self.active_uw)) / 1000;
+        transfer.saturating_add(active)
+    }
+}
+
+/// Cumulative totals of an `EnergyMeter`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EnergyTotals {
+    /// Recorded updates.
+    pub updates: u32,
+    /// Transferred bytes.
+    pub bytes: u64,
+    /// Panel active time in microseconds.
+    pub active_us: u64,
+    /// Estimated energy in nanojoules.
+    pub nj: u64,
+}
+
+/// Estimates the energy of every update with an `EnergyModel` and adds it up.
+pub struct EnergyMeter {
+    model: EnergyModel,
+    totals: EnergyTotals,
+}
+
+impl EnergyMeter {
+    #[must_use]
+    pub const fn new(model: EnergyModel) -> Self {
+        Self {
+            model,
+            totals: EnergyTotals {
+                updates: 0,
+                bytes: 0,
+                active_us: 0,
+                nj: 0,
+            },
+        }
+    }
+
+    /// Records an update from the `metrics` taken after it and the time the
+    /// panel was active for it, e.g. `FlushTiming::last_us`. Returns the
+    /// estimate of this update in nanojoules.
+    pub fn record(&mut self, metrics: &Metrics, active_us: u64) -> u64 {
+        let nj = self.model.estimate_nj(metrics.bytes, active_us);
+        let totals = &mut self.totals;
+        totals.updates += 1;
+        totals.bytes += metrics.bytes;
+        totals.active_us += active_us;
+        totals.nj = totals.nj.saturating_add(nj);
+        nj
+    }
+
+    /// Returns the coefficients.
+    pub fn model(&self) -> EnergyModel {
+        self.model
+    }
+
+    /// Returns the cumulative totals.
+    pub fn totals(&self) -> EnergyTotals {
+        self.totals
+    }
+
+    /// Returns the cumulative totals and resets them.
+    pub fn take_totals(&mut self) -> EnergyTotals {
+        core::mem::take(&mut self.totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+
+    use super::*;
+
+    #[test]
+    fn flush_timer_measures_from_start_to_end() {
+        let now = Cell::new(100);
+        let mut timer = FlushTimer::new(|| now.get());
+        for duration in [250, 50] {
+            block_on(timer.time(async {
+                // A gap between two transactions is part of the flush.
+                now.set(now.get() + duration);
+            }));
+        }
+        let timing = timer.take_timing();
+        assert_eq!(
+            timing,
+            FlushTiming {
+                flushes: 2,
+                total_us: 300,
+                max_us: 250,
+                last_us: 50,
+            }
+        );
+        assert_eq!(timing.average_us(), Some(150));
+        assert_eq!(timer.timing(), FlushTiming::default());
+    }
+
+    #[test]
+    fn energy_adds_byte_and_active_time_costs() {
+        let mut energy = EnergyMeter::new(EnergyModel {
+            nj_per_byte: 10,
+            active_uw: 5_000,
+        });
+        let metrics = Metrics {
+            bytes: 40_960,
+            ..Metrics::default()
+        };
+        // 409.6 uJ for the bytes, 5 mW for 20 ms is 100 uJ
+        assert_eq!(energy.record(&metrics, 20_000), 509_600);
+        assert_eq!(energy.record(&Metrics::default(), 1_000), 5_000);
+        assert_eq!(
+            energy.take_totals(),
+            EnergyTotals {
+                updates: 2,
+                bytes: 40_960,
+                active_us: 21_000,
+                nj: 514_600,
+            }
+        );
+        assert_eq!(energy.totals(), EnergyTotals::default());
+    }
+}
@@ -0,0 +1,238 @@
+//! Recording SPI device and pins for testing without hardware, requires the `std` feature.
+//!
+//! ```ignore
+//! let recorder = Recorder::new();
+//! let mut display = ST7735IF::new(recorder.spi(), recorder.pin("DC"), recorder.pin("RST"), config);
+//! display.set_orientation(Orientation::Landscape).await?;
+//! let (command, _) = recorder.commands("DC").pop().unwrap();
+//! assert_eq!(command, 0x36);
+//! ```
+use core::cell::RefCell;
+use core::convert::Infallible;
+use std::vec::Vec;
+
+use embedded_hal::digital::{self, OutputPin};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::{self, Operation, SpiBus, SpiDevice};
+
+/// Something that happened on a recorded bus or pin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// An `SpiDevice` transaction started, chip select asserted.
+    TransactionStart,
+    /// An `SpiDevice` transaction ended, chip select deasserted.
+    TransactionEnd,
+    /// Bytes written, including the write half of transfers.
+    Write(Vec<u8>),
+    /// Number of bytes read, reads return zeros.
+    Read(usize),
+    /// Delay within a transaction in nanoseconds.
+    Delay(u32),
+    /// A recorded pin was set.
+    Pin { name: &'static str, high: bool },
+}
+
+/// Shared log of the devices it hands out, in the order things happened.
+#[derive(Default)]
+pub struct Recorder {
+    events: RefCell<Vec<Event>>,
+}
+
+impl Recorder {
+    /// Creates an empty log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an SPI device or bus recording into this log.
+    #[must_use]
+    pub fn spi(&self) -> RecordingSpi<'_> {
+        RecordingSpi { recorder: self }
+    }
+
+    /// Returns an output pin recording into this log under `name`.
+    #[must_use]
+    pub fn pin(&self, name: &'static str) -> RecordingPin<'_> {
+        RecordingPin {
+            recorder: self,
+            name,
+        }
+    }
+
+    /// Returns a copy of all recorded events.
+    #[must_use]
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// Discards the recorded events, e.g. after initialization.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear();
+    }
+
+    /// Decodes the written bytes into `(command, parameters)` using the data/command pin `dc`.
+    ///
+    /// Bytes written while `dc` is low are commands, bytes written while it is
+    /// high are parameters or data of the last command.
+    #[must_use]
+    pub fn commands(&self, dc: &str) -> Vec<(u8, Vec<u8>)> {
+        let mut commands: Vec<(u8, Vec<u8>)> = Vec::new();
+        let mut data_phase = false;
+        for event in self.events.borrow().iter() {
+            match event {
+                Event::Pin { name, high } if *name == dc => data_phase = *high,
+                Event::Write(bytes) if data_phase => {
+                    if let Some((_, params)) = commands.last_mut() {
+                        params.extend_from_slice(bytes);
+                    }
+                }
+                Event::Write(bytes) => {
+                    commands.extend(bytes.iter().map(|&command| (command, Vec::new())));
+                }
+                _ => {}
+            }
+        }
+        commands
+    }
+
+    fn record(&self, event: Event) {
+        self.events.borrow_mut().push(event);
+    }
+}
+
+/// SPI device and bus recording into a `Recorder`, never fails.
+pub struct RecordingSpi<'a> {
+    recorder: &'a Recorder,
+}
+
+impl spi::ErrorType for RecordingSpi<'_> {
+    type Error = Infallible;
+}
+
+impl SpiDevice for RecordingSpi<'_> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        self.recorder.record(Event::TransactionStart);
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => SpiBus::write(self, bytes).await?,
+                Operation::Read(words) => SpiBus::read(self, words).await?,
+                Operation::Transfer(read, write) => SpiBus::transfer(self, read, write).await?,
+                Operation::TransferInPlace(words) => SpiBus::transfer_in_place(self, words).await?,
+                Operation::DelayNs(ns) => self.recorder.record(Event::Delay(*ns)),
+            }
+        }
+        self.recorder.record(Event::TransactionEnd);
+        Ok(())
+    }
+}
+
+impl SpiBus for RecordingSpi<'_> {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0);
+        self.recorder.record(Event::Read(words.len()));
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.recorder.record(Event::Write(words.to_vec()));
+        Ok(())
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::write(self, write).await?;
+        SpiBus::read(self, read).await
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::write(self, words).await?;
+        SpiBus::read(self, words).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Output pin recording its transitions into a `Recorder`.
+pub struct RecordingPin<'a> {
+    recorder: &'a Recorder,
+    name: &'static str,
+}
+
+impl digital::ErrorType for RecordingPin<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for RecordingPin<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.recorder.record(Event::Pin {
+            name: self.name,
+            high: false,
+        });
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.recorder.record(Event::Pin {
+            name: self.name,
+            high: true,
+        });
+        Ok(())
+    }
+}
+
+/// Delay returning immediately, e.g. to run `init` in tests.
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec;
+
+    use super::*;
+    use crate::{Config, Orientation, ST7735IF};
+
+    #[test]
+    fn records_commands_in_transactions() {
+        let recorder = Recorder::new();
+        let mut display = ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        );
+        block_on(display.set_orientation(Orientation::Portrait)).unwrap();
+        assert_eq!(recorder.commands("DC"), [(0x36, vec![0x00])]);
+        assert_eq!(
+            recorder.events(),
+            [
+                Event::Pin {
+                    name: "DC",
+                    high: false
+                },
+                Event::TransactionStart,
+                Event::Write(vec![0x36]),
+                Event::TransactionEnd,
+                Event::Pin {
+                    name: "DC",
+                    high: true
+                },
+                Event::TransactionStart,
+                Event::Write(vec![0x00]),
+                Event::TransactionEnd,
+            ]
+        );
+
+        recorder.clear();
+        block_on(display.set_orientation(Orientation::Landscape)).unwrap();
+        assert_eq!(recorder.commands("DC"), [(0x36, vec![0x60])]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Controller models sharing the transports, framebuffers and drawing code.
+//!
+//! A `Model` supplies what differs between MIPI DCS style controllers: the
+//! power up programming and the pixel format value. Everything after that,
+//! address windows, MADCTL orientation and memory writes, is common to the
+//! ST7735 and its siblings, so the drivers take the model as a type parameter:
+//!
+//! ```ignore
+//! let display = ST7735IF::new(spi, dc, rst, config).with_model(ST7789);
+//! ```
+use crate::instruction::Instruction;
+
+/// A command of a model's power up sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InitCommand {
+    /// Command byte
+    pub command: u8,
+    /// Parameters
+    pub params: &'static [u8],
+    /// Delay after the command in milliseconds
+    pub delay_ms: u32,
+}
+
+impl InitCommand {
+    #[must_use]
+    pub const fn new(command: u8, params: &'static [u8], delay_ms: u32) -> Self {
+        Self {
+            command,
+            params,
+            delay_ms,
+        }
+    }
+}
+
+/// Display controller model.
+pub trait Model {
+    /// Returns the controller name, e.g. for `dump_config`.
+    fn name(&self) -> &'static str;
+
+    /// Returns the commands run after the hardware reset.
+    ///
+    /// The sequence wakes the controller and programs frame rate, power and
+    /// gamma. The driver follows up with inversion, MADCTL and COLMOD, and
+    /// leaves the output off until `turn_on`.
+    fn init_sequence(&self) -> &[InitCommand];
+
+    /// Returns the COLMOD parameter selecting 16 bit `Rgb565` pixels.
+    fn colmod_16bit(&self) -> u8 {
+        0x05
+    }
+
+    /// Returns the display RAM `(columns, rows)` in the native portrait scan.
+    fn gram_size(&self) -> (u16, u16) {
+        (132, 162)
+    }
+
+    /// Whether the controller has the ST7735 frame rate registers FRMCTR1/2/3.
+    fn has_frame_rate_control(&self) -> bool {
+        true
+    }
+
+    /// Whether the controller has the ST7735 power registers PWCTR1-5 and VMCTR1.
+    fn has_power_control(&self) -> bool {
+        true
+    }
+}
+
+/// ST7735 chip variant, selects the power up programming.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChipVariant {
+    /// ST7735R, e.g. the 1.8" 128x160 modules.
+    #[default]
+    ST7735R,
+    /// ST7735S, e.g. the 0.96" 80x160 and 1.44" 128x128 modules.
+    ST7735S,
+}
+
+/// Sitronix ST7735, the default model, programmed for the given `ChipVariant`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ST7735(pub ChipVariant);
+
+impl Model for ST7735 {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            ChipVariant::ST7735R => "ST7735R",
+            ChipVariant::ST7735S => "ST7735S",
+        }
+    }
+
+    fn init_sequence(&self) -> &[InitCommand] {
+        match self.0 {
+            ChipVariant::ST7735R => &ST7735R_SEQUENCE,
+            ChipVariant::ST7735S => &ST7735S_SEQUENCE,
+        }
+    }
+}
+
+/// Frame rate and power programming of the ST7735R, the sequence of the original driver.
+const ST7735R_SEQUENCE: [InitCommand; 12] = [
+    InitCommand::new(Instruction::SWRESET as u8, &[], 200),
+    InitCommand::new(Instruction::SLPOUT as u8, &[], 200),
+    InitCommand::new(Instruction::FRMCTR1 as u8, &[0x01, 0x2C, 0x2D], 0),
+    InitCommand::new(Instruction::FRMCTR2 as u8, &[0x01, 0x2C, 0x2D], 0),
+    InitCommand::new(
+        Instruction::FRMCTR3 as u8,
+        &[0x01, 0x2C, 0x2D, 0x01, 0x2C, 0x2D],
+        0,
+    ),
+    InitCommand::new(Instruction::INVCTR as u8, &[0x07], 0),
+    InitCommand::new(Instruction::PWCTR1 as u8, &[0xA2, 0x02, 0x84], 0),
+    InitCommand::new(Instruction::PWCTR2 as u8, &[0xC5], 0),
+    InitCommand::new(Instruction::PWCTR3 as u8, &[0x0A, 0x00], 0),
+    InitCommand::new(Instruction::PWCTR4 as u8, &[0x8A, 0x2A], 0),
+    InitCommand::new(Instruction::PWCTR5 as u8, &[0x8A, 0xEE], 0),
+    InitCommand::new(Instruction::VMCTR1 as u8, &[0x0E], 0),
+];
+
+/// Frame rate, power and gamma programming of the ST7735S reference code.
+const ST7735S_SEQUENCE: [InitCommand; 14] = [
+    InitCommand::new(Instruction::SWRESET as u8, &[], 150),
+    InitCommand::new(Instruction::SLPOUT as u8, &[], 120),
+    InitCommand::new(Instruction::FRMCTR1 as u8, &[0x05, 0x3C, 0x3C], 0),
+    InitCommand::new(Instruction::FRMCTR2 as u8, &[0x05, 0x3C, 0x3C], 0),
+    InitCommand::new(
+        Instruction::FRMCTR3 as u8,
+        &[0x05, 0x3C, 0x3C, 0x05, 0x3C, 0x3C],
+        0,
+    ),
+    InitCommand::new(Instruction::INVCTR as u8, &[0x03], 0),
+    InitCommand::new(Instruction::PWCTR1 as u8, &[0x28, 0x08, 0x04], 0),
+    InitCommand::new(Instruction::PWCTR2 as u8, &[0xC0], 0),
+    InitCommand::new(Instruction::PWCTR3 as u8, &[0x0D, 0x00], 0),
+    InitCommand::new(Instruction::PWCTR4 as u8, &[0x8D, 0x2A], 0),
+    InitCommand::new(Instruction::PWCTR5 as u8, &[0x8D, 0xEE], 0),
+    InitCommand::new(Instruction::VMCTR1 as u8, &[0x1A], 0),
+    InitCommand::new(
+        Instruction::GMCTRP1 as u8,
+        &[
+            0x04, 0x22, 0x07, 0x0A, 0x2E, 0x30, 0x25, 0x2A, 0x28, 0x26, 0x2E, 0x3A, 0x00, 0x01,
+            0x03, 0x13,
+        ],
+        0,
+    ),
+    InitCommand::new(
+        Instruction::GMCTRN1 as u8,
+        &[
+            0x04, 0x16, 0x06, 0x0D, 0x2D, 0x26, 0x23, 0x27, 0x27, 0x25, 0x2D, 0x3B, 0x00, 0x01,
+            0x04, 0x13,
+        ],
+        0,
+    ),
+];
+
+/// Sitronix ST7789, e.g. 240x240 and 240x320 modules.
+///
+/// The controller's reset defaults are fine for most panels, most of them
+/// need `Config::inverted` set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ST7789;
+
+impl Model for ST7789 {
+    fn name(&self) -> &'static str {
+        "ST7789"
+    }
+
+    fn init_sequence(&self) -> &[InitCommand] {
+        const SEQUENCE: [InitCommand; 3] = [
+            InitCommand::new(Instruction::SWRESET as u8, &[], 150),
+            InitCommand::new(Instruction::SLPOUT as u8, &[], 120),
+            InitCommand::new(Instruction::NORON as u8, &[], 10),
+        ];
+        &SEQUENCE
+    }
+
+    fn colmod_16bit(&self) -> u8 {
+        0x55
+    }
+
+    fn gram_size(&self) -> (u16, u16) {
+        (240, 320)
+    }
+
+    fn has_frame_rate_control(&self) -> bool {
+        false
+    }
+
+    fn has_power_control(&self) -> bool {
+        false
+    }
+}
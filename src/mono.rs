@@ -0,0 +1,94 @@
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    pixelcolor::{BinaryColor, Rgb565},
+    prelude::*,
+};
+
+/// Calculates the required buffer size for a `MonoFramebuffer`.
+#[must_use]
+pub const fn mono_buffer_size(width: u16, height: u16) -> usize {
+    (width as usize * height as usize).div_ceil(8)
+}
+
+/// Framebuffer storing 1 bit per pixel.
+///
+/// Pixels are stored row-major, most significant bit first. The buffer is
+/// expanded to `foreground`/`background` colors while it is being flushed,
+/// see `ST7735IF::flush_mono`.
+pub struct MonoFramebuffer<const N: usize> {
+    pub width: u32,
+    pub height: u32,
+    /// Color of set (`BinaryColor::On`) pixels.
+    pub foreground: Rgb565,
+    /// Color of cleared (`BinaryColor::Off`) pixels.
+    pub background: Rgb565,
+    pub buffer: [u8; N],
+}
+
+impl<const N: usize> MonoFramebuffer<N> {
+    #[must_use]
+    pub fn new(width: u32, height: u32, foreground: Rgb565, background: Rgb565) -> Self {
+        Self {
+            width,
+            height,
+            foreground,
+            background,
+            buffer: [0; N],
+        }
+    }
+
+    /// Sets a pixel at the given coords.
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: BinaryColor) {
+        if u32::from(x) >= self.width || u32::from(y) >= self.height {
+            return;
+        }
+        let idx = usize::from(y) * self.width as usize + usize::from(x);
+        if let Some(byte) = self.buffer.get_mut(idx / 8) {
+            let mask = 0x80 >> (idx % 8);
+            if color.is_on() {
+                *byte |= mask;
+            } else {
+                *byte &= !mask;
+            }
+        }
+    }
+
+    /// Returns the expanded `Rgb565` colors of all pixels in row-major order.
+    pub fn colors(&self) -> impl Iterator<Item = Rgb565> + '_ {
+        let len = self.width as usize * self.height as usize;
+        self.buffer
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| byte & (0x80 >> bit) != 0))
+            .take(len)
+            .map(|on| if on { self.foreground } else { self.background })
+    }
+}
+
+impl<const N: usize> DrawTarget for MonoFramebuffer<N> {
+    type Error = ();
+    type Color = BinaryColor;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u16, pos.y as u16, color));
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let fill = if color.is_on() { 0xFF } else { 0x00 };
+        self.buffer.fill(fill);
+        Ok(())
+    }
+}
+
+impl<const N: usize> OriginDimensions for MonoFramebuffer<N> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
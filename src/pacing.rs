@@ -0,0 +1,192 @@
+//! Frame pacing to a target refresh rate.
+//!
+//! `Paced` reads a monotonic microsecond clock supplied by the application,
+//! e.g. `|| embassy_time::Instant::now().as_micros()`, and lets flushes
+//! through at most once per frame interval:
+//!
+//! ```ignore
+//! let mut pacer = Paced::from_fps(|| Instant::now().as_micros(), 30);
+//! loop {
+//!     update(&mut display);
+//!     // Skips the flush if the last one was less than 1/30 s ago.
+//!     pacer.flush(&mut display).await?;
+//! }
+//! ```
+//!
+//! When waiting for every frame, e.g. with `flush_paced`, frames whose render
+//! and flush took longer than the interval are counted as deadline misses:
+//!
+//! ```ignore
+//! loop {
+//!     update(&mut display);
+//!     pacer.flush_paced(&mut display, &mut Delay).await?;
+//!     if pacer.stats().missed > 0 {
+//!         defmt::warn!("{}", pacer.take_stats());
+//!     }
+//! }
+//! ```
+use embedded_hal_async::delay::DelayNs;
+
+/// Frame deadline statistics of a `Paced`, see `Paced::stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacingStats {
+    /// Frames measured by `wait`.
+    pub frames: u32,
+    /// Frames that took longer than the interval.
+    pub missed: u32,
+    /// Longest frame in microseconds.
+    pub worst_us: u64,
+}
+
+/// Limits flushes to one per frame interval.
+pub struct Paced<C> {
+    clock: C,
+    interval_us: u32,
+    /// Clock value of the last flush let through
+    last_us: Option<u64>,
+    /// Whether a flush was skipped since
+    pending: bool,
+    stats: PacingStats,
+}
+
+impl<C: FnMut() -> u64> Paced<C> {
+    /// Creates a pacer letting one flush through every `interval_us` microseconds.
+    pub fn new(clock: C, interval_us: u32) -> Self {
+        Self {
+            clock,
+            interval_us,
+            last_us: None,
+            pending: false,
+            stats: PacingStats::default(),
+        }
+    }
+
+    /// Creates a pacer for a refresh rate of `fps` frames per second.
+    pub fn from_fps(clock: C, fps: u32) -> Self {
+        Self::new(clock, 1_000_000 / fps.max(1))
+    }
+
+    /// Returns the frame interval in microseconds.
+    pub fn interval_us(&self) -> u32 {
+        self.interval_us
+    }
+
+    /// Microseconds until the next flush is due, 0 if it is due now.
+    pub fn remaining_us(&mut self) -> u32 {
+        let Some(last) = self.last_us else {
+            return 0;
+        };
+        let elapsed = (self.clock)().saturating_sub(last);
+        u32::try_from(u64::from(self.interval_us).saturating_sub(elapsed)).unwrap_or(u32::MAX)
+    }
+
+    /// Requests a flush, returns whether it is due and records it if so.
+    ///
+    /// A request that is not due is remembered, see `is_pending`, so several
+    /// skipped requests coalesce into the next flush.
+    pub fn request(&mut self) -> bool {
+        if self.remaining_us() > 0 {
+            self.pending = true;
+            return false;
+        }
+        self.last_us = Some((self.clock)());
+        self.pending = false;
+        true
+    }
+
+    /// Whether a request was skipped since the last flush let through.
+    ///
+    /// Poll with `request` when idle so the last change is not left on the
+    /// framebuffer only.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Deadline statistics of the frames measured by `wait`.
+    pub fn stats(&self) -> PacingStats {
+        self.stats
+    }
+
+    /// Returns the deadline statistics and resets them.
+    pub fn take_stats(&mut self) -> PacingStats {
+        core::mem::take(&mut self.stats)
+    }
+
+    /// Waits until the next flush is due and records it.
+    ///
+    /// The time since the previous `wait` returned, i.e. the last flush and
+    /// the render of this frame, is recorded in `stats` and counts as a missed
+    /// deadline if it exceeds the interval. Pausing between frames also counts,
+    /// so take the stats after a pause.
+    pub async fn wait<D: DelayNs>(&mut self, delay: &mut D) {
+        if let Some(last) = self.last_us {
+            let elapsed = (self.clock)().saturating_sub(last);
+            let stats = &mut self.stats;
+            stats.frames += 1;
+            stats.missed += u32::from(elapsed > u64::from(self.interval_us));
+            stats.worst_us = stats.worst_us.max(elapsed);
+        }
+        let remaining = self.remaining_us();
+        if remaining > 0 {
+            delay.delay_us(remaining).await;
+        }
+        self.last_us = Some((self.clock)());
+        self.pending = false;
+    }
+
+    /// Flushes `display` if a flush is due, otherwise skips it, returns whether it flushed.
+    #[cfg(feature = "buffered")]
+    pub async fn flush<T: crate::Tile>(&mut self, display: &mut T) -> Result<bool, T::FlushError> {
+        if !self.request() {
+            return Ok(false);
+        }
+        display.flush().await?;
+        Ok(true)
+    }
+
+    /// Waits until a flush is due, then flushes `display`.
+    #[cfg(feature = "buffered")]
+    pub async fn flush_paced<T, D>(
+        &mut self,
+        display: &mut T,
+        delay: &mut D,
+    ) -> Result<(), T::FlushError>
+    where
+        T: crate::Tile,
+        D: DelayNs,
+    {
+        self.wait(delay).await;
+        display.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::mock::NoDelay;
+
+    #[test]
+    fn frames_over_the_interval_are_missed_deadlines() {
+        let now = Cell::new(0);
+        let mut pacer = Paced::new(|| now.get(), 1000);
+        block_on(pacer.wait(&mut NoDelay));
+        for frame_us in [400, 1000, 1500, 900, 3000] {
+            now.set(now.get() + frame_us);
+            block_on(pacer.wait(&mut NoDelay));
+        }
+        assert_eq!(
+            pacer.take_stats(),
+            PacingStats {
+                frames: 5,
+                missed: 2,
+                worst_us: 3000,
+            }
+        );
+        assert_eq!(pacer.stats(), PacingStats::default());
+    }
+}
@@ -0,0 +1,119 @@
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    pixelcolor::{
+        raw::{RawData, RawU4},
+        PixelColor, Rgb565,
+    },
+    prelude::*,
+};
+
+/// Calculates the required buffer size for a `PaletteFramebuffer`.
+#[must_use]
+pub const fn palette_buffer_size(width: u16, height: u16) -> usize {
+    (width as usize * height as usize).div_ceil(2)
+}
+
+/// Index into the 16 entry palette of a `PaletteFramebuffer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PaletteIndex(u8);
+
+impl PaletteIndex {
+    /// Creates a palette index, only the low 4 bits of `index` are used.
+    #[must_use]
+    pub const fn new(index: u8) -> Self {
+        Self(index & 0x0F)
+    }
+
+    /// Returns the index value.
+    #[must_use]
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+}
+
+impl PixelColor for PaletteIndex {
+    type Raw = RawU4;
+}
+
+impl From<RawU4> for PaletteIndex {
+    fn from(raw: RawU4) -> Self {
+        Self::new(raw.into_inner())
+    }
+}
+
+/// Framebuffer storing 4 bit palette indices per pixel.
+///
+/// Pixels are stored row-major, two per byte with the first pixel in the high
+/// nibble. Indices are expanded through `palette` while the buffer is being
+/// flushed, see `ST7735IF::flush_palette`.
+pub struct PaletteFramebuffer<const N: usize> {
+    pub width: u32,
+    pub height: u32,
+    pub palette: [Rgb565; 16],
+    pub buffer: [u8; N],
+}
+
+impl<const N: usize> PaletteFramebuffer<N> {
+    #[must_use]
+    pub fn new(width: u32, height: u32, palette: [Rgb565; 16]) -> Self {
+        Self {
+            width,
+            height,
+            palette,
+            buffer: [0; N],
+        }
+    }
+
+    /// Sets a pixel palette index at the given coords.
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: PaletteIndex) {
+        if u32::from(x) >= self.width || u32::from(y) >= self.height {
+            return;
+        }
+        let idx = usize::from(y) * self.width as usize + usize::from(x);
+        if let Some(byte) = self.buffer.get_mut(idx / 2) {
+            if idx % 2 == 0 {
+                *byte = (*byte & 0x0F) | (color.index() << 4);
+            } else {
+                *byte = (*byte & 0xF0) | color.index();
+            }
+        }
+    }
+
+    /// Returns the expanded `Rgb565` colors of all pixels in row-major order.
+    pub fn colors(&self) -> impl Iterator<Item = Rgb565> + '_ {
+        let len = self.width as usize * self.height as usize;
+        self.buffer
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0F])
+            .take(len)
+            .map(|index| self.palette[usize::from(index)])
+    }
+}
+
+impl<const N: usize> DrawTarget for PaletteFramebuffer<N> {
+    type Error = ();
+    type Color = PaletteIndex;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| bb.contains(*pos))
+            .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u16, pos.y as u16, color));
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.buffer.fill(color.index() << 4 | color.index());
+        Ok(())
+    }
+}
+
+impl<const N: usize> OriginDimensions for PaletteFramebuffer<N> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
@@ -0,0 +1,150 @@
+//! Render pipeline handing completed frames to a flusher task.
+//!
+//! Drawing and transferring run in separate tasks of one executor: the
+//! `Renderer` draws into a free frame and submits it, the flusher task owns the
+//! display and transfers submitted frames.
+//!
+//! ```ignore
+//! static PIPE: StaticCell<FramePipe<'static, N>> = StaticCell::new();
+//! let pipe = &*PIPE.init(FramePipe::new(FRAME_A.init(frame()), FRAME_B.init(frame())));
+//! spawner.spawn(flusher(pipe, display))?;
+//!
+//! #[embassy_executor::task]
+//! async fn flusher(pipe: &'static FramePipe<'static, N>, mut display: Display) {
+//!     let error = pipe.run(&mut display).await;
+//! }
+//!
+//! // in the UI task
+//! let mut renderer = pipe.renderer();
+//! let frame = renderer.acquire().await;
+//! draw_ui(frame);
+//! renderer.submit(frame);
+//! ```
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+/// Two frames passed between a `Renderer` and a flusher task.
+///
+/// The renderer draws into one frame while the other is transferred. If the
+/// renderer submits faster than frames are transferred, the waiting frame is
+/// replaced by the newer one and handed out again. Acquired frames hold
+/// stale contents, so draw them completely.
+pub struct FramePipe<'a, const N: usize> {
+    /// Frames ready to be drawn into, both are free while the renderer
+    /// has not acquired one and the flusher releases its frame
+    free: [Cell<Option<&'a mut Frame<N>>>; 2],
+    /// Frame waiting to be transferred
+    ready: Cell<Option<&'a mut Frame<N>>>,
+    renderer_waker: Cell<Option<Waker>>,
+    flusher_waker: Cell<Option<Waker>>,
+}
+
+/// Handle drawing frames for a `FramePipe`.
+pub struct Renderer<'p, 'a, const N: usize> {
+    pipe: &'p FramePipe<'a, N>,
+}
+
+impl<'a, const N: usize> FramePipe<'a, N> {
+    #[must_use]
+    pub fn new(a: &'a mut Frame<N>, b: &'a mut Frame<N>) -> Self {
+        Self {
+            free: [Cell::new(Some(a)), Cell::new(None)],
+            ready: Cell::new(Some(b)),
+            renderer_waker: Cell::new(None),
+            flusher_waker: Cell::new(None),
+        }
+    }
+
+    /// Returns the handle for the drawing task.
+    pub fn renderer(&self) -> Renderer<'_, 'a, N> {
+        Renderer { pipe: self }
+    }
+
+    /// Waits for a submitted frame.
+    pub async fn receive(&self) -> &'a mut Frame<N> {
+        poll_fn(|cx| match self.ready.take() {
+            Some(frame) => Poll::Ready(frame),
+            None => {
+                self.flusher_waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Hands a received frame back to the renderer once it is transferred.
+    pub fn release(&self, frame: &'a mut Frame<N>) {
+        self.put_free(frame);
+        if let Some(waker) = self.renderer_waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Puts a frame into an empty free slot, with two frames one is always empty.
+    fn put_free(&self, frame: &'a mut Frame<N>) {
+        match self.free[0].take() {
+            None => self.free[0].set(Some(frame)),
+            Some(other) => {
+                self.free[0].set(Some(other));
+                self.free[1].set(Some(frame));
+            }
+        }
+    }
+
+    fn take_free(&self) -> Option<&'a mut Frame<N>> {
+        self.free[0].take().or_else(|| self.free[1].take())
+    }
+
+    /// Transfers submitted frames to `display` until a transfer fails, the body of the flusher task.
+    ///
+    /// The second frame passed to `new` is submitted right away, so it should
+    /// hold the initial screen contents.
+    pub async fn run<DI, RST, BL, M, E>(&self, display: &mut ST7735IF<DI, RST, BL, M>) -> Error<E>
+    where
+        DI: WriteOnlyInterface<Error = E>,
+        RST: OutputPin<Error = Infallible>,
+        BL: Backlight,
+        M: Model,
+    {
+        loop {
+            let frame = self.receive().await;
+            let result = display.flush_frame(frame).await;
+            self.release(frame);
+            if let Err(e) = result {
+                return e;
+            }
+        }
+    }
+}
+
+impl<'a, const N: usize> Renderer<'_, 'a, N> {
+    /// Waits for a frame to draw into.
+    pub async fn acquire(&mut self) -> &'a mut Frame<N> {
+        poll_fn(|cx| match self.pipe.take_free() {
+            Some(frame) => Poll::Ready(frame),
+            None => {
+                self.pipe.renderer_waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Submits a drawn frame for transfer.
+    ///
+    /// A frame still waiting for transfer is superseded and becomes free again.
+    pub fn submit(&mut self, frame: &'a mut Frame<N>) {
+        if let Some(stale) = self.pipe.ready.replace(Some(frame)) {
+            self.pipe.put_free(stale);
+        }
+        if let Some(waker) = self.pipe.flusher_waker.take() {
+            waker.wake();
+        }
+    }
+}
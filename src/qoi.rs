@@ -0,0 +1,269 @@
+//! Streaming decoder for QOI images, requires the `qoi` feature.
+//!
+//! Pixels are decoded one at a time and converted from RGB888 to `Rgb565`, the
+//! decoder state is a 64 entry color table. Alpha is ignored.
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    pixelcolor::{Rgb565, Rgb888},
+    prelude::*,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+const MAGIC: &[u8; 4] = b"qoif";
+const HEADER_LEN: usize = 14;
+
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_MASK: u8 = 0xC0;
+
+/// QOI image, e.g. from `include_bytes!`.
+#[derive(Clone, Copy, Debug)]
+pub struct QoiImage<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+}
+
+impl<'a> QoiImage<'a> {
+    /// Reads the image header, `None` if it is invalid or the image is larger than `u16::MAX`.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let header = data.get(..HEADER_LEN)?;
+        if &header[..4] != MAGIC {
+            return None;
+        }
+        let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let height = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        Some(Self {
+            data: &data[HEADER_LEN..],
+            width: u16::try_from(width).ok()?,
+            height: u16::try_from(height).ok()?,
+        })
+    }
+
+    /// Returns the image size.
+    #[must_use]
+    pub fn size(&self) -> Size {
+        Size::new(u32::from(self.width), u32::from(self.height))
+    }
+
+    /// Decodes the pixels row by row.
+    ///
+    /// Stops early if the data is truncated.
+    #[must_use]
+    pub fn pixels(&self) -> QoiPixels<'a> {
+        QoiPixels {
+            data: self.data,
+            index: [[0; 4]; 64],
+            pixel: [0, 0, 0, 255],
+            run: 0,
+            remaining: usize::from(self.width) * usize::from(self.height),
+        }
+    }
+
+    /// Draws the image into a frame with its top left corner at `pos`, clipped to the frame.
+    pub fn draw<const N: usize>(&self, frame: &mut Frame<N>, pos: Point) {
+        let width = usize::from(self.width.max(1));
+        for (i, color) in self.pixels().enumerate() {
+            let x = pos.x + (i % width) as i32;
+            let y = pos.y + (i / width) as i32;
+            if let (Ok(x), Ok(y)) = (u16::try_from(x), u16::try_from(y)) {
+                frame.set_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Iterator over the pixels of a `QoiImage`.
+pub struct QoiPixels<'a> {
+    data: &'a [u8],
+    /// Previously seen pixels by hash.
+    index: [[u8; 4]; 64],
+    /// Last pixel as RGBA.
+    pixel: [u8; 4],
+    /// Remaining repetitions of the last pixel.
+    run: u8,
+    remaining: usize,
+}
+
+impl QoiPixels<'_> {
+    fn take<const LEN: usize>(&mut self) -> Option<[u8; LEN]> {
+        let (bytes, rest) = self.data.split_first_chunk::<LEN>()?;
+        self.data = rest;
+        Some(*bytes)
+    }
+
+    fn decode(&mut self) -> Option<()> {
+        if self.run > 0 {
+            self.run -= 1;
+            return Some(());
+        }
+        let [op] = self.take()?;
+        match op {
+            OP_RGB => {
+                let [r, g, b] = self.take()?;
+                self.pixel = [r, g, b, self.pixel[3]];
+            }
+            OP_RGBA => self.pixel = self.take()?,
+            _ => match op & OP_MASK {
+                OP_INDEX => self.pixel = self.index[usize::from(op)],
+                OP_DIFF => {
+                    let [r, g, b, _] = &mut self.pixel;
+                    *r = r.wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                    *g = g.wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                    *b = b.wrapping_add(op & 0x03).wrapping_sub(2);
+                }
+                OP_LUMA => {
+                    let [next] = self.take()?;
+                    let [r, g, b, _] = &mut self.pixel;
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    *r = r.wrapping_add(dg).wrapping_add(next >> 4).wrapping_sub(8);
+                    *g = g.wrapping_add(dg);
+                    *b = b.wrapping_add(dg).wrapping_add(next & 0x0F).wrapping_sub(8);
+                }
+                // QOI_OP_RUN, the pixel repeats `(op & 0x3F) + 1` times.
+                _ => self.run = op & 0x3F,
+            },
+        }
+        let [r, g, b, a] = self.pixel;
+        let hash = usize::from(
+            r.wrapping_mul(3)
+                .wrapping_add(g.wrapping_mul(5))
+                .wrapping_add(b.wrapping_mul(7))
+                .wrapping_add(a.wrapping_mul(11)),
+        ) % 64;
+        self.index[hash] = self.pixel;
+        Some(())
+    }
+}
+
+impl Iterator for QoiPixels<'_> {
+    type Item = Rgb565;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.decode()?;
+        self.remaining -= 1;
+        let [r, g, b, _] = self.pixel;
+        Some(Rgb888::new(r, g, b).into())
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Decodes a QOI image straight to display RAM at `(x, y)`.
+    ///
+    /// Pixels are decoded in small chunks, no framebuffer is needed. The image
+    /// must lie within the display, it is not clipped. Invalid headers are
+    /// ignored, truncated data leaves the rest of the area unchanged.
+    pub async fn draw_qoi(&mut self, x: u16, y: u16, data: &[u8]) -> Result<(), Error<E>> {
+        let Some(image) = QoiImage::new(data) else {
+            return Ok(());
+        };
+        if image.width == 0 || image.height == 0 {
+            return Ok(());
+        }
+        self.start_window_write(x, y, x + image.width - 1, y + image.height - 1)
+            .await?;
+        self.write_colors(image.pixels()).await
+    }
+}
+
+/// Decodes a QOI image into a framebuffer of `size` at `(x, y)`, clipped to it.
+#[cfg(feature = "buffered")]
+pub(crate) fn blit_qoi(buffer: &mut [u16], size: (u16, u16), pos: (u16, u16), data: &[u8]) {
+    use embedded_graphics_core::pixelcolor::raw::{RawData, RawU16};
+
+    if let Some(image) = QoiImage::new(data) {
+        let pixels = image.pixels().map(|color| RawU16::from(color).into_inner());
+        crate::blit_pixels(buffer, size, pos, image.width, pixels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Rgb565 {
+        Rgb888::new(r, g, b).into()
+    }
+
+    /// Header of a `width` x `height` RGB image.
+    fn header(width: u32, height: u32) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data.extend([3, 0]);
+        data
+    }
+
+    #[test]
+    fn decodes_hand_built_ops() {
+        let mut data = header(4, 2);
+        data.extend([
+            OP_RGB, 0xFF, 0x00, 0x00, // red, index 50
+            0xC1, // OP_RUN, 2 more
+            0xAA, 0x3A, // OP_LUMA dg +10, dr - dg -5, db - dg +2
+            0x32, // OP_INDEX 50
+            0x5B, // OP_DIFF dr -1, dg 0, db +1
+            OP_RGBA, 0x00, 0x00, 0xFF, 0x80, // blue, alpha ignored
+            0xC0, // OP_RUN, 1 more
+            0, 0, 0, 0, 0, 0, 0, 1, // end marker
+        ]);
+        let image = QoiImage::new(&data).unwrap();
+        assert_eq!(image.size(), Size::new(4, 2));
+        let red = rgb(0xFF, 0, 0);
+        let blue = rgb(0, 0, 0xFF);
+        assert_eq!(
+            image.pixels().collect::<Vec<_>>(),
+            [
+                red,
+                red,
+                red,
+                rgb(4, 10, 12),
+                red,
+                rgb(0xFE, 0, 1),
+                blue,
+                blue
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_and_truncation_stay_within_the_image() {
+        let mut data = header(2, 1);
+        data.extend([OP_RGB, 0x00, 0xFF, 0x00, 0xFD]);
+        let image = QoiImage::new(&data).unwrap();
+        assert_eq!(image.pixels().count(), 2);
+        for len in HEADER_LEN..data.len() - 1 {
+            let image = QoiImage::new(&data[..len]).unwrap();
+            assert!(image.pixels().count() < 2);
+        }
+    }
+
+    #[test]
+    fn hostile_headers_are_rejected() {
+        let data = header(4, 2);
+        assert!(QoiImage::new(&data[..HEADER_LEN - 1]).is_none());
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'Q';
+        assert!(QoiImage::new(&bad_magic).is_none());
+        assert!(QoiImage::new(&header(0x1_0000, 1)).is_none());
+        assert!(QoiImage::new(&header(1, u32::MAX)).is_none());
+    }
+}
@@ -0,0 +1,766 @@
+//! QR code rendering, requires the `qr` feature.
+//!
+//! A small encoder for byte mode QR codes up to version 10 (57 x 57 modules,
+//! up to 271 bytes at low error correction), enough for pairing URLs and
+//! provisioning payloads. No heap is used, the encoder works on fixed size
+//! buffers.
+use embedded_graphics_core::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+/// Largest supported version.
+const MAX_VERSION: usize = 10;
+/// Modules per side of the largest supported version.
+const MAX_SIZE: usize = MAX_VERSION * 4 + 17;
+const MODULE_BYTES: usize = (MAX_SIZE * MAX_SIZE).div_ceil(8);
+/// Total codewords of the largest supported version.
+const MAX_CODEWORDS: usize = 346;
+/// Longest error correction block of the supported versions.
+const MAX_BLOCK_ECC: usize = 30;
+const MAX_BLOCKS: usize = 8;
+/// Light modules drawn around the code.
+const QUIET_ZONE: u32 = 4;
+
+/// Error correction codewords per block, indexed by level and version.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; MAX_VERSION + 1]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28],
+];
+
+/// Error correction blocks, indexed by level and version.
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; MAX_VERSION + 1]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8],
+];
+
+/// Error correction level, the share of codewords that can be restored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum QrEcc {
+    /// About 7 %
+    Low,
+    /// About 15 %
+    Medium,
+    /// About 25 %
+    Quartile,
+    /// About 30 %
+    High,
+}
+
+impl QrEcc {
+    /// Index into the codeword tables.
+    const fn ordinal(self) -> usize {
+        self as usize
+    }
+
+    /// Value of the format information bits.
+    const fn format_bits(self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium => 0,
+            Self::Quartile => 3,
+            Self::High => 2,
+        }
+    }
+}
+
+/// Error of `draw_qr`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum QrError<E = ()> {
+    /// The data does not fit into the largest supported version.
+    DataTooLong,
+    /// Drawing to the target failed.
+    Draw(E),
+}
+
+/// Encoded QR code symbol.
+pub struct QrCode {
+    size: usize,
+    modules: [u8; MODULE_BYTES],
+    /// Function pattern modules, which are not masked.
+    function: [u8; MODULE_BYTES],
+}
+
+impl QrCode {
+    /// Encodes `data` in byte mode using the smallest version that fits.
+    pub fn encode(data: &[u8], ecc: QrEcc) -> Result<Self, QrError> {
+        let version = (1..=MAX_VERSION)
+            .find(|&version| {
+                let count_bits = if version < 10 { 8 } else { 16 };
+                data.len() < 1 << count_bits
+                    && 4 + count_bits + data.len() * 8 <= num_data_codewords(version, ecc) * 8
+            })
+            .ok_or(QrError::DataTooLong)?;
+
+        let mut codewords = [0u8; MAX_CODEWORDS];
+        let data_len = num_data_codewords(version, ecc);
+        encode_segment(&mut codewords[..data_len], data, version);
+
+        let mut qr = Self {
+            size: version * 4 + 17,
+            modules: [0; MODULE_BYTES],
+            function: [0; MODULE_BYTES],
+        };
+        qr.draw_function_patterns(version, ecc);
+        let mut interleaved = [0u8; MAX_CODEWORDS];
+        let total = add_ecc_and_interleave(&codewords[..data_len], version, ecc, &mut interleaved);
+        qr.draw_codewords(&interleaved[..total]);
+
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(ecc, mask);
+            let penalty = qr.penalty_score();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            // Masking is its own inverse.
+            qr.apply_mask(mask);
+        }
+        qr.apply_mask(best.1);
+        qr.draw_format_bits(ecc, best.1);
+        Ok(qr)
+    }
+
+    /// Number of modules per side.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module at column `x` and row `y` is dark, out of bounds modules are light.
+    #[must_use]
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && get_bit(&self.modules, y * self.size + x)
+    }
+
+    /// Draws the code with its quiet zone, each module `scale` pixels wide.
+    pub fn draw<D>(&self, target: &mut D, scale: u32, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let side = (self.size as u32 + 2 * QUIET_ZONE) * scale;
+        target.fill_solid(
+            &Rectangle::new(position, Size::new(side, side)),
+            Rgb565::WHITE,
+        )?;
+        let origin = position + Point::new_equal((QUIET_ZONE * scale) as i32);
+        for y in 0..self.size {
+            for x in (0..self.size).filter(|&x| self.module(x, y)) {
+                let top_left = origin + Point::new(x as i32, y as i32) * scale as i32;
+                target.fill_solid(
+                    &Rectangle::new(top_left, Size::new_equal(scale)),
+                    Rgb565::BLACK,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        let index = y * self.size + x;
+        set_bit(&mut self.modules, index, dark);
+        set_bit(&mut self.function, index, true);
+    }
+
+    fn draw_function_patterns(&mut self, version: usize, ecc: QrEcc) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder_pattern(x, y);
+        }
+        let mut positions = [0usize; 7];
+        let positions = alignment_pattern_positions(version, &mut positions);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                let corner = (i == 0 && (j == 0 || j == last)) || (i == last && j == 0);
+                if !corner {
+                    self.draw_alignment_pattern(x, y);
+                }
+            }
+        }
+        // Reserve the format areas, the real bits are drawn after masking.
+        self.draw_format_bits(ecc, 0);
+        self.draw_version(version);
+    }
+
+    fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let xx = x as i32 + dx;
+                let yy = y as i32 + dy;
+                if (0..self.size as i32).contains(&xx) && (0..self.size as i32).contains(&yy) {
+                    let dist = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, dist != 2 && dist != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as i32 + dx) as usize, (y as i32 + dy) as usize, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, ecc: QrEcc, mask: u8) {
+        let data = ecc.format_bits() << 3 | u32::from(mask);
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: u32| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i as u32));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i as u32));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i as u32));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i as u32));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let mut rem = version as u32;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = (version as u32) << 12 | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Places the codewords in the zigzag scan over the non-function modules.
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    let index = y * size + x;
+                    if !get_bit(&self.function, index) && i < data.len() * 8 {
+                        let dark = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        set_bit(&mut self.modules, index, dark);
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if invert && !get_bit(&self.function, index) {
+                    let dark = get_bit(&self.modules, index);
+                    set_bit(&mut self.modules, index, !dark);
+                }
+            }
+        }
+    }
+
+    /// Penalty of the current symbol according to the four rules of ISO/IEC 18004.
+    fn penalty_score(&self) -> u32 {
+        let size = self.size;
+        let mut penalty = 0;
+        // Adjacent modules in rows and columns of the same color, and finder-like patterns.
+        for transpose in [false, true] {
+            let at = |a: usize, b: usize| {
+                if transpose {
+                    self.module(b, a)
+                } else {
+                    self.module(a, b)
+                }
+            };
+            for b in 0..size {
+                let mut run = 0;
+                let mut color = false;
+                for a in 0..size {
+                    let dark = at(a, b);
+                    if a > 0 && dark == color {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            penalty += 3 + (run - 5);
+                        }
+                        run = 1;
+                        color = dark;
+                    }
+                }
+                if run >= 5 {
+                    penalty += 3 + (run - 5);
+                }
+                // 1:1:3:1:1 pattern with four light modules on one side.
+                for a in 0..size + 4 {
+                    let start = a as i32 - 10;
+                    let module = |i: i32| {
+                        let pos = start + i;
+                        pos >= 0 && (pos as usize) < size && at(pos as usize, b)
+                    };
+                    let core = [true, false, true, true, true, false, true];
+                    let light = |from: i32| (from..from + 4).all(|i| !module(i));
+                    if core
+                        .iter()
+                        .enumerate()
+                        .all(|(i, &c)| module(i as i32 + 4) == c)
+                        && (light(0) || light(11))
+                    {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+        // 2x2 blocks of the same color.
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.module(x, y);
+                if color == self.module(x + 1, y)
+                    && color == self.module(x, y + 1)
+                    && color == self.module(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        // Balance of dark and light modules.
+        let total = (size * size) as u32;
+        let dark = (0..size * size)
+            .filter(|&i| get_bit(&self.modules, i))
+            .count() as u32;
+        let k = (dark * 20)
+            .abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+        penalty + k * 10
+    }
+}
+
+/// Encodes `data` as a QR code and draws it with its quiet zone at `position`.
+///
+/// Uses medium error correction, each module is `scale` pixels wide. The code
+/// is `(size + 8) * scale` pixels wide, e.g. 33 modules for short URLs.
+pub fn draw_qr<D>(
+    target: &mut D,
+    data: &[u8],
+    scale: u32,
+    position: Point,
+) -> Result<(), QrError<D::Error>>
+where
+    D: DrawTarget<Color = Rgb565>,
+{
+    let qr = QrCode::encode(data, QrEcc::Medium).map_err(|_| QrError::DataTooLong)?;
+    qr.draw(target, scale, position).map_err(QrError::Draw)
+}
+
+fn get_bit(bits: &[u8], index: usize) -> bool {
+    bits[index / 8] & (1 << (index % 8)) != 0
+}
+
+fn set_bit(bits: &mut [u8], index: usize, value: bool) {
+    if value {
+        bits[index / 8] |= 1 << (index % 8);
+    } else {
+        bits[index / 8] &= !(1 << (index % 8));
+    }
+}
+
+/// Number of modules available for codewords, including remainder bits.
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn num_data_codewords(version: usize, ecc: QrEcc) -> usize {
+    let ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.ordinal()][version]);
+    let blocks = usize::from(NUM_ERROR_CORRECTION_BLOCKS[ecc.ordinal()][version]);
+    num_raw_data_modules(version) / 8 - ecc_len * blocks
+}
+
+fn alignment_pattern_positions(version: usize, buf: &mut [usize; 7]) -> &[usize] {
+    if version == 1 {
+        return &[];
+    }
+    let num_align = version / 7 + 2;
+    let step = (version * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+    buf[0] = 6;
+    let mut pos = version * 4 + 17 - 7;
+    for i in (1..num_align).rev() {
+        buf[i] = pos;
+        pos -= step;
+    }
+    &buf[..num_align]
+}
+
+/// Writes the byte mode segment, terminator and padding into `out`.
+fn encode_segment(out: &mut [u8], data: &[u8], version: usize) {
+    let mut bit = 0;
+    let mut push = |value: u32, len: usize| {
+        for i in (0..len).rev() {
+            if (value >> i) & 1 != 0 {
+                out[bit / 8] |= 0x80 >> (bit % 8);
+            }
+            bit += 1;
+        }
+    };
+    push(0b0100, 4);
+    push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for &byte in data {
+        push(u32::from(byte), 8);
+    }
+    let capacity = out.len() * 8;
+    // Terminator of up to four zero bits, then pad to a byte boundary.
+    let bit = (bit + 4).min(capacity).next_multiple_of(8);
+    for (i, pad) in out[bit / 8..].iter_mut().enumerate() {
+        *pad = if i % 2 == 0 { 0xEC } else { 0x11 };
+    }
+}
+
+/// Splits the data into blocks, appends their error correction codewords and interleaves them.
+fn add_ecc_and_interleave(
+    data: &[u8],
+    version: usize,
+    ecc: QrEcc,
+    out: &mut [u8; MAX_CODEWORDS],
+) -> usize {
+    let num_blocks = usize::from(NUM_ERROR_CORRECTION_BLOCKS[ecc.ordinal()][version]);
+    let block_ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.ordinal()][version]);
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let mut divisor = [0u8; MAX_BLOCK_ECC];
+    reed_solomon_divisor(&mut divisor[..block_ecc_len]);
+    let mut eccs = [[0u8; MAX_BLOCK_ECC]; MAX_BLOCKS];
+    let mut starts = [0usize; MAX_BLOCKS];
+    let mut start = 0;
+    for (i, ecc) in eccs.iter_mut().enumerate().take(num_blocks) {
+        let data_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        starts[i] = start;
+        reed_solomon_remainder(
+            &data[start..start + data_len],
+            &divisor[..block_ecc_len],
+            &mut ecc[..block_ecc_len],
+        );
+        start += data_len;
+    }
+
+    // Short blocks have one data codeword less, skip that position for them.
+    let short_data_len = short_block_len - block_ecc_len;
+    let mut len = 0;
+    for i in 0..=short_block_len {
+        for j in 0..num_blocks {
+            let short = j < num_short_blocks;
+            let byte = if i < short_data_len || (i == short_data_len && !short) {
+                data[starts[j] + i]
+            } else if i == short_data_len {
+                continue;
+            } else {
+                eccs[j][i - short_data_len - 1]
+            };
+            out[len] = byte;
+            len += 1;
+        }
+    }
+    len
+}
+
+fn reed_solomon_divisor(result: &mut [u8]) {
+    let degree = result.len();
+    result.fill(0);
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8], result: &mut [u8]) {
+    result.fill(0);
+    for &byte in data {
+        let factor = byte ^ result[0];
+        result.copy_within(1.., 0);
+        let last = result.len() - 1;
+        result[last] = 0;
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_multiply(d, factor);
+        }
+    }
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= u16::from((y >> i) & 1) * u16::from(x);
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Format information read from the copy around the top left finder.
+    fn read_format_bits(qr: &QrCode) -> u32 {
+        (0..15)
+            .map(|i| {
+                let (x, y) = match i {
+                    0..=5 => (8, i),
+                    6 => (8, 7),
+                    7 => (8, 8),
+                    8 => (7, 8),
+                    _ => (14 - i, 8),
+                };
+                u32::from(qr.module(x, y)) << i
+            })
+            .sum()
+    }
+
+    /// Format information read from the copy split between the other two finders.
+    fn read_second_format_bits(qr: &QrCode) -> u32 {
+        let size = qr.size();
+        (0..15)
+            .map(|i| {
+                let (x, y) = if i < 8 {
+                    (size - 1 - i, 8)
+                } else {
+                    (8, size - 15 + i)
+                };
+                u32::from(qr.module(x, y)) << i
+            })
+            .sum()
+    }
+
+    #[test]
+    fn gf_multiply_uses_the_qr_polynomial() {
+        assert_eq!(gf_multiply(0x80, 0x02), 0x1D);
+        assert_eq!(gf_multiply(0x02, 0x80), 0x1D);
+        assert_eq!(gf_multiply(0x53, 0x01), 0x53);
+        assert_eq!(gf_multiply(0x53, 0x00), 0x00);
+        // 2^255 == 1 in GF(256)
+        let power = (0..255).fold(1, |acc, _| gf_multiply(acc, 0x02));
+        assert_eq!(power, 1);
+    }
+
+    #[test]
+    fn reed_solomon_matches_iso_18004_example() {
+        // ISO/IEC 18004 Annex I, "01234567" as 1-M
+        let data = [
+            0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11,
+            0xEC, 0x11,
+        ];
+        let mut divisor = [0; 10];
+        reed_solomon_divisor(&mut divisor);
+        let mut ecc = [0; 10];
+        reed_solomon_remainder(&data, &divisor, &mut ecc);
+        assert_eq!(
+            ecc,
+            [0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]
+        );
+    }
+
+    #[test]
+    fn reed_solomon_matches_hello_world_example() {
+        // "HELLO WORLD" as 1-M
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17,
+        ];
+        let mut divisor = [0; 10];
+        reed_solomon_divisor(&mut divisor);
+        let mut ecc = [0; 10];
+        reed_solomon_remainder(&data, &divisor, &mut ecc);
+        assert_eq!(ecc, [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn codeword_counts_match_the_version_tables() {
+        assert_eq!(num_raw_data_modules(1), 208);
+        assert_eq!(num_raw_data_modules(2), 359);
+        assert_eq!(num_raw_data_modules(7), 1568);
+        assert_eq!(num_raw_data_modules(10), 2768);
+        let version_1 = [QrEcc::Low, QrEcc::Medium, QrEcc::Quartile, QrEcc::High]
+            .map(|ecc| num_data_codewords(1, ecc));
+        assert_eq!(version_1, [19, 16, 13, 9]);
+        assert_eq!(num_data_codewords(10, QrEcc::Low), 274);
+    }
+
+    #[test]
+    fn alignment_patterns_match_the_position_table() {
+        let mut buf = [0; 7];
+        assert_eq!(alignment_pattern_positions(1, &mut buf), &[] as &[usize]);
+        assert_eq!(alignment_pattern_positions(2, &mut buf), [6, 18]);
+        assert_eq!(alignment_pattern_positions(7, &mut buf), [6, 22, 38]);
+        assert_eq!(alignment_pattern_positions(10, &mut buf), [6, 28, 50]);
+    }
+
+    #[test]
+    fn byte_segment_is_terminated_and_padded() {
+        let mut out = [0; 16];
+        encode_segment(&mut out, b"A", 1);
+        assert_eq!(
+            out,
+            [
+                0x40, 0x14, 0x10, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC,
+                0x11, 0xEC
+            ]
+        );
+    }
+
+    #[test]
+    fn smallest_version_is_chosen_by_byte_capacity() {
+        let data = [b'x'; 272];
+        for (ecc, capacity) in [
+            (QrEcc::Low, 17),
+            (QrEcc::Medium, 14),
+            (QrEcc::Quartile, 11),
+            (QrEcc::High, 7),
+        ] {
+            assert_eq!(QrCode::encode(&data[..capacity], ecc).unwrap().size(), 21);
+            assert_eq!(
+                QrCode::encode(&data[..capacity + 1], ecc).unwrap().size(),
+                25
+            );
+        }
+        assert_eq!(QrCode::encode(&data[..271], QrEcc::Low).unwrap().size(), 57);
+        assert!(matches!(
+            QrCode::encode(&data, QrEcc::Low),
+            Err(QrError::DataTooLong)
+        ));
+    }
+
+    #[test]
+    fn format_bits_match_iso_18004_example() {
+        // ISO/IEC 18004 Annex C, level M with mask 101
+        let mut qr = QrCode::encode(b"01234567", QrEcc::Medium).unwrap();
+        qr.draw_format_bits(QrEcc::Medium, 0b101);
+        assert_eq!(read_format_bits(&qr), 0b100_0000_1100_1110);
+        assert_eq!(read_second_format_bits(&qr), 0b100_0000_1100_1110);
+        // Level L with mask 0 and level H with mask 7
+        qr.draw_format_bits(QrEcc::Low, 0);
+        assert_eq!(read_format_bits(&qr), 0b111_0111_1100_0100);
+        qr.draw_format_bits(QrEcc::High, 7);
+        assert_eq!(read_format_bits(&qr), 0b000_1000_0011_1011);
+        // Dark module
+        assert!(qr.module(8, qr.size() - 8));
+    }
+
+    #[test]
+    fn version_bits_match_iso_18004_example() {
+        // Version 7 is 000111 110010010100
+        let qr = QrCode::encode(&[0; 110], QrEcc::Medium).unwrap();
+        assert_eq!(qr.size(), 45);
+        let size = qr.size();
+        let bits = |transpose: bool| -> u32 {
+            (0..18)
+                .map(|i| {
+                    let (a, b) = (size - 11 + i % 3, i / 3);
+                    let dark = if transpose {
+                        qr.module(b, a)
+                    } else {
+                        qr.module(a, b)
+                    };
+                    u32::from(dark) << i
+                })
+                .sum()
+        };
+        assert_eq!(bits(false), 0x07C94);
+        assert_eq!(bits(true), 0x07C94);
+    }
+
+    #[test]
+    fn encoded_symbol_records_its_level_and_mask() {
+        let qr = QrCode::encode(b"https://embassy.dev", QrEcc::Quartile).unwrap();
+        let format = read_format_bits(&qr);
+        assert_eq!(format, read_second_format_bits(&qr));
+        let data = (format ^ 0x5412) >> 10;
+        assert_eq!(data >> 3, QrEcc::Quartile.format_bits());
+        // The chosen mask has the lowest penalty.
+        let mask = (data & 7) as u8;
+        let penalty = qr.penalty_score();
+        let mut other = QrCode::encode(b"https://embassy.dev", QrEcc::Quartile).unwrap();
+        other.apply_mask(mask);
+        for candidate in 0..8 {
+            other.apply_mask(candidate);
+            other.draw_format_bits(QrEcc::Quartile, candidate);
+            assert!(other.penalty_score() >= penalty);
+            other.apply_mask(candidate);
+        }
+    }
+
+    #[test]
+    fn masks_follow_the_iso_18004_conditions() {
+        let mut qr = QrCode::encode(b"A", QrEcc::Low).unwrap();
+        let before = qr.modules;
+        // Column 9, rows 9 and 10 are data modules.
+        qr.apply_mask(0);
+        assert_ne!(qr.module(9, 9), get_bit(&before, 9 * 21 + 9));
+        assert_eq!(qr.module(9, 10), get_bit(&before, 10 * 21 + 9));
+        // Function patterns are never masked.
+        assert_eq!(qr.module(0, 0), get_bit(&before, 0));
+        qr.apply_mask(0);
+        assert_eq!(qr.modules, before);
+    }
+}
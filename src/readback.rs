@@ -0,0 +1,151 @@
+//! Reading identification and status back from the controller.
+//!
+//! Requires a `ReadInterface`, i.e. a transport with the controller's data
+//! output wired up:
+//!
+//! ```ignore
+//! let [manufacturer, version, driver] = display.read_id().await?;
+//! info!("panel {:02x} {:02x} {:02x}", manufacturer, version, driver);
+//!
+//! let status = display.read_status().await?;
+//! if status.sleeping || !status.display_on {
+//!     display.recover(&mut delay).await?;
+//! }
+//! ```
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+
+use crate::instruction::Instruction;
+use crate::interface::ReadInterface;
+use crate::{Backlight, Error, Model, ST7735IF};
+
+/// Decoded display status as reported by RDDST.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayStatus {
+    /// Booster voltage is on
+    pub booster_on: bool,
+    /// Memory access control, the MY, MX, MV, ML, RGB and MH bits of MADCTL
+    pub madctl: u8,
+    /// Interface pixel format, the low COLMOD bits
+    pub pixel_format: u8,
+    pub idle_mode: bool,
+    pub partial_mode: bool,
+    /// Sleep mode, controller is asleep after reset and SLPIN
+    pub sleeping: bool,
+    pub normal_mode: bool,
+    pub vertical_scrolling: bool,
+    pub inverted: bool,
+    pub display_on: bool,
+    pub tearing_effect_on: bool,
+    /// Selected gamma curve
+    pub gamma_curve: u8,
+    /// Tearing effect output also signals horizontal blanking
+    pub tearing_effect_hblank: bool,
+}
+
+impl DisplayStatus {
+    /// Decodes the four RDDST response bytes.
+    #[must_use]
+    pub fn from_bytes(raw: [u8; 4]) -> Self {
+        let bit = |byte: u8, bit: u8| byte & (1 << bit) != 0;
+        Self {
+            booster_on: bit(raw[0], 7),
+            madctl: (raw[0] << 1) & 0xFC,
+            pixel_format: (raw[1] >> 4) & 0x07,
+            idle_mode: bit(raw[1], 3),
+            partial_mode: bit(raw[1], 2),
+            sleeping: !bit(raw[1], 1),
+            normal_mode: bit(raw[1], 0),
+            vertical_scrolling: bit(raw[2], 7),
+            inverted: bit(raw[2], 5),
+            display_on: bit(raw[2], 2),
+            tearing_effect_on: bit(raw[2], 1),
+            gamma_curve: ((raw[2] & 0x01) << 2) | (raw[3] >> 6),
+            tearing_effect_hblank: bit(raw[3], 5),
+        }
+    }
+}
+
+/// Result of the self-diagnostics the controller runs on sleep out, read through RDDSDR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SelfDiagnostic {
+    /// Registers loaded and the display works
+    Passed,
+    /// Loading the factory defaults into the registers failed
+    RegisterLoadingFault,
+    /// The display functionality check failed
+    FunctionalityFault,
+    /// Both checks failed
+    RegisterLoadingAndFunctionalityFault,
+}
+
+impl SelfDiagnostic {
+    /// Decodes the RDDSDR response byte, the controller sets D7 and D6 for passed checks.
+    #[must_use]
+    pub fn from_byte(raw: u8) -> Self {
+        match (raw & 0x80 != 0, raw & 0x40 != 0) {
+            (true, true) => Self::Passed,
+            (false, true) => Self::RegisterLoadingFault,
+            (true, false) => Self::FunctionalityFault,
+            (false, false) => Self::RegisterLoadingAndFunctionalityFault,
+        }
+    }
+
+    /// Whether all checks passed.
+    #[must_use]
+    pub fn passed(self) -> bool {
+        self == Self::Passed
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: ReadInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Reads the display ID (RDDID): manufacturer, driver version and driver ID.
+    ///
+    /// An ST7735 answers with a manufacturer ID of 0x7C, the other bytes
+    /// identify the panel batch.
+    pub async fn read_id(&mut self) -> Result<[u8; 3], Error<E>> {
+        let mut id = [0; 3];
+        self.read_command(Instruction::RDDID, 1, &mut id).await?;
+        Ok(id)
+    }
+
+    /// Reads and decodes the display status (RDDST).
+    pub async fn read_status(&mut self) -> Result<DisplayStatus, Error<E>> {
+        let mut raw = [0; 4];
+        self.read_command(Instruction::RDDST, 1, &mut raw).await?;
+        Ok(DisplayStatus::from_bytes(raw))
+    }
+
+    /// Reads the self-diagnostic result (RDDSDR).
+    ///
+    /// The checks run when the controller leaves sleep mode, read the result
+    /// after `init`.
+    pub async fn read_self_diagnostic(&mut self) -> Result<SelfDiagnostic, Error<E>> {
+        let mut raw = [0];
+        self.read_command(Instruction::RDDSDR, 0, &mut raw).await?;
+        Ok(SelfDiagnostic::from_byte(raw[0]))
+    }
+
+    async fn read_command(
+        &mut self,
+        instruction: Instruction,
+        dummy_bits: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Error<E>> {
+        let result = self
+            .di
+            .read_command(instruction as u8, dummy_bits, buf)
+            .await;
+        self.faulted |= result.is_err();
+        result.map_err(Error::from_interface::<DI>)
+    }
+}
@@ -0,0 +1,368 @@
+//! Receiver for pixel updates streamed from a host, e.g. over UART or USB CDC.
+//!
+//! Packets are framed as
+//!
+//! ```text
+//! 0xA5 0x5A, kind u8, payload length u16 LE, payload, CRC-16/CCITT-FALSE u16 LE
+//! ```
+//!
+//! with the CRC covering kind, length and payload. A `Window` packet sets the
+//! area the following pixel packets fill, so partial updates only send the
+//! changed area. After a corrupted packet the receiver skips ahead to the next
+//! sync marker.
+use core::convert::Infallible;
+
+use embedded_graphics_core::pixelcolor::raw::RawU16;
+use embedded_hal::digital::OutputPin;
+
+use crate::{rle, Backlight, Error, Model, WriteOnlyInterface, ST7735IF};
+
+const SYNC: [u8; 2] = [0xA5, 0x5A];
+
+/// Async byte stream the receiver reads packets from.
+///
+/// Has the shape of `embedded_io_async::Read`, so implementing it for a UART
+/// or a USB CDC class only forwards `read`.
+#[allow(async_fn_in_trait)]
+pub trait ByteSource {
+    type Error;
+
+    /// Reads at least one byte into `buf` and returns the count, 0 at the end of the stream.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Kind of a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum PacketKind {
+    /// Sets the area to fill, payload is `x0, y0, x1, y1` as inclusive `u16` LE coordinates.
+    Window = 0x01,
+    /// Big endian `Rgb565` pixels continuing the current area, an even number of bytes.
+    Raw = 0x02,
+    /// Run length encoded pixels continuing the current area, packets as in `rle`
+    /// without image header and not split across payloads.
+    Rle = 0x03,
+}
+
+impl PacketKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(Self::Window),
+            0x02 => Some(Self::Raw),
+            0x03 => Some(Self::Rle),
+            _ => None,
+        }
+    }
+}
+
+/// Error of receiving a packet.
+///
+/// All errors but `Read`, `EndOfStream` and `Display` only drop the packet,
+/// the next `receive` continues with the next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RemoteError<E, R> {
+    /// Reading from the source failed.
+    Read(R),
+    /// The source ended.
+    EndOfStream,
+    /// The CRC of the packet did not match.
+    Crc,
+    /// The packet kind is unknown.
+    UnknownKind(u8),
+    /// The payload does not fit into the receive buffer or is malformed.
+    Malformed,
+    /// Writing to the display failed.
+    Display(Error<E>),
+}
+
+impl<E: core::fmt::Debug, R: core::fmt::Debug> core::fmt::Display for RemoteError<E, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "remote read error: {e:?}"),
+            Self::EndOfStream => f.write_str("remote stream ended"),
+            Self::Crc => f.write_str("remote packet CRC mismatch"),
+            Self::UnknownKind(kind) => write!(f, "unknown remote packet kind {kind:#04x}"),
+            Self::Malformed => f.write_str("malformed remote packet"),
+            Self::Display(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug, R: core::fmt::Debug> core::error::Error for RemoteError<E, R> {}
+
+/// Receives packets into a buffer of `N` bytes, the largest accepted payload.
+pub struct RemoteReceiver<const N: usize> {
+    buffer: [u8; N],
+}
+
+impl<const N: usize> Default for RemoteReceiver<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RemoteReceiver<N> {
+    /// Creates a receiver.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { buffer: [0; N] }
+    }
+
+    /// Receives one packet and applies it to the display, returning its kind.
+    pub async fn receive<S, DI, RST, BL, M, E>(
+        &mut self,
+        source: &mut S,
+        display: &mut ST7735IF<DI, RST, BL, M>,
+    ) -> Result<PacketKind, RemoteError<E, S::Error>>
+    where
+        S: ByteSource,
+        DI: WriteOnlyInterface<Error = E>,
+        RST: OutputPin<Error = Infallible>,
+        BL: Backlight,
+        M: Model,
+    {
+        wait_for_sync(source).await?;
+        let mut header = [0u8; 3];
+        read_exact(source, &mut header).await?;
+        let len = usize::from(u16::from_le_bytes([header[1], header[2]]));
+        if len > N {
+            // The length may be corrupted, so resync instead of skipping the payload.
+            return Err(RemoteError::Malformed);
+        }
+        read_exact(source, &mut self.buffer[..len]).await?;
+        let mut received = [0u8; 2];
+        read_exact(source, &mut received).await?;
+        let crc = crc16(crc16(0xFFFF, &header), &self.buffer[..len]);
+        if u16::from_le_bytes(received) != crc {
+            return Err(RemoteError::Crc);
+        }
+        let kind = PacketKind::from_u8(header[0]).ok_or(RemoteError::UnknownKind(header[0]))?;
+        let payload = &self.buffer[..len];
+        match kind {
+            PacketKind::Window => {
+                let [x0, y0, x1, y1] = parse_window(payload).ok_or(RemoteError::Malformed)?;
+                display
+                    .start_window_write(x0, y0, x1, y1)
+                    .await
+                    .map_err(RemoteError::Display)?;
+            }
+            PacketKind::Raw => {
+                // A pixel split across packets would shift all following pixels by a byte.
+                if len % 2 != 0 {
+                    return Err(RemoteError::Malformed);
+                }
+                display
+                    .write_memory(payload)
+                    .await
+                    .map_err(RemoteError::Display)?;
+            }
+            PacketKind::Rle => {
+                let colors = rle::decode_packets(payload).map(|color| RawU16::new(color).into());
+                display
+                    .write_colors(colors)
+                    .await
+                    .map_err(RemoteError::Display)?;
+            }
+        }
+        Ok(kind)
+    }
+
+    /// Applies packets until the source fails or ends, skipping corrupted packets.
+    pub async fn run<S, DI, RST, BL, M, E>(
+        &mut self,
+        source: &mut S,
+        display: &mut ST7735IF<DI, RST, BL, M>,
+    ) -> RemoteError<E, S::Error>
+    where
+        S: ByteSource,
+        DI: WriteOnlyInterface<Error = E>,
+        RST: OutputPin<Error = Infallible>,
+        BL: Backlight,
+        M: Model,
+    {
+        loop {
+            match self.receive(source, display).await {
+                Ok(_)
+                | Err(RemoteError::Crc | RemoteError::UnknownKind(_) | RemoteError::Malformed) => {}
+                Err(e) => return e,
+            }
+        }
+    }
+}
+
+fn parse_window(payload: &[u8]) -> Option<[u16; 4]> {
+    let bytes: &[u8; 8] = payload.try_into().ok()?;
+    let mut coords = [0u16; 4];
+    for (coord, bytes) in coords.iter_mut().zip(bytes.chunks_exact(2)) {
+        *coord = u16::from_le_bytes([bytes[0], bytes[1]]);
+    }
+    let [x0, y0, x1, y1] = coords;
+    (x0 <= x1 && y0 <= y1).then_some(coords)
+}
+
+/// Skips bytes until the sync marker.
+async fn wait_for_sync<S: ByteSource, E>(source: &mut S) -> Result<(), RemoteError<E, S::Error>> {
+    let mut previous = 0;
+    loop {
+        let mut byte = [0u8];
+        read_exact(source, &mut byte).await?;
+        if [previous, byte[0]] == SYNC {
+            return Ok(());
+        }
+        previous = byte[0];
+    }
+}
+
+async fn read_exact<S: ByteSource, E>(
+    source: &mut S,
+    mut buf: &mut [u8],
+) -> Result<(), RemoteError<E, S::Error>> {
+    while !buf.is_empty() {
+        match source.read(buf).await.map_err(RemoteError::Read)? {
+            0 => return Err(RemoteError::EndOfStream),
+            n => buf = &mut buf[n..],
+        }
+    }
+    Ok(())
+}
+
+/// CRC-16/CCITT-FALSE, start with `0xFFFF`.
+fn crc16(mut crc: u16, data: &[u8]) -> u16 {
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Frames a packet for sending from the host, requires the `std` feature.
+///
+/// # Panics
+///
+/// Panics if the payload is longer than `u16::MAX` bytes.
+#[cfg(any(test, feature = "std"))]
+#[must_use]
+pub fn encode_packet(kind: PacketKind, payload: &[u8]) -> std::vec::Vec<u8> {
+    let len = u16::try_from(payload.len()).expect("payload too long");
+    let mut packet = std::vec::Vec::with_capacity(payload.len() + 7);
+    packet.extend_from_slice(&SYNC);
+    packet.push(kind as u8);
+    packet.extend_from_slice(&len.to_le_bytes());
+    packet.extend_from_slice(payload);
+    let crc = crc16(0xFFFF, &packet[2..]);
+    packet.extend_from_slice(&crc.to_le_bytes());
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::{Recorder, RecordingPin, RecordingSpi};
+    use crate::{Config, SpiInterface};
+
+    type Display<'a> = ST7735IF<SpiInterface<RecordingSpi<'a>, RecordingPin<'a>>, RecordingPin<'a>>;
+    type Result<T> = core::result::Result<T, RemoteError<Infallible, Infallible>>;
+
+    /// Byte source over a slice, handing out at most 3 bytes per read.
+    struct Bytes<'a>(&'a [u8]);
+
+    impl ByteSource for Bytes<'_> {
+        type Error = Infallible;
+
+        async fn read(&mut self, buf: &mut [u8]) -> core::result::Result<usize, Infallible> {
+            let len = buf.len().min(self.0.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn display(recorder: &Recorder) -> Display<'_> {
+        ST7735IF::new(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+        )
+    }
+
+    fn receive_all(stream: &[u8], recorder: &Recorder) -> Vec<Result<PacketKind>> {
+        let mut display = display(recorder);
+        let mut receiver = RemoteReceiver::<16>::new();
+        let mut source = Bytes(stream);
+        let mut results = Vec::new();
+        loop {
+            match block_on(receiver.receive(&mut source, &mut display)) {
+                Err(RemoteError::EndOfStream) => return results,
+                result => results.push(result),
+            }
+        }
+    }
+
+    fn window(x0: u16, y0: u16, x1: u16, y1: u16) -> Vec<u8> {
+        [x0, y0, x1, y1]
+            .iter()
+            .flat_map(|c| c.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn crc_known_answer() {
+        assert_eq!(crc16(0xFFFF, b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn encoded_packets_round_trip() {
+        let mut stream = encode_packet(PacketKind::Window, &window(1, 2, 2, 2));
+        stream.extend(encode_packet(PacketKind::Raw, &[0xF8, 0x00, 0x07, 0xE0]));
+        let recorder = Recorder::new();
+        let results = receive_all(&stream, &recorder);
+        assert_eq!(results, [Ok(PacketKind::Window), Ok(PacketKind::Raw)]);
+        assert_eq!(
+            recorder.commands("DC"),
+            [
+                (0x2A, vec![0, 1, 0, 2]),
+                (0x2B, vec![0, 2, 0, 2]),
+                (0x2C, vec![0xF8, 0x00, 0x07, 0xE0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn resyncs_after_corruption() {
+        let mut corrupted = encode_packet(PacketKind::Raw, &[0x12, 0x34]);
+        corrupted[5] ^= 0x01;
+        let mut stream = vec![0x00, 0xA5];
+        stream.extend(corrupted);
+        stream.extend([0x5A, 0xA5]);
+        stream.extend(encode_packet(PacketKind::Window, &window(0, 0, 0, 0)));
+        let recorder = Recorder::new();
+        let results = receive_all(&stream, &recorder);
+        assert_eq!(results, [Err(RemoteError::Crc), Ok(PacketKind::Window)]);
+    }
+
+    #[test]
+    fn odd_raw_payload_is_malformed() {
+        let mut stream = encode_packet(PacketKind::Window, &window(0, 0, 1, 0));
+        stream.extend(encode_packet(PacketKind::Raw, &[0xF8, 0x00, 0x07]));
+        let recorder = Recorder::new();
+        let results = receive_all(&stream, &recorder);
+        assert_eq!(
+            results,
+            [Ok(PacketKind::Window), Err(RemoteError::Malformed)]
+        );
+        assert_eq!(recorder.commands("DC").pop(), Some((0x2C, Vec::new())));
+    }
+}
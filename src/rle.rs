@@ -0,0 +1,266 @@
+//! Run length encoded `Rgb565` images.
+//!
+//! An image is a little endian `u16` width and height followed by packets.
+//! A packet starts with a control byte `c`: if its top bit is set, the next
+//! big endian `Rgb565` pixel is repeated `(c & 0x7F) + 1` times, otherwise
+//! `c + 1` literal big endian pixels follow. Packets run on across rows.
+//! Images are created on the host with `encode`, which requires the `std`
+//! feature.
+use core::convert::Infallible;
+
+use embedded_graphics_core::{pixelcolor::raw::RawU16, prelude::*};
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+const HEADER_LEN: usize = 4;
+const RUN: u8 = 0x80;
+
+/// Run length encoded image, e.g. from `include_bytes!`.
+#[derive(Clone, Copy, Debug)]
+pub struct RleImage<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+}
+
+impl<'a> RleImage<'a> {
+    /// Reads the image header, `None` if the data is too short.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let header = data.get(..HEADER_LEN)?;
+        Some(Self {
+            data: &data[HEADER_LEN..],
+            width: u16::from_le_bytes([header[0], header[1]]),
+            height: u16::from_le_bytes([header[2], header[3]]),
+        })
+    }
+
+    /// Returns the image size.
+    #[must_use]
+    pub fn size(&self) -> Size {
+        Size::new(u32::from(self.width), u32::from(self.height))
+    }
+
+    /// Decodes the pixels row by row as native `Rgb565` words.
+    ///
+    /// Stops early if the data is truncated.
+    #[must_use]
+    pub fn pixels(&self) -> RlePixels<'a> {
+        RlePixels {
+            data: self.data,
+            run: None,
+            literal: 0,
+            remaining: usize::from(self.width) * usize::from(self.height),
+        }
+    }
+
+    /// Draws the image into a frame with its top left corner at `pos`, clipped to the frame.
+    pub fn draw<const N: usize>(&self, frame: &mut Frame<N>, pos: Point) {
+        let width = usize::from(self.width.max(1));
+        for (i, color) in self.pixels().enumerate() {
+            let x = pos.x + (i % width) as i32;
+            let y = pos.y + (i / width) as i32;
+            if let (Ok(x), Ok(y)) = (u16::try_from(x), u16::try_from(y)) {
+                frame.set_pixel(x, y, RawU16::new(color).into());
+            }
+        }
+    }
+}
+
+/// Decodes bare packets without image header until the data ends.
+pub(crate) fn decode_packets(data: &[u8]) -> RlePixels<'_> {
+    RlePixels {
+        data,
+        run: None,
+        literal: 0,
+        remaining: usize::MAX,
+    }
+}
+
+/// Iterator over the pixels of an `RleImage`.
+pub struct RlePixels<'a> {
+    data: &'a [u8],
+    /// Color and remaining count of the current run.
+    run: Option<(u16, u8)>,
+    /// Remaining pixels of the current literal packet.
+    literal: usize,
+    remaining: usize,
+}
+
+impl RlePixels<'_> {
+    fn next_word(&mut self) -> Option<u16> {
+        let (bytes, rest) = self.data.split_first_chunk::<2>()?;
+        self.data = rest;
+        Some(u16::from_be_bytes(*bytes))
+    }
+}
+
+impl Iterator for RlePixels<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.run.is_none() && self.literal == 0 {
+            let (&control, rest) = self.data.split_first()?;
+            self.data = rest;
+            if control & RUN != 0 {
+                self.run = Some((self.next_word()?, (control & !RUN) + 1));
+            } else {
+                self.literal = usize::from(control) + 1;
+            }
+        }
+        let color = match &mut self.run {
+            Some((color, count)) => {
+                let color = *color;
+                *count -= 1;
+                if *count == 0 {
+                    self.run = None;
+                }
+                color
+            }
+            None => {
+                self.literal -= 1;
+                self.next_word()?
+            }
+        };
+        self.remaining -= 1;
+        Some(color)
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Decodes a run length encoded image straight to display RAM at `(x, y)`.
+    ///
+    /// Pixels are decoded in small chunks, no framebuffer is needed. The image
+    /// must lie within the display, it is not clipped. Truncated data leaves
+    /// the rest of the area unchanged.
+    pub async fn draw_rle(&mut self, x: u16, y: u16, data: &[u8]) -> Result<(), Error<E>> {
+        let Some(image) = RleImage::new(data) else {
+            return Ok(());
+        };
+        if image.width == 0 || image.height == 0 {
+            return Ok(());
+        }
+        self.start_window_write(x, y, x + image.width - 1, y + image.height - 1)
+            .await?;
+        self.write_colors(image.pixels().map(|color| RawU16::new(color).into()))
+            .await
+    }
+}
+
+/// Copies a run length encoded image into a framebuffer of `size` at `(x, y)`, clipped to it.
+#[cfg(feature = "buffered")]
+pub(crate) fn blit_rle(buffer: &mut [u16], size: (u16, u16), pos: (u16, u16), data: &[u8]) {
+    if let Some(image) = RleImage::new(data) {
+        crate::blit_pixels(buffer, size, pos, image.width, image.pixels());
+    }
+}
+
+/// Run length encodes a `width` x `height` big endian `Rgb565` image.
+///
+/// # Panics
+///
+/// Panics if `pixels` is shorter than `width * height` pixels.
+#[cfg(any(test, feature = "std"))]
+#[must_use]
+pub fn encode(width: u16, height: u16, pixels: &[u8]) -> std::vec::Vec<u8> {
+    /// Longest run or literal packet.
+    const MAX_PACKET: usize = 128;
+
+    let len = usize::from(width) * usize::from(height);
+    let words: std::vec::Vec<&[u8]> = pixels[..len * 2].chunks_exact(2).collect();
+    let mut out = std::vec::Vec::new();
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+
+    let run_length = |start: usize| {
+        words[start..]
+            .iter()
+            .take(MAX_PACKET)
+            .take_while(|&&word| word == words[start])
+            .count()
+    };
+    let mut i = 0;
+    while i < len {
+        let run = run_length(i);
+        if run >= 2 {
+            out.push(RUN | (run - 1) as u8);
+            out.extend_from_slice(words[i]);
+            i += run;
+            continue;
+        }
+        // Collect literals up to the next run of at least two pixels.
+        let start = i;
+        while i < len && i - start < MAX_PACKET && run_length(i) < 2 {
+            i += 1;
+        }
+        out.push((i - start - 1) as u8);
+        for word in &words[start..i] {
+            out.extend_from_slice(word);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn bytes(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn encoded_images_round_trip() {
+        let mut words = vec![0xF800; 300];
+        words.extend([1, 2, 3, 3, 4]);
+        words.extend(0..200);
+        let data = encode(101, 5, &bytes(&words));
+        let image = RleImage::new(&data).unwrap();
+        assert_eq!(image.size(), Size::new(101, 5));
+        assert_eq!(image.pixels().collect::<Vec<_>>(), words);
+    }
+
+    #[test]
+    fn packets_known_answer() {
+        let data = encode(3, 2, &bytes(&[7, 7, 7, 1, 2, 2]));
+        assert_eq!(
+            data,
+            [
+                3, 0, 2, 0, // 3 x 2
+                0x82, 0x00, 0x07, // 3 x 7
+                0x00, 0x00, 0x01, // literal 1
+                0x81, 0x00, 0x02, // 2 x 2
+            ]
+        );
+        // Runs longer than a packet are split.
+        let data = encode(130, 1, &bytes(&[5; 130]));
+        assert_eq!(data[4..], [0xFF, 0x00, 0x05, 0x81, 0x00, 0x05]);
+    }
+
+    #[test]
+    fn truncated_data_stops_early() {
+        assert!(RleImage::new(&[1, 0, 1]).is_none());
+        let data = encode(4, 1, &bytes(&[1, 2, 3, 4]));
+        for len in 4..data.len() {
+            let image = RleImage::new(&data[..len]).unwrap();
+            let decoded = image.pixels().count();
+            assert!(decoded < 4, "{len} bytes decoded {decoded} pixels");
+        }
+        // A run longer than the image ends with the image.
+        let image = RleImage::new(&[2, 0, 1, 0, 0xFF, 0x12, 0x34]).unwrap();
+        assert_eq!(image.pixels().collect::<Vec<_>>(), [0x1234, 0x1234]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Display shared between tasks, possibly on different executors.
+//!
+//! ```ignore
+//! use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+//!
+//! static DISPLAY: StaticCell<SharedDisplay<CriticalSectionRawMutex, Display>> = StaticCell::new();
+//! let display = &*DISPLAY.init(SharedDisplay::new(st7735));
+//! spawner.spawn(ui_task(display))?;
+//! interrupt_spawner.spawn(alert_task(display))?;
+//!
+//! // in a task
+//! display.draw(|d| banner.draw(d)).await?;
+//! display.flush().await?;
+//! ```
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::{Mutex, MutexGuard};
+
+/// Driver behind an `embassy_sync::mutex::Mutex`.
+///
+/// The lock is held across awaits, e.g. for a flush, and released when the
+/// guard is dropped. `M` picks the sharing: `NoopRawMutex` for tasks of one
+/// executor, `CriticalSectionRawMutex` to share with interrupt executors or
+/// other cores.
+pub struct SharedDisplay<M: RawMutex, D> {
+    display: Mutex<M, D>,
+}
+
+/// Exclusive access to a `SharedDisplay`, unlocks when dropped.
+pub type SharedDisplayGuard<'a, M, D> = MutexGuard<'a, M, D>;
+
+impl<M: RawMutex, D> SharedDisplay<M, D> {
+    #[must_use]
+    pub const fn new(display: D) -> Self {
+        Self {
+            display: Mutex::new(display),
+        }
+    }
+
+    /// Waits until the display is free and locks it.
+    pub async fn lock(&self) -> SharedDisplayGuard<'_, M, D> {
+        self.display.lock().await
+    }
+
+    /// Locks the display if it is free.
+    pub fn try_lock(&self) -> Option<SharedDisplayGuard<'_, M, D>> {
+        self.display.try_lock().ok()
+    }
+
+    /// Locks the display and runs `f` on it, e.g. to draw into its framebuffer.
+    pub async fn draw<R>(&self, f: impl FnOnce(&mut D) -> R) -> R {
+        let mut display = self.lock().await;
+        f(&mut display)
+    }
+
+    /// Locks the display and transfers its framebuffer.
+    #[cfg(feature = "buffered")]
+    pub async fn flush(&self) -> Result<(), D::FlushError>
+    where
+        D: crate::Tile,
+    {
+        self.lock().await.flush().await
+    }
+
+    /// Returns the driver.
+    pub fn into_inner(self) -> D {
+        self.display.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::block_on;
+    use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, NoopRawMutex};
+
+    use super::*;
+
+    fn assert_sync<T: Sync>(_: &T) {}
+
+    #[test]
+    fn lock_is_exclusive_until_the_guard_drops() {
+        let shared: SharedDisplay<NoopRawMutex, u32> = SharedDisplay::new(1);
+        let mut guard = shared.try_lock().unwrap();
+        *guard += 1;
+        assert!(shared.try_lock().is_none());
+        drop(guard);
+        assert_eq!(block_on(shared.draw(|d| *d)), 2);
+        assert_eq!(shared.into_inner(), 2);
+    }
+
+    #[test]
+    fn critical_section_mutex_is_sync() {
+        let shared: SharedDisplay<CriticalSectionRawMutex, u32> = SharedDisplay::new(0);
+        assert_sync(&shared);
+    }
+}
@@ -0,0 +1,60 @@
+//! `Frame` with its size in the type, checked against the buffer at compile time.
+use core::ops::{Deref, DerefMut};
+
+use crate::{buffer_size, Frame, Orientation};
+
+/// `WIDTH` x `HEIGHT` `Frame`, like the `ST7735` driver its buffer size `N`
+/// is checked at compile time.
+///
+/// Derefs to the `Frame`, e.g. for `flush_frame(&frame)` or drawing.
+pub struct SizedFrame<const WIDTH: u16, const HEIGHT: u16, const N: usize> {
+    frame: Frame<N>,
+}
+
+impl<const WIDTH: u16, const HEIGHT: u16, const N: usize> SizedFrame<WIDTH, HEIGHT, N> {
+    /// Static assertion that N is correct.
+    // MSRV: remove N when constant generic expressions are stabilized
+    // See <https://github.com/rust-lang/rust/issues/76560>
+    const CHECK_N: () = assert!(
+        N == buffer_size(WIDTH, HEIGHT),
+        "Invalid N: N must be equal to WIDTH x HEIGHT x 2!"
+    );
+
+    /// Creates a black frame, usable in `static` initializers.
+    #[must_use]
+    pub const fn new(orientation: Orientation) -> Self {
+        let () = Self::CHECK_N;
+        Self {
+            frame: Frame::new_black(WIDTH as u32, HEIGHT as u32, orientation),
+        }
+    }
+
+    /// Returns the plain `Frame`.
+    #[must_use]
+    pub fn into_frame(self) -> Frame<N> {
+        self.frame
+    }
+}
+
+impl<const WIDTH: u16, const HEIGHT: u16, const N: usize> Default for SizedFrame<WIDTH, HEIGHT, N> {
+    /// Black landscape frame, the orientation does not change the size.
+    fn default() -> Self {
+        Self::new(Orientation::Landscape)
+    }
+}
+
+impl<const WIDTH: u16, const HEIGHT: u16, const N: usize> Deref for SizedFrame<WIDTH, HEIGHT, N> {
+    type Target = Frame<N>;
+
+    fn deref(&self) -> &Frame<N> {
+        &self.frame
+    }
+}
+
+impl<const WIDTH: u16, const HEIGHT: u16, const N: usize> DerefMut
+    for SizedFrame<WIDTH, HEIGHT, N>
+{
+    fn deref_mut(&mut self) -> &mut Frame<N> {
+        &mut self.frame
+    }
+}
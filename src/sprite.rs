@@ -0,0 +1,208 @@
+//! Sprites cut from an `Rgb565` atlas image.
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawU16, Rgb565},
+    prelude::*,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Frame, Model, WriteOnlyInterface, ST7735IF};
+
+/// Pixels per write when streaming a flipped sprite row to the display.
+const ROW_CHUNK: usize = 64;
+
+/// Mirroring applied while drawing a sprite.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flip {
+    /// Mirror along the vertical axis.
+    pub horizontal: bool,
+    /// Mirror along the horizontal axis.
+    pub vertical: bool,
+}
+
+/// Atlas of equally sized cells in a big endian `Rgb565` image, e.g. from `include_bytes!`.
+///
+/// Cells are numbered row by row starting at the top left.
+#[derive(Clone, Copy, Debug)]
+pub struct SpriteSheet<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    cell_width: u16,
+    cell_height: u16,
+}
+
+impl<'a> SpriteSheet<'a> {
+    /// Creates a sprite sheet from an atlas `width` pixels wide.
+    ///
+    /// The height is derived from the data length, incomplete rows and cells are ignored.
+    #[must_use]
+    pub fn new(data: &'a [u8], width: u16, cell_width: u16, cell_height: u16) -> Self {
+        let height = (data.len() / (usize::from(width) * 2).max(1)).min(usize::from(u16::MAX));
+        Self {
+            data,
+            width,
+            height: height as u16,
+            cell_width: cell_width.max(1),
+            cell_height: cell_height.max(1),
+        }
+    }
+
+    /// Number of cells per row of the atlas.
+    #[must_use]
+    pub fn columns(&self) -> u16 {
+        self.width / self.cell_width
+    }
+
+    /// Number of complete cells in the atlas.
+    #[must_use]
+    pub fn cell_count(&self) -> usize {
+        usize::from(self.columns()) * usize::from(self.height / self.cell_height)
+    }
+
+    /// Returns the sprite of cell `index`, `None` if the atlas has no such cell.
+    #[must_use]
+    pub fn cell(&self, index: usize) -> Option<Sprite<'a>> {
+        if index >= self.cell_count() {
+            return None;
+        }
+        let columns = usize::from(self.columns());
+        Some(Sprite {
+            data: self.data,
+            stride: self.width,
+            x: (index % columns) as u16 * self.cell_width,
+            y: (index / columns) as u16 * self.cell_height,
+            width: self.cell_width,
+            height: self.cell_height,
+        })
+    }
+}
+
+/// Rectangular area of an atlas image.
+#[derive(Clone, Copy, Debug)]
+pub struct Sprite<'a> {
+    data: &'a [u8],
+    /// Atlas width in pixels
+    stride: u16,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+impl<'a> Sprite<'a> {
+    /// Uses a whole big endian `Rgb565` image of `width` x `height` pixels as a sprite.
+    #[must_use]
+    pub fn new(data: &'a [u8], width: u16, height: u16) -> Self {
+        Self {
+            data,
+            stride: width,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the sprite size.
+    #[must_use]
+    pub fn size(&self) -> Size {
+        Size::new(u32::from(self.width), u32::from(self.height))
+    }
+
+    /// Raw bytes of a row of the sprite.
+    fn row(&self, row: u16) -> &'a [u8] {
+        let start =
+            (usize::from(self.y + row) * usize::from(self.stride) + usize::from(self.x)) * 2;
+        let end = (start + usize::from(self.width) * 2).min(self.data.len());
+        self.data.get(start..end).unwrap_or(&[])
+    }
+
+    /// Draws the sprite into a frame with its top left corner at `pos`.
+    ///
+    /// The sprite is clipped to the frame. Pixels of the `key` color, if any, are transparent.
+    pub fn draw<const N: usize>(
+        &self,
+        frame: &mut Frame<N>,
+        pos: Point,
+        flip: Flip,
+        key: Option<Rgb565>,
+    ) {
+        let key = key.map(|key| RawU16::from(key).into_inner());
+        for row in 0..self.height {
+            let y = if flip.vertical {
+                self.height - 1 - row
+            } else {
+                row
+            };
+            let Ok(dest_y) = u16::try_from(pos.y + i32::from(y)) else {
+                continue;
+            };
+            for (col, bytes) in self.row(row).chunks_exact(2).enumerate() {
+                let col = col as u16;
+                let x = if flip.horizontal {
+                    self.width - 1 - col
+                } else {
+                    col
+                };
+                let Ok(dest_x) = u16::try_from(pos.x + i32::from(x)) else {
+                    continue;
+                };
+                let color = u16::from_be_bytes([bytes[0], bytes[1]]);
+                if key != Some(color) {
+                    frame.set_pixel(dest_x, dest_y, RawU16::new(color).into());
+                }
+            }
+        }
+    }
+}
+
+impl<DI, RST, BL, M, E> ST7735IF<DI, RST, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+{
+    /// Draws a sprite straight to display RAM with its top left corner at `(x, y)`.
+    ///
+    /// The sprite must lie within the display. Transparency is not possible
+    /// here since display RAM is not read back.
+    pub async fn draw_sprite(
+        &mut self,
+        sprite: &Sprite<'_>,
+        x: u16,
+        y: u16,
+        flip: Flip,
+    ) -> Result<(), Error<E>> {
+        if sprite.width == 0 || sprite.height == 0 {
+            return Ok(());
+        }
+        self.start_window_write(x, y, x + sprite.width - 1, y + sprite.height - 1)
+            .await?;
+        for row in 0..sprite.height {
+            let row = if flip.vertical {
+                sprite.height - 1 - row
+            } else {
+                row
+            };
+            let data = sprite.row(row);
+            if !flip.horizontal {
+                self.write_data(data).await?;
+                continue;
+            }
+            let mut buf = [0u8; ROW_CHUNK * 2];
+            let mut pixels = data.chunks_exact(2).rev().peekable();
+            while pixels.peek().is_some() {
+                let mut len = 0;
+                for (dest, src) in buf.chunks_exact_mut(2).zip(pixels.by_ref().take(ROW_CHUNK)) {
+                    dest.copy_from_slice(src);
+                    len += 2;
+                }
+                self.write_data(&buf[..len]).await?;
+            }
+        }
+        Ok(())
+    }
+}
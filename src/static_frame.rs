@@ -0,0 +1,181 @@
+//! Frames and driver buffers placed in statics, so they are never built on the stack.
+//!
+//! A 160x128 frame is 40 KB, moving it through a local or `StaticCell::init`
+//! easily overflows the stack before it reaches the static. `StaticFrame` and
+//! `StaticBuffer` are zeroed in their initializer instead, like
+//! `static_cell::ConstStaticCell`:
+//!
+//! ```ignore
+//! let frame = st7735_embassy::static_frame!(160, 128, Orientation::Landscape);
+//! display.flush_frame(frame).await?;
+//!
+//! // In a RAM region reachable by DMA
+//! let frame = st7735_embassy::static_frame!(160, 128, Orientation::Landscape, ".dma_ram");
+//!
+//! // Buffer of the `ST7735` driver
+//! let buffer = st7735_embassy::static_buffer!(160, 128);
+//! let display = ST7735::new_with_buffer(spi, dc, rst, config, 160, 128, buffer);
+//! ```
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{Frame, Orientation};
+
+/// Value in a static, handed out once as `&'static mut`.
+struct TakeOnce<T> {
+    value: UnsafeCell<T>,
+    taken: AtomicBool,
+}
+
+impl<T> TakeOnce<T> {
+    const fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            taken: AtomicBool::new(false),
+        }
+    }
+
+    // The `taken` flag makes the mutable borrow unique.
+    #[allow(clippy::mut_from_ref)]
+    fn try_take(&'static self) -> Option<&'static mut T> {
+        if self.taken.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        // SAFETY: `taken` guarantees this is the only reference.
+        Some(unsafe { &mut *self.value.get() })
+    }
+}
+
+// SAFETY: the value is only reachable through the single `try_take`.
+unsafe impl<T: Send> Sync for TakeOnce<T> {}
+
+/// Black `Frame` in a static, handed out once as `&'static mut`.
+pub struct StaticFrame<const N: usize>(TakeOnce<Frame<N>>);
+
+impl<const N: usize> StaticFrame<N> {
+    /// Creates a black frame of `width` x `height`, to be used in a `static` initializer.
+    #[must_use]
+    pub const fn new(width: u32, height: u32, orientation: Orientation) -> Self {
+        Self(TakeOnce::new(Frame::new_black(width, height, orientation)))
+    }
+
+    /// Returns the frame, `None` if it was taken before.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_take(&'static self) -> Option<&'static mut Frame<N>> {
+        self.0.try_take()
+    }
+
+    /// Returns the frame.
+    ///
+    /// # Panics
+    ///
+    /// If the frame was taken before.
+    #[allow(clippy::mut_from_ref)]
+    pub fn take(&'static self) -> &'static mut Frame<N> {
+        self.try_take().expect("StaticFrame taken twice")
+    }
+}
+
+/// Black buffer of `N` native `Rgb565` pixels in a static, handed out once as
+/// `&'static mut`, e.g. for `ST7735::new_with_buffer`.
+pub struct StaticBuffer<const N: usize>(TakeOnce<[u16; N]>);
+
+impl<const N: usize> StaticBuffer<N> {
+    /// Creates a black buffer, to be used in a `static` initializer.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(TakeOnce::new([0; N]))
+    }
+
+    /// Returns the buffer, `None` if it was taken before.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_take(&'static self) -> Option<&'static mut [u16; N]> {
+        self.0.try_take()
+    }
+
+    /// Returns the buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer was taken before.
+    #[allow(clippy::mut_from_ref)]
+    pub fn take(&'static self) -> &'static mut [u16; N] {
+        self.try_take().expect("StaticBuffer taken twice")
+    }
+}
+
+impl<const N: usize> Default for StaticBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Places a black `width` x `height` `Frame` in a static and returns it as `&'static mut`.
+///
+/// An optional link section places the static in a specific RAM region, e.g.
+/// one reachable by DMA. Each expansion is its own static and panics if it
+/// runs twice, e.g. in a loop.
+#[macro_export]
+macro_rules! static_frame {
+    ($width:expr, $height:expr, $orientation:expr $(, $section:literal)? $(,)?) => {{
+        const N: usize = $crate::buffer_size($width, $height);
+        $(#[link_section = $section])?
+        static FRAME: $crate::StaticFrame<N> =
+            $crate::StaticFrame::new($width as u32, $height as u32, $orientation);
+        FRAME.take()
+    }};
+}
+
+/// Places a black buffer for a `width` x `height` `ST7735` in a static and
+/// returns it as `&'static mut [u16]`, see `ST7735::new_with_buffer`.
+///
+/// Takes an optional link section and panics if it runs twice like `static_frame!`.
+#[macro_export]
+macro_rules! static_buffer {
+    ($width:expr, $height:expr $(, $section:literal)? $(,)?) => {{
+        const N: usize = $crate::pixel_count($width, $height);
+        $(#[link_section = $section])?
+        static BUFFER: $crate::StaticBuffer<N> = $crate::StaticBuffer::new();
+        let buffer: &'static mut [u16] = BUFFER.take();
+        buffer
+    }};
+}
+
+#[cfg(all(test, feature = "buffered"))]
+mod tests {
+    use embassy_futures::block_on;
+    use embedded_graphics_core::pixelcolor::raw::RawU16;
+
+    use super::*;
+    use crate::mock::Recorder;
+    use crate::{Config, ST7735};
+
+    #[test]
+    fn buffered_driver_draws_into_a_static_buffer() {
+        let recorder = Recorder::new();
+        let buffer = crate::static_buffer!(16, 8, ".data.st7735_test");
+        buffer[3] = 0xFFFF;
+        let mut display = ST7735::new_with_buffer(
+            recorder.spi(),
+            recorder.pin("DC"),
+            recorder.pin("RST"),
+            Config::new(),
+            16,
+            8,
+            buffer,
+        );
+        assert_eq!(display.get_pixel(3, 0), Some(RawU16::new(0).into()));
+        display.set_pixel(1, 0, 0xF800);
+        block_on(display.flush()).unwrap();
+        let (_, data) = recorder.commands("DC").pop().unwrap();
+        assert_eq!(data.len(), 16 * 8 * 2);
+        assert_eq!(data[2..4], [0xF8, 0x00]);
+    }
+
+    #[test]
+    fn static_buffer_is_taken_once() {
+        static BUFFER: StaticBuffer<4> = StaticBuffer::new();
+        assert!(BUFFER.try_take().is_some());
+        assert!(BUFFER.try_take().is_none());
+    }
+}
@@ -0,0 +1,21 @@
+//! Synchronization sources for flush scheduling.
+use embedded_hal_async::digital::Wait;
+
+/// Source of sync events a flush can wait for, e.g. the tearing effect (TE)
+/// output of the display, an external frame sync or a camera VSYNC.
+#[allow(async_fn_in_trait)]
+pub trait SyncSignal {
+    type Error;
+
+    /// Waits for the next sync event.
+    async fn wait_for_sync(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Any async input pin is a sync source triggering on its rising edge.
+impl<T: Wait> SyncSignal for T {
+    type Error = T::Error;
+
+    async fn wait_for_sync(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_rising_edge().await
+    }
+}
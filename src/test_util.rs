@@ -0,0 +1,189 @@
+//! Assertions for display tests, requires the `std` feature.
+//!
+//! Mismatches are reported with a map of the compared region, `.` for
+//! matching and `X` for differing pixels, followed by the first differences.
+use std::fmt::Write;
+use std::string::String;
+
+use embedded_graphics_core::{pixelcolor::Rgb565, prelude::*, primitives::Rectangle};
+
+use crate::emulator::EmulatedPanel;
+use crate::Frame;
+
+/// Differences listed after the map.
+const LISTED_DIFFERENCES: usize = 8;
+
+/// Pixels a test can read back.
+pub trait ReadPixels {
+    /// Returns the color at `(x, y)`, `None` outside of the readable area.
+    fn read_pixel(&self, x: u16, y: u16) -> Option<Rgb565>;
+}
+
+impl<const N: usize> ReadPixels for Frame<N> {
+    fn read_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        self.get_pixel(x, y)
+    }
+}
+
+impl ReadPixels for EmulatedPanel {
+    fn read_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        self.pixel(x, y)
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST, BL, M, BUF, E> ReadPixels for crate::ST7735<DI, RST, BUF, BL, M>
+where
+    DI: crate::WriteOnlyInterface<Error = E>,
+    RST: embedded_hal::digital::OutputPin<Error = core::convert::Infallible>,
+    BL: crate::Backlight,
+    M: crate::Model,
+    BUF: core::borrow::BorrowMut<[u16]>,
+{
+    fn read_pixel(&self, x: u16, y: u16) -> Option<Rgb565> {
+        self.get_pixel(x, y)
+    }
+}
+
+/// Compares `region` of `actual` pixel by pixel, `expected` gets the absolute position.
+///
+/// Returns a rendered diff if any pixel differs or cannot be read.
+#[must_use]
+pub fn region_diff<S, F>(actual: &S, region: Rectangle, expected: F) -> Option<String>
+where
+    S: ReadPixels + ?Sized,
+    F: Fn(Point) -> Rgb565,
+{
+    let mut map = String::new();
+    let mut differences = std::vec::Vec::new();
+    let width = region.size.width as i32;
+    for (i, point) in region.points().enumerate() {
+        let want = expected(point);
+        let got = u16::try_from(point.x)
+            .ok()
+            .zip(u16::try_from(point.y).ok())
+            .and_then(|(x, y)| actual.read_pixel(x, y));
+        if got == Some(want) {
+            map.push('.');
+        } else {
+            map.push('X');
+            differences.push((point, got, want));
+        }
+        if (i as i32 + 1) % width == 0 {
+            map.push('\n');
+        }
+    }
+    if differences.is_empty() {
+        return None;
+    }
+    let mut diff = String::new();
+    writeln!(
+        diff,
+        "{} of {} pixels differ in {:?}:",
+        differences.len(),
+        region.size.width * region.size.height,
+        region
+    )
+    .ok();
+    diff.push_str(&map);
+    for (point, got, want) in differences.iter().take(LISTED_DIFFERENCES) {
+        writeln!(
+            diff,
+            "  ({}, {}): got {got:?}, expected {want:?}",
+            point.x, point.y
+        )
+        .ok();
+    }
+    if differences.len() > LISTED_DIFFERENCES {
+        writeln!(
+            diff,
+            "  ... and {} more",
+            differences.len() - LISTED_DIFFERENCES
+        )
+        .ok();
+    }
+    Some(diff)
+}
+
+/// Asserts that every pixel of `region` has the color `expected`.
+///
+/// # Panics
+///
+/// Panics with a rendered diff if a pixel differs.
+#[track_caller]
+pub fn assert_region_eq<S>(actual: &S, region: Rectangle, expected: Rgb565)
+where
+    S: ReadPixels + ?Sized,
+{
+    if let Some(diff) = region_diff(actual, region, |_| expected) {
+        panic!("{diff}");
+    }
+}
+
+/// Asserts that `region` of `actual` matches a golden image whose top left corner is at `region.top_left`.
+///
+/// # Panics
+///
+/// Panics with a rendered diff if a pixel differs.
+#[track_caller]
+pub fn assert_region_matches<S, G>(actual: &S, region: Rectangle, golden: &G)
+where
+    S: ReadPixels + ?Sized,
+    G: ReadPixels + ?Sized,
+{
+    let expected = |point: Point| {
+        let relative = point - region.top_left;
+        golden
+            .read_pixel(relative.x as u16, relative.y as u16)
+            .unwrap_or(Rgb565::BLACK)
+    };
+    if let Some(diff) = region_diff(actual, region, expected) {
+        panic!("{diff}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Orientation;
+
+    fn frame() -> Frame<{ crate::buffer_size(4, 3) }> {
+        let mut frame = Frame::new_black(4, 3, Orientation::Landscape);
+        frame.set_pixel(1, 1, Rgb565::RED);
+        frame
+    }
+
+    #[test]
+    fn region_diff_renders_mismatches() {
+        let region = Rectangle::new(Point::new(0, 0), Size::new(4, 3));
+        let diff = region_diff(&frame(), region, |_| Rgb565::BLACK).unwrap();
+        let expected = std::format!(
+            "1 of 12 pixels differ in {region:?}:\n....\n.X..\n....\n  (1, 1): got {:?}, expected {:?}\n",
+            Some(Rgb565::RED),
+            Rgb565::BLACK
+        );
+        assert_eq!(diff, expected);
+    }
+
+    #[test]
+    fn assert_region_matches_accepts_golden_image() {
+        let golden = frame();
+        let region = Rectangle::new(Point::new(0, 0), Size::new(4, 3));
+        assert_region_matches(&frame(), region, &golden);
+        assert_region_eq(
+            &frame(),
+            Rectangle::new(Point::new(2, 0), Size::new(2, 3)),
+            Rgb565::BLACK,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 4 pixels differ")]
+    fn assert_region_eq_panics_on_mismatch() {
+        assert_region_eq(
+            &frame(),
+            Rectangle::new(Point::new(0, 0), Size::new(2, 2)),
+            Rgb565::BLACK,
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Several panels side by side as one larger `DrawTarget`.
+//!
+//! ```ignore
+//! let mut wall = TiledDisplay::new([left, right]);
+//! Text::new("Platform 3", Point::new(120, 64), style).draw(&mut wall)?;
+//! wall.flush().await?;
+//! ```
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    draw_target::DrawTarget, pixelcolor::Rgb565, prelude::*, primitives::Rectangle,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{Backlight, Error, Model, WriteOnlyInterface};
+
+/// Buffered driver usable as a panel of a `TiledDisplay` or `DisplayGroup`.
+#[allow(async_fn_in_trait)]
+pub trait Tile: DrawTarget<Color = Rgb565> + OriginDimensions {
+    type FlushError;
+
+    /// Transfers the framebuffer to the panel.
+    async fn flush(&mut self) -> Result<(), Self::FlushError>;
+
+    /// Starts a transfer of the framebuffer continued by `flush_pixels`.
+    async fn begin_flush(&mut self) -> Result<(), Self::FlushError>;
+
+    /// Transfers up to `len` framebuffer pixels from `start` on, returns the number transferred.
+    async fn flush_pixels(&mut self, start: usize, len: usize) -> Result<usize, Self::FlushError>;
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST, BL, M, BUF, E> Tile for crate::ST7735<DI, RST, BUF, BL, M>
+where
+    DI: WriteOnlyInterface<Error = E>,
+    RST: OutputPin<Error = Infallible>,
+    BL: Backlight,
+    M: Model,
+    BUF: core::borrow::BorrowMut<[u16]>,
+{
+    type FlushError = Error<E>;
+
+    async fn flush(&mut self) -> Result<(), Self::FlushError> {
+        self.flush().await
+    }
+
+    async fn begin_flush(&mut self) -> Result<(), Self::FlushError> {
+        self.begin_flush().await
+    }
+
+    async fn flush_pixels(&mut self, start: usize, len: usize) -> Result<usize, Self::FlushError> {
+        self.flush_pixels(start, len).await
+    }
+}
+
+/// `T` panels placed left to right, drawn as one `DrawTarget`.
+///
+/// Pixels are routed to the panel covering their x coordinate. The display
+/// is as wide as all panels together and as high as the highest one, areas
+/// below lower panels are clipped.
+pub struct TiledDisplay<P, const T: usize> {
+    panels: [P; T],
+}
+
+impl<P: Tile, const T: usize> TiledDisplay<P, T> {
+    /// Combines `panels`, the first one is leftmost.
+    #[must_use]
+    pub fn new(panels: [P; T]) -> Self {
+        Self { panels }
+    }
+
+    /// Returns the panels, e.g. to initialize them.
+    pub fn panels_mut(&mut self) -> &mut [P; T] {
+        &mut self.panels
+    }
+
+    /// Returns the panels.
+    pub fn into_inner(self) -> [P; T] {
+        self.panels
+    }
+
+    /// Transfers the framebuffers to all panels, left to right.
+    pub async fn flush(&mut self) -> Result<(), P::FlushError> {
+        for panel in &mut self.panels {
+            panel.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the panels with the area each covers in display coordinates.
+    fn tiles_mut(&mut self) -> impl Iterator<Item = (Rectangle, &mut P)> {
+        let mut x = 0;
+        self.panels.iter_mut().map(move |panel| {
+            let area = Rectangle::new(Point::new(x, 0), panel.size());
+            x += panel.size().width as i32;
+            (area, panel)
+        })
+    }
+}
+
+impl<P: Tile, const T: usize> DrawTarget for TiledDisplay<P, T> {
+    type Error = P::Error;
+    type Color = Rgb565;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some((area, panel)) = self.tiles_mut().find(|(area, _)| area.contains(point)) {
+                panel.draw_iter(core::iter::once(Pixel(point - area.top_left, color)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        for (tile, panel) in self.tiles_mut() {
+            let part = area.intersection(&tile);
+            if !part.is_zero_sized() {
+                let local = Rectangle::new(part.top_left - tile.top_left, part.size);
+                panel.fill_solid(&local, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        for panel in &mut self.panels {
+            panel.clear(color)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: Tile, const T: usize> OriginDimensions for TiledDisplay<P, T> {
+    fn size(&self) -> Size {
+        let width = self.panels.iter().map(|panel| panel.size().width).sum();
+        let height = self
+            .panels
+            .iter()
+            .map(|panel| panel.size().height)
+            .max()
+            .unwrap_or(0);
+        Size::new(width, height)
+    }
+}
@@ -0,0 +1,135 @@
+//! Coordinate transforms between logical and panel-native coordinates.
+//!
+//! Logical coordinates are the ones used for drawing: `(0, 0)` is the top left
+//! corner as seen in the configured `Orientation`. Physical coordinates are the
+//! panel-native GRAM coordinates, i.e. the logical coordinates in
+//! `Orientation::Portrait` where no MADCTL bits are set.
+//!
+//! The controller first mirrors the column (MX) and row (MY) address counters
+//! and then exchanges rows and columns (MV).
+use crate::{Mirror, Orientation};
+
+const MY: u8 = 0x80;
+const MX: u8 = 0x40;
+const MV: u8 = 0x20;
+
+impl Orientation {
+    /// Whether rows and columns are exchanged relative to the panel-native scan.
+    #[must_use]
+    pub const fn is_landscape(self) -> bool {
+        self as u8 & MV != 0
+    }
+
+    /// Whether this is one of the mirrored orientations.
+    #[must_use]
+    pub const fn is_mirrored(self) -> bool {
+        matches!(
+            self,
+            Self::PortraitMirrored
+                | Self::LandscapeMirrored
+                | Self::PortraitSwappedMirrored
+                | Self::LandscapeSwappedMirrored
+        )
+    }
+
+    /// Returns the orientation scanning like the MY, MX and MV bits of `bits`.
+    #[must_use]
+    pub const fn from_scan_bits(bits: u8) -> Self {
+        match bits & (MY | MX | MV) {
+            0x00 => Self::Portrait,
+            0x60 => Self::Landscape,
+            0xC0 => Self::PortraitSwapped,
+            0xA0 => Self::LandscapeSwapped,
+            0x40 => Self::PortraitMirrored,
+            0x20 => Self::LandscapeMirrored,
+            0x80 => Self::PortraitSwappedMirrored,
+            _ => Self::LandscapeSwappedMirrored,
+        }
+    }
+}
+
+/// Returns the logical `(width, height)` of a panel with the given native size.
+#[must_use]
+pub const fn logical_size(orientation: Orientation, native: (u16, u16)) -> (u16, u16) {
+    if orientation.is_landscape() {
+        (native.1, native.0)
+    } else {
+        native
+    }
+}
+
+/// Returns the native `(width, height)` of a panel with the given logical size.
+#[must_use]
+pub const fn native_size(orientation: Orientation, logical: (u16, u16)) -> (u16, u16) {
+    // Exchanging rows and columns is its own inverse.
+    logical_size(orientation, logical)
+}
+
+/// Returns the MADCTL scan bits for an orientation with output mirroring applied.
+#[must_use]
+pub const fn madctl(orientation: Orientation, mirror: Mirror) -> u8 {
+    orientation as u8 ^ mirror.bits()
+}
+
+/// Maps a logical point to physical coordinates.
+///
+/// `size` is the logical `(width, height)`, points outside of it are not checked.
+#[must_use]
+pub const fn logical_to_physical(
+    orientation: Orientation,
+    size: (u16, u16),
+    point: (u16, u16),
+) -> (u16, u16) {
+    logical_to_physical_bits(orientation as u8, size, point)
+}
+
+/// Maps a physical point to logical coordinates.
+///
+/// `size` is the logical `(width, height)`, points outside of it are not checked.
+#[must_use]
+pub const fn physical_to_logical(
+    orientation: Orientation,
+    size: (u16, u16),
+    point: (u16, u16),
+) -> (u16, u16) {
+    physical_to_logical_bits(orientation as u8, size, point)
+}
+
+/// Maps a logical point to physical coordinates for raw MADCTL scan bits.
+#[must_use]
+pub const fn logical_to_physical_bits(bits: u8, size: (u16, u16), point: (u16, u16)) -> (u16, u16) {
+    let x = if bits & MX != 0 {
+        size.0 - 1 - point.0
+    } else {
+        point.0
+    };
+    let y = if bits & MY != 0 {
+        size.1 - 1 - point.1
+    } else {
+        point.1
+    };
+    if bits & MV != 0 {
+        (y, x)
+    } else {
+        (x, y)
+    }
+}
+
+/// Maps a physical point to logical coordinates for raw MADCTL scan bits.
+#[must_use]
+pub const fn physical_to_logical_bits(bits: u8, size: (u16, u16), point: (u16, u16)) -> (u16, u16) {
+    let (x, y) = if bits & MV != 0 {
+        (point.1, point.0)
+    } else {
+        point
+    };
+    let x = if bits & MX != 0 { size.0 - 1 - x } else { x };
+    let y = if bits & MY != 0 { size.1 - 1 - y } else { y };
+    (x, y)
+}
+
+/// Maps a logical point to the address sent in CASET/RASET, applying the global offset.
+#[must_use]
+pub const fn window_address(offset: (u16, u16), point: (u16, u16)) -> (u16, u16) {
+    (point.0 + offset.0, point.1 + offset.1)
+}
@@ -0,0 +1,254 @@
+use core::fmt;
+
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+/// Tab stops every this many columns.
+const TAB_WIDTH: usize = 4;
+
+#[derive(Clone, Copy)]
+struct Cell {
+    ch: char,
+    fg: Rgb565,
+    bg: Rgb565,
+}
+
+/// Text console with a `COLS` x `ROWS` character grid, written through `core::fmt::Write`.
+///
+/// Text wraps at the last column and the console scrolls up when the cursor
+/// moves past the last row. `\n` starts a new line, `\r` returns to the first
+/// column, `\t` advances to the next tab stop and backspace moves the cursor
+/// left. Characters missing from the font are drawn by the font's fallback.
+///
+/// Only rows changed since the last draw are redrawn, `draw` returns the area
+/// it touched so just that region needs to be flushed.
+pub struct Console<const COLS: usize, const ROWS: usize> {
+    cells: [[Cell; COLS]; ROWS],
+    dirty: [bool; ROWS],
+    origin: Point,
+    font: &'static MonoFont<'static>,
+    fg: Rgb565,
+    bg: Rgb565,
+    col: usize,
+    row: usize,
+    cursor_visible: bool,
+}
+
+impl<const COLS: usize, const ROWS: usize> Console<COLS, ROWS> {
+    /// Creates an empty console with its top left corner at `origin`.
+    #[must_use]
+    pub fn new(origin: Point, font: &'static MonoFont<'static>, fg: Rgb565, bg: Rgb565) -> Self {
+        let blank = Cell { ch: ' ', fg, bg };
+        Self {
+            cells: [[blank; COLS]; ROWS],
+            dirty: [true; ROWS],
+            origin,
+            font,
+            fg,
+            bg,
+            col: 0,
+            row: 0,
+            cursor_visible: false,
+        }
+    }
+
+    /// Sets the colors used for following text.
+    pub fn set_colors(&mut self, fg: Rgb565, bg: Rgb565) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Returns the colors used for following text as `(foreground, background)`.
+    #[must_use]
+    pub fn colors(&self) -> (Rgb565, Rgb565) {
+        (self.fg, self.bg)
+    }
+
+    /// Shows the cursor as an inverted cell.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+        self.dirty[self.row] = true;
+    }
+
+    /// Moves the cursor, coordinates are clamped to the grid.
+    pub fn set_cursor(&mut self, col: usize, row: usize) {
+        self.dirty[self.row] = true;
+        self.col = col.min(COLS.saturating_sub(1));
+        self.row = row.min(ROWS.saturating_sub(1));
+        self.dirty[self.row] = true;
+    }
+
+    /// Returns the cursor position as `(column, row)`.
+    #[must_use]
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.col, self.row)
+    }
+
+    /// Clears the grid with the current background color and homes the cursor.
+    pub fn clear(&mut self) {
+        for row in 0..ROWS {
+            self.clear_cells(row, 0..COLS);
+        }
+        self.set_cursor(0, 0);
+    }
+
+    /// Clears from the cursor to the end of its row.
+    pub fn clear_to_end_of_line(&mut self) {
+        self.clear_cells(self.row, self.col..COLS);
+    }
+
+    /// Clears from the cursor to the end of the grid.
+    pub fn clear_to_end(&mut self) {
+        self.clear_to_end_of_line();
+        for row in self.row + 1..ROWS {
+            self.clear_cells(row, 0..COLS);
+        }
+    }
+
+    /// Writes a character at the cursor, interpreting control characters.
+    pub fn put_char(&mut self, c: char) {
+        if COLS == 0 || ROWS == 0 {
+            return;
+        }
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.set_cursor(0, self.row),
+            '\t' => {
+                let col = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.set_cursor(col, self.row);
+            }
+            '\x08' => self.set_cursor(self.col.saturating_sub(1), self.row),
+            c if c.is_control() => {}
+            c => {
+                if self.col >= COLS {
+                    self.new_line();
+                }
+                self.cells[self.row][self.col] = Cell {
+                    ch: c,
+                    fg: self.fg,
+                    bg: self.bg,
+                };
+                self.dirty[self.row] = true;
+                // The cursor may sit one past the last column until the next character wraps.
+                self.col += 1;
+            }
+        }
+    }
+
+    /// Area covered by the console.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        let char_size = self.char_size();
+        Rectangle::new(
+            self.origin,
+            Size::new(
+                char_size.width * COLS as u32,
+                char_size.height * ROWS as u32,
+            ),
+        )
+    }
+
+    /// Area of the rows changed since the last draw, `None` if nothing changed.
+    #[must_use]
+    pub fn dirty_region(&self) -> Option<Rectangle> {
+        let first = self.dirty.iter().position(|&dirty| dirty)?;
+        let last = self.dirty.iter().rposition(|&dirty| dirty)?;
+        Some(self.row_area(first, last - first + 1))
+    }
+
+    /// Draws the rows changed since the last draw and returns their area.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let region = self.dirty_region();
+        let char_size = self.char_size();
+        for row in 0..ROWS {
+            if !self.dirty[row] {
+                continue;
+            }
+            for (col, cell) in self.cells[row].iter().enumerate() {
+                let (fg, bg) = if self.cursor_visible && (col, row) == (self.col, self.row) {
+                    (cell.bg, cell.fg)
+                } else {
+                    (cell.fg, cell.bg)
+                };
+                let style = MonoTextStyleBuilder::new()
+                    .font(self.font)
+                    .text_color(fg)
+                    .background_color(bg)
+                    .build();
+                let position = self.origin
+                    + Point::new(
+                        (char_size.width * col as u32) as i32,
+                        (char_size.height * row as u32) as i32,
+                    );
+                let mut buf = [0; 4];
+                Text::with_baseline(
+                    cell.ch.encode_utf8(&mut buf),
+                    position,
+                    style,
+                    Baseline::Top,
+                )
+                .draw(target)?;
+            }
+            self.dirty[row] = false;
+        }
+        Ok(region)
+    }
+
+    fn new_line(&mut self) {
+        self.dirty[self.row] = true;
+        self.col = 0;
+        if self.row + 1 < ROWS {
+            self.row += 1;
+        } else {
+            self.cells.rotate_left(1);
+            self.clear_cells(ROWS - 1, 0..COLS);
+            self.dirty = [true; ROWS];
+        }
+        self.dirty[self.row] = true;
+    }
+
+    fn clear_cells(&mut self, row: usize, cols: core::ops::Range<usize>) {
+        let blank = Cell {
+            ch: ' ',
+            fg: self.fg,
+            bg: self.bg,
+        };
+        if let Some(cells) = self.cells[row].get_mut(cols) {
+            cells.fill(blank);
+        }
+        self.dirty[row] = true;
+    }
+
+    fn row_area(&self, first: usize, rows: usize) -> Rectangle {
+        let char_size = self.char_size();
+        Rectangle::new(
+            self.origin + Point::new(0, (char_size.height * first as u32) as i32),
+            Size::new(
+                char_size.width * COLS as u32,
+                char_size.height * rows as u32,
+            ),
+        )
+    }
+
+    fn char_size(&self) -> Size {
+        Size::new(
+            self.font.character_size.width + self.font.character_spacing,
+            self.font.character_size.height,
+        )
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> fmt::Write for Console<COLS, ROWS> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().for_each(|c| self.put_char(c));
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::{Deque, String};
+
+/// Width of the `[sssss.mmm] ` timestamp prefix in characters.
+const TIMESTAMP_CHARS: u32 = 12;
+
+struct Line<const COLS: usize> {
+    timestamp_ms: u64,
+    text: String<COLS>,
+}
+
+/// On-device log console showing the most recent `LINES` lines with timestamps.
+///
+/// Lines are truncated to `COLS` bytes. Feed it from wherever log lines arrive,
+/// e.g. a loop receiving from a channel, and call `draw` to render only the
+/// lines appended since the last draw. Once the ring is full every push
+/// scrolls all rows, and the next draw redraws the whole view.
+pub struct LogView<const LINES: usize, const COLS: usize> {
+    lines: Deque<Line<COLS>, LINES>,
+    origin: Point,
+    style: MonoTextStyle<'static, Rgb565>,
+    background: Rgb565,
+    /// Lines appended since the last draw.
+    pending: usize,
+    /// Whether rows moved up since the last draw.
+    scrolled: bool,
+}
+
+impl<const LINES: usize, const COLS: usize> LogView<LINES, COLS> {
+    /// Creates an empty log view with its top left corner at `origin`.
+    #[must_use]
+    pub fn new(origin: Point, style: MonoTextStyle<'static, Rgb565>, background: Rgb565) -> Self {
+        Self {
+            lines: Deque::new(),
+            origin,
+            style,
+            background,
+            pending: 0,
+            scrolled: false,
+        }
+    }
+
+    /// Appends a line, dropping the oldest one when the view is full.
+    pub fn push(&mut self, timestamp_ms: u64, text: &str) {
+        let mut line = Line {
+            timestamp_ms,
+            text: String::new(),
+        };
+        for c in text.trim_end_matches(['\r', '\n']).chars() {
+            if line.text.push(c).is_err() {
+                break;
+            }
+        }
+        if self.lines.is_full() {
+            self.lines.pop_front();
+            self.scrolled = true;
+        }
+        self.lines.push_back(line).ok();
+        self.pending = (self.pending + 1).min(LINES);
+    }
+
+    /// Removes all lines, the next draw clears the view.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.pending = 0;
+        self.scrolled = true;
+    }
+
+    /// Area covered by the view.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        let char_size = self.char_size();
+        let columns = TIMESTAMP_CHARS + COLS as u32;
+        Rectangle::new(
+            self.origin,
+            Size::new(char_size.width * columns, char_size.height * LINES as u32),
+        )
+    }
+
+    /// Draws the lines appended since the last draw, or the whole view after scrolling.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let fill = PrimitiveStyle::with_fill(self.background);
+        let first = if self.scrolled {
+            self.bounding_box().into_styled(fill).draw(target)?;
+            0
+        } else {
+            self.lines.len() - self.pending
+        };
+        let char_size = self.char_size();
+        let row_size = Size::new(self.bounding_box().size.width, char_size.height);
+        for (row, line) in self.lines.iter().enumerate().skip(first) {
+            let top_left = self.origin + Point::new(0, (char_size.height * row as u32) as i32);
+            if !self.scrolled {
+                Rectangle::new(top_left, row_size)
+                    .into_styled(fill)
+                    .draw(target)?;
+            }
+            let mut timestamp: String<16> = String::new();
+            let secs = line.timestamp_ms / 1000;
+            let millis = line.timestamp_ms % 1000;
+            write!(timestamp, "[{secs:>5}.{millis:03}]").ok();
+            Text::with_baseline(&timestamp, top_left, self.style, Baseline::Top).draw(target)?;
+            let text_left = top_left + Point::new((char_size.width * TIMESTAMP_CHARS) as i32, 0);
+            Text::with_baseline(&line.text, text_left, self.style, Baseline::Top).draw(target)?;
+        }
+        self.pending = 0;
+        self.scrolled = false;
+        Ok(())
+    }
+
+    fn char_size(&self) -> Size {
+        let font = self.style.font;
+        Size::new(
+            font.character_size.width + font.character_spacing,
+            font.character_size.height,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+use embedded_graphics::{
+    draw_target::DrawTargetExt,
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::String;
+
+/// Gap between the end of the text and its next repetition, in characters.
+const GAP_CHARS: u32 = 3;
+
+/// Single line of text scrolling horizontally through `bounds`.
+///
+/// Text that fits into `bounds` is shown without scrolling. Drawing is
+/// clipped to `bounds` and only happens when the scroll position changed.
+pub struct Marquee<const LEN: usize> {
+    text: String<LEN>,
+    bounds: Rectangle,
+    style: MonoTextStyle<'static, Rgb565>,
+    background: Rgb565,
+    /// Scroll speed in pixels per second.
+    speed: u32,
+    start_ms: u64,
+    offset: u32,
+    /// Whether the strip has to be redrawn.
+    dirty: bool,
+}
+
+impl<const LEN: usize> Marquee<LEN> {
+    /// Creates a marquee scrolling at `speed` pixels per second, text is truncated to `LEN` bytes.
+    #[must_use]
+    pub fn new(
+        bounds: Rectangle,
+        text: &str,
+        style: MonoTextStyle<'static, Rgb565>,
+        background: Rgb565,
+        speed: u32,
+    ) -> Self {
+        let mut marquee = Self {
+            text: String::new(),
+            bounds,
+            style,
+            background,
+            speed,
+            start_ms: 0,
+            offset: 0,
+            dirty: true,
+        };
+        marquee.set_text(text);
+        marquee
+    }
+
+    /// Replaces the text and restarts scrolling at the next `update`.
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        for c in text.chars() {
+            if self.text.push(c).is_err() {
+                break;
+            }
+        }
+        self.offset = 0;
+        self.start_ms = 0;
+        self.dirty = true;
+    }
+
+    /// Advances the scroll position to the time `now_ms`, e.g. from a monotonic clock.
+    ///
+    /// The first update after creating the marquee or changing the text sets the start time.
+    pub fn update(&mut self, now_ms: u64) {
+        if self.start_ms == 0 {
+            self.start_ms = now_ms.max(1);
+        }
+        let period = self.period();
+        if period == 0 {
+            return;
+        }
+        let elapsed = now_ms.saturating_sub(self.start_ms);
+        let travelled = elapsed * u64::from(self.speed) / 1000;
+        let offset = (travelled % u64::from(period)) as u32;
+        if offset != self.offset {
+            self.offset = offset;
+            self.dirty = true;
+        }
+    }
+
+    /// Area covered by the marquee.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Draws the strip if the scroll position changed since the last draw.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut clipped = target.clipped(&self.bounds);
+        self.bounds
+            .into_styled(PrimitiveStyle::with_fill(self.background))
+            .draw(&mut clipped)?;
+        let left = self.bounds.top_left - Point::new(self.offset as i32, 0);
+        Text::with_baseline(&self.text, left, self.style, Baseline::Top).draw(&mut clipped)?;
+        let period = self.period();
+        if period > 0 {
+            let repeat = left + Point::new(period as i32, 0);
+            Text::with_baseline(&self.text, repeat, self.style, Baseline::Top)
+                .draw(&mut clipped)?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Scroll distance until the text repeats, 0 if the text fits.
+    fn period(&self) -> u32 {
+        let font = self.style.font;
+        let char_width = font.character_size.width + font.character_spacing;
+        let text_width = char_width * self.text.chars().count() as u32;
+        if text_width <= self.bounds.size.width {
+            0
+        } else {
+            text_width + char_width * GAP_CHARS
+        }
+    }
+}
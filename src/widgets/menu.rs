@@ -0,0 +1,149 @@
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+/// Scrollable list of items with a highlighted selection.
+///
+/// Navigation only marks rows as changed, `draw` then redraws the rows whose
+/// highlight changed, or every visible row when the list scrolled.
+pub struct Menu<'a> {
+    items: &'a [&'a str],
+    bounds: Rectangle,
+    font: &'static MonoFont<'static>,
+    text: Rgb565,
+    background: Rgb565,
+    highlight_text: Rgb565,
+    highlight: Rgb565,
+    selected: usize,
+    /// Index of the first visible item.
+    first: usize,
+    /// `(first, selected)` at the last draw, `None` if everything has to be drawn.
+    drawn: Option<(usize, usize)>,
+}
+
+impl<'a> Menu<'a> {
+    /// Creates a menu with the first item selected.
+    ///
+    /// The selected row is drawn with `highlight_text` on `highlight`, the
+    /// others with `text` on `background`.
+    #[must_use]
+    pub fn new(
+        items: &'a [&'a str],
+        bounds: Rectangle,
+        font: &'static MonoFont<'static>,
+        (text, background): (Rgb565, Rgb565),
+        (highlight_text, highlight): (Rgb565, Rgb565),
+    ) -> Self {
+        Self {
+            items,
+            bounds,
+            font,
+            text,
+            background,
+            highlight_text,
+            highlight,
+            selected: 0,
+            first: 0,
+            drawn: None,
+        }
+    }
+
+    /// Moves the selection up, stopping at the first item.
+    pub fn up(&mut self) {
+        self.set_selected(self.selected.saturating_sub(1));
+    }
+
+    /// Moves the selection down, stopping at the last item.
+    pub fn down(&mut self) {
+        self.set_selected(self.selected + 1);
+    }
+
+    /// Returns the index of the selected item, e.g. when the select button is pressed.
+    #[must_use]
+    pub fn select(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects an item, scrolling it into view.
+    pub fn set_selected(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+        let rows = self.visible_rows();
+        if self.selected < self.first {
+            self.first = self.selected;
+        } else if rows > 0 && self.selected >= self.first + rows {
+            self.first = self.selected + 1 - rows;
+        }
+    }
+
+    /// Replaces the items, the selection is kept if possible.
+    pub fn set_items(&mut self, items: &'a [&'a str]) {
+        self.items = items;
+        self.first = 0;
+        self.set_selected(self.selected);
+        self.drawn = None;
+    }
+
+    /// Area covered by the menu.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Draws the rows that changed since the last draw.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        match self.drawn {
+            Some((first, selected)) if first == self.first => {
+                if selected != self.selected {
+                    self.draw_item(target, selected)?;
+                    self.draw_item(target, self.selected)?;
+                }
+            }
+            _ => {
+                self.bounds
+                    .into_styled(PrimitiveStyle::with_fill(self.background))
+                    .draw(target)?;
+                let last = (self.first + self.visible_rows()).min(self.items.len());
+                for index in self.first..last {
+                    self.draw_item(target, index)?;
+                }
+            }
+        }
+        self.drawn = Some((self.first, self.selected));
+        Ok(())
+    }
+
+    fn draw_item<D>(&self, target: &mut D, index: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let Some(item) = self.items.get(index) else {
+            return Ok(());
+        };
+        let (text, background) = if index == self.selected {
+            (self.highlight_text, self.highlight)
+        } else {
+            (self.text, self.background)
+        };
+        let row_height = self.font.character_size.height;
+        let top_left =
+            self.bounds.top_left + Point::new(0, ((index - self.first) as u32 * row_height) as i32);
+        Rectangle::new(top_left, Size::new(self.bounds.size.width, row_height))
+            .into_styled(PrimitiveStyle::with_fill(background))
+            .draw(target)?;
+        let style = MonoTextStyle::new(self.font, text);
+        Text::with_baseline(item, top_left, style, Baseline::Top)
+            .draw(&mut target.clipped(&self.bounds))?;
+        Ok(())
+    }
+
+    fn visible_rows(&self) -> usize {
+        (self.bounds.size.height / self.font.character_size.height.max(1)) as usize
+    }
+}
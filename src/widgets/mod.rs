@@ -0,0 +1,19 @@
+//! Text and UI widgets, requires the `widgets` feature.
+//!
+//! Widgets draw into any `DrawTarget<Color = Rgb565>` and keep track of what
+//! changed since they were last drawn, so only those parts are redrawn.
+mod console;
+mod log_view;
+mod marquee;
+mod menu;
+mod plot;
+mod progress_bar;
+mod terminal;
+
+pub use console::Console;
+pub use log_view::LogView;
+pub use marquee::Marquee;
+pub use menu::Menu;
+pub use plot::{Plot, Scale};
+pub use progress_bar::ProgressBar;
+pub use terminal::Terminal;
@@ -0,0 +1,167 @@
+use embedded_graphics::{
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+};
+
+/// Vertical scaling of a `Plot`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scale {
+    /// Scale to the smallest and largest sample currently shown.
+    Auto,
+    /// Fixed range, samples outside of it are clamped.
+    Fixed { min: i32, max: i32 },
+}
+
+/// Sweeping line plot of the last `N` samples, one sample per pixel column.
+///
+/// New samples are written left to right and wrap around like on an
+/// oscilloscope, the column after the newest sample is kept blank as sweep
+/// marker. Columns stay in place, so a draw only touches the columns of the
+/// samples pushed since the last draw. The whole plot is redrawn when the
+/// range of an `Auto` scale changes.
+pub struct Plot<const N: usize> {
+    samples: [i32; N],
+    len: usize,
+    /// Index the next sample is written to.
+    head: usize,
+    /// Samples pushed since the last draw.
+    pending: usize,
+    bounds: Rectangle,
+    scale: Scale,
+    line: Rgb565,
+    background: Rgb565,
+    /// Range used at the last draw, `None` before the first draw.
+    drawn_range: Option<(i32, i32)>,
+}
+
+impl<const N: usize> Plot<N> {
+    /// Creates an empty plot, `bounds` should be `N` pixels wide.
+    #[must_use]
+    pub fn new(bounds: Rectangle, scale: Scale, line: Rgb565, background: Rgb565) -> Self {
+        Self {
+            samples: [0; N],
+            len: 0,
+            head: 0,
+            pending: 0,
+            bounds,
+            scale,
+            line,
+            background,
+            drawn_range: None,
+        }
+    }
+
+    /// Appends a sample, overwriting the oldest one when the plot is full.
+    pub fn push(&mut self, value: i32) {
+        if N == 0 {
+            return;
+        }
+        self.samples[self.head] = value;
+        self.head = (self.head + 1) % N;
+        self.len = (self.len + 1).min(N);
+        self.pending = (self.pending + 1).min(N);
+    }
+
+    /// Removes all samples, the next draw clears the plot.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.head = 0;
+        self.pending = 0;
+        self.drawn_range = None;
+    }
+
+    /// Changes the scaling, the next draw redraws the whole plot.
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+        self.drawn_range = None;
+    }
+
+    /// Area covered by the plot.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Draws the columns of new samples, or the whole plot if the range changed.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let range = self.range();
+        let columns = if self.drawn_range == Some(range) {
+            self.pending
+        } else {
+            self.bounds
+                .into_styled(PrimitiveStyle::with_fill(self.background))
+                .draw(target)?;
+            self.len
+        };
+        for age in (1..=columns).rev() {
+            let index = (self.head + N - age) % N;
+            self.draw_column(target, index, range)?;
+        }
+        if self.len > 0 {
+            self.clear_column(target, self.head)?;
+        }
+        self.pending = 0;
+        self.drawn_range = Some(range);
+        Ok(())
+    }
+
+    fn draw_column<D>(
+        &self,
+        target: &mut D,
+        index: usize,
+        range: (i32, i32),
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.clear_column(target, index)?;
+        let y = self.y(self.samples[index], range);
+        // Connect to the previous sample unless this is the oldest one.
+        let oldest = (self.head + N - self.len) % N;
+        let prev_y = if index == oldest {
+            y
+        } else {
+            self.y(self.samples[(index + N - 1) % N], range)
+        };
+        let x = self.bounds.top_left.x + index as i32;
+        Line::new(Point::new(x, prev_y), Point::new(x, y))
+            .into_styled(PrimitiveStyle::with_stroke(self.line, 1))
+            .draw(target)
+    }
+
+    fn clear_column<D>(&self, target: &mut D, index: usize) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let top_left = self.bounds.top_left + Point::new(index as i32, 0);
+        Rectangle::new(top_left, Size::new(1, self.bounds.size.height))
+            .into_styled(PrimitiveStyle::with_fill(self.background))
+            .draw(target)
+    }
+
+    /// Returns the `(min, max)` range to plot, `min < max`.
+    fn range(&self) -> (i32, i32) {
+        let (min, max) = match self.scale {
+            Scale::Fixed { min, max } => (min, max),
+            Scale::Auto => {
+                let shown = self.samples.iter().take(self.len);
+                let min = shown.clone().copied().min().unwrap_or(0);
+                let max = shown.copied().max().unwrap_or(0);
+                (min, max)
+            }
+        };
+        (min, max.max(min.saturating_add(1)))
+    }
+
+    /// Maps a sample to a screen row, larger values are further up.
+    fn y(&self, value: i32, (min, max): (i32, i32)) -> i32 {
+        let height = i64::from(self.bounds.size.height.saturating_sub(1));
+        let value = i64::from(value.clamp(min, max));
+        let offset = (value - i64::from(min)) * height / (i64::from(max) - i64::from(min));
+        self.bounds.top_left.y + (height - offset) as i32
+    }
+}
@@ -0,0 +1,146 @@
+use core::fmt::Write;
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use heapless::String;
+
+/// Horizontal progress bar from 0 to 100 percent.
+///
+/// After the first draw only the part of the bar between the previously drawn
+/// and the current value is redrawn, plus the percentage text if enabled.
+pub struct ProgressBar {
+    bounds: Rectangle,
+    fill: Rgb565,
+    track: Rgb565,
+    border: Option<Rgb565>,
+    text_style: Option<MonoTextStyle<'static, Rgb565>>,
+    value: u8,
+    /// Filled width in pixels at the last draw, `None` before the first draw.
+    drawn: Option<u32>,
+}
+
+impl ProgressBar {
+    /// Creates an empty bar drawn with `fill` for the done part and `track` for the rest.
+    #[must_use]
+    pub fn new(bounds: Rectangle, fill: Rgb565, track: Rgb565) -> Self {
+        Self {
+            bounds,
+            fill,
+            track,
+            border: None,
+            text_style: None,
+            value: 0,
+            drawn: None,
+        }
+    }
+
+    /// Draws a one pixel border around the bar.
+    #[must_use]
+    pub fn with_border(mut self, color: Rgb565) -> Self {
+        self.border = Some(color);
+        self
+    }
+
+    /// Shows the value as percentage text centered on the bar.
+    ///
+    /// The text is drawn without background, use a color readable on both the
+    /// fill and the track color.
+    #[must_use]
+    pub fn with_text(mut self, style: MonoTextStyle<'static, Rgb565>) -> Self {
+        self.text_style = Some(style);
+        self
+    }
+
+    /// Sets the progress in percent, values above 100 are clamped.
+    pub fn set_value(&mut self, percent: u8) {
+        self.value = percent.min(100);
+    }
+
+    /// Returns the progress in percent.
+    #[must_use]
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Area covered by the bar including its border.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Draws the bar, after the first draw only the changed part.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let inner = self.inner();
+        let filled = inner.size.width * u32::from(self.value) / 100;
+        match self.drawn {
+            Some(drawn) if drawn == filled => return Ok(()),
+            Some(drawn) => self.draw_span(target, drawn.min(filled), drawn.max(filled))?,
+            None => {
+                if let Some(border) = self.border {
+                    self.bounds
+                        .into_styled(PrimitiveStyle::with_stroke(border, 1))
+                        .draw(target)?;
+                }
+                self.draw_span(target, 0, inner.size.width)?;
+            }
+        }
+        if let Some(style) = self.text_style {
+            let area = self.text("100%", style).bounding_box();
+            let left = (area.top_left.x - inner.top_left.x).max(0) as u32;
+            self.draw_span(target, left, left + area.size.width)?;
+            let mut percent: String<4> = String::new();
+            write!(percent, "{}%", self.value).ok();
+            self.text(&percent, style).draw(target)?;
+        }
+        self.drawn = Some(filled);
+        Ok(())
+    }
+
+    /// Redraws the columns `start..end` of the inner area with fill or track color.
+    fn draw_span<D>(&self, target: &mut D, start: u32, end: u32) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        let inner = self.inner();
+        let filled = inner.size.width * u32::from(self.value) / 100;
+        let end = end.min(inner.size.width);
+        let split = filled.clamp(start, end);
+        for (from, to, color) in [(start, split, self.fill), (split, end, self.track)] {
+            if to > from {
+                let top_left = inner.top_left + Point::new(from as i32, 0);
+                Rectangle::new(top_left, Size::new(to - from, inner.size.height))
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn text<'t>(
+        &self,
+        text: &'t str,
+        style: MonoTextStyle<'static, Rgb565>,
+    ) -> Text<'t, MonoTextStyle<'static, Rgb565>> {
+        let text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        Text::with_text_style(text, self.inner().center(), style, text_style)
+    }
+
+    fn inner(&self) -> Rectangle {
+        if self.border.is_some() {
+            self.bounds.offset(-1)
+        } else {
+            self.bounds
+        }
+    }
+}
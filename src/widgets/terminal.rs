@@ -0,0 +1,187 @@
+use core::fmt;
+
+use embedded_graphics::{
+    mono_font::MonoFont, pixelcolor::Rgb565, prelude::*, primitives::Rectangle,
+};
+
+use super::Console;
+
+/// Maximum number of numeric parameters of a control sequence.
+const MAX_PARAMS: usize = 4;
+
+/// The 8 standard ANSI colors followed by their bright variants.
+const PALETTE: [Rgb565; 16] = [
+    Rgb565::new(0, 0, 0),
+    Rgb565::new(21, 0, 0),
+    Rgb565::new(0, 42, 0),
+    Rgb565::new(21, 42, 0),
+    Rgb565::new(0, 0, 21),
+    Rgb565::new(21, 0, 21),
+    Rgb565::new(0, 42, 21),
+    Rgb565::new(21, 42, 21),
+    Rgb565::new(10, 21, 10),
+    Rgb565::new(31, 21, 10),
+    Rgb565::new(10, 63, 10),
+    Rgb565::new(31, 63, 10),
+    Rgb565::new(10, 21, 31),
+    Rgb565::new(31, 21, 31),
+    Rgb565::new(10, 63, 31),
+    Rgb565::new(31, 63, 31),
+];
+
+enum State {
+    Ground,
+    Escape,
+    Csi {
+        params: [u16; MAX_PARAMS],
+        count: usize,
+    },
+}
+
+/// Colored terminal on top of a `Console`, interpreting a subset of VT100/ANSI escapes.
+///
+/// Supported control sequences:
+/// - `ESC[<n>m` select graphic rendition: reset (0), foreground (30-37, 90-97,
+///   39 for default) and background (40-47, 100-107, 49 for default)
+/// - `ESC[<n>A`, `B`, `C`, `D` cursor up, down, forward and back
+/// - `ESC[<row>;<col>H` and `f` cursor position, 1 based
+/// - `ESC[<n>J` erase below (0) or the whole display (2)
+/// - `ESC[K` erase to the end of the line
+///
+/// Other sequences are parsed and ignored.
+pub struct Terminal<const COLS: usize, const ROWS: usize> {
+    console: Console<COLS, ROWS>,
+    default_fg: Rgb565,
+    default_bg: Rgb565,
+    state: State,
+}
+
+impl<const COLS: usize, const ROWS: usize> Terminal<COLS, ROWS> {
+    /// Creates an empty terminal with its top left corner at `origin` and the given default colors.
+    #[must_use]
+    pub fn new(origin: Point, font: &'static MonoFont<'static>, fg: Rgb565, bg: Rgb565) -> Self {
+        Self {
+            console: Console::new(origin, font, fg, bg),
+            default_fg: fg,
+            default_bg: bg,
+            state: State::Ground,
+        }
+    }
+
+    /// Returns the underlying console.
+    pub fn console(&self) -> &Console<COLS, ROWS> {
+        &self.console
+    }
+
+    /// Returns the underlying console, e.g. to show the cursor.
+    pub fn console_mut(&mut self) -> &mut Console<COLS, ROWS> {
+        &mut self.console
+    }
+
+    /// Feeds a character of the output stream.
+    pub fn put_char(&mut self, c: char) {
+        self.state = match core::mem::replace(&mut self.state, State::Ground) {
+            State::Ground if c == '\x1b' => State::Escape,
+            State::Ground => {
+                self.console.put_char(c);
+                State::Ground
+            }
+            State::Escape if c == '[' => State::Csi {
+                params: [0; MAX_PARAMS],
+                count: 0,
+            },
+            State::Escape => State::Ground,
+            State::Csi {
+                mut params,
+                mut count,
+            } => match c {
+                '0'..='9' => {
+                    let digit = c as u16 - u16::from(b'0');
+                    if let Some(param) = params.get_mut(count) {
+                        *param = param.saturating_mul(10).saturating_add(digit);
+                    }
+                    State::Csi { params, count }
+                }
+                ';' => {
+                    count += 1;
+                    State::Csi { params, count }
+                }
+                // Private mode prefixes and intermediate bytes
+                '?' | ' '..='/' => State::Csi { params, count },
+                '@'..='~' => {
+                    let params = &params[..(count + 1).min(MAX_PARAMS)];
+                    self.execute(c, params);
+                    State::Ground
+                }
+                _ => State::Ground,
+            },
+        };
+    }
+
+    /// Area covered by the terminal.
+    #[must_use]
+    pub fn bounding_box(&self) -> Rectangle {
+        self.console.bounding_box()
+    }
+
+    /// Draws the rows changed since the last draw and returns their area.
+    pub fn draw<D>(&mut self, target: &mut D) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = Rgb565>,
+    {
+        self.console.draw(target)
+    }
+
+    fn execute(&mut self, command: char, params: &[u16]) {
+        let first = params[0];
+        let count = usize::from(first.max(1));
+        let (col, row) = self.console.cursor();
+        match command {
+            'A' => self.console.set_cursor(col, row.saturating_sub(count)),
+            'B' => self.console.set_cursor(col, row + count),
+            'C' => self.console.set_cursor(col + count, row),
+            'D' => self.console.set_cursor(col.saturating_sub(count), row),
+            'H' | 'f' => {
+                let row = usize::from(first.max(1)) - 1;
+                let col = usize::from(params.get(1).copied().unwrap_or(0).max(1)) - 1;
+                self.console.set_cursor(col, row);
+            }
+            'J' => match first {
+                0 => self.console.clear_to_end(),
+                2 => {
+                    let cursor = self.console.cursor();
+                    self.console.clear();
+                    self.console.set_cursor(cursor.0, cursor.1);
+                }
+                _ => {}
+            },
+            'K' if first == 0 => self.console.clear_to_end_of_line(),
+            'm' => params
+                .iter()
+                .for_each(|&param| self.select_rendition(param)),
+            _ => {}
+        }
+    }
+
+    fn select_rendition(&mut self, param: u16) {
+        let (fg, bg) = self.console.colors();
+        let (fg, bg) = match param {
+            0 => (self.default_fg, self.default_bg),
+            30..=37 => (PALETTE[usize::from(param - 30)], bg),
+            39 => (self.default_fg, bg),
+            40..=47 => (fg, PALETTE[usize::from(param - 40)]),
+            49 => (fg, self.default_bg),
+            90..=97 => (PALETTE[usize::from(param - 90 + 8)], bg),
+            100..=107 => (fg, PALETTE[usize::from(param - 100 + 8)]),
+            _ => (fg, bg),
+        };
+        self.console.set_colors(fg, bg);
+    }
+}
+
+impl<const COLS: usize, const ROWS: usize> fmt::Write for Terminal<COLS, ROWS> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().for_each(|c| self.put_char(c));
+        Ok(())
+    }
+}